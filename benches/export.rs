@@ -0,0 +1,41 @@
+use claude::{ export::{ write_conversation, MarkdownExporter }, ChatMessage, Conversation };
+use criterion::{ black_box, criterion_group, criterion_main, Criterion };
+
+fn conversation(messages: usize) -> (Conversation, Vec<ChatMessage>) {
+    let conversation = serde_json::from_value(
+        serde_json::json!({
+            "uuid": "e56a5ab3-0eca-4a04-9c63-3fadaf14cd17",
+            "name": "Benchmark",
+            "summary": "A large conversation used to benchmark exports.",
+        })
+    ).unwrap();
+    let messages = (0..messages)
+        .map(|i| {
+            serde_json::from_value(
+                serde_json::json!({
+                    "uuid": format!("00000000-0000-0000-0000-{:012}", i),
+                    "attachments": [],
+                    "sender": if i % 2 == 0 { "human" } else { "assistant" },
+                    "index": i,
+                    "text": "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(40),
+                    "chat_feedback": null,
+                })
+            ).unwrap()
+        })
+        .collect();
+    (conversation, messages)
+}
+
+fn markdown_export(c: &mut Criterion) {
+    let (conversation, messages) = conversation(1_000);
+    c.bench_function("export/markdown", |b| {
+        b.iter(|| {
+            let mut exporter = MarkdownExporter::new(Vec::new());
+            write_conversation(&mut exporter, black_box(&conversation), black_box(&messages)).unwrap();
+            exporter.into_inner()
+        })
+    });
+}
+
+criterion_group!(benches, markdown_export);
+criterion_main!(benches);
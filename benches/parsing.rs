@@ -0,0 +1,57 @@
+use claude::{ sse::parse_completions, ChatMessage };
+use criterion::{ black_box, criterion_group, criterion_main, Criterion, Throughput };
+
+fn completion_stream(events: usize) -> String {
+    (0..events)
+        .map(|i| {
+            format!(
+                "data: {}\n\n",
+                serde_json::json!({
+                    "completion": format!(" token{}", i),
+                    "stop_reason": null,
+                    "model": "claude-2.0",
+                    "stop": null,
+                    "log_id": "6c1fd1ab0c5f4b0e",
+                    "messageLimit": { "type": "within_limit" },
+                })
+            )
+        })
+        .collect()
+}
+
+fn history(messages: usize) -> String {
+    let messages: Vec<_> = (0..messages)
+        .map(|i| {
+            serde_json::json!({
+                "uuid": format!("00000000-0000-0000-0000-{:012}", i),
+                "attachments": [],
+                "sender": if i % 2 == 0 { "human" } else { "assistant" },
+                "index": i,
+                "text": "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(20),
+                "chat_feedback": null,
+            })
+        })
+        .collect();
+    serde_json::to_string(&messages).unwrap()
+}
+
+fn sse_parsing(c: &mut Criterion) {
+    let body = completion_stream(2_000);
+    let mut group = c.benchmark_group("sse");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+    group.bench_function("parse_completions", |b| b.iter(|| parse_completions(black_box(&body))));
+    group.finish();
+}
+
+fn history_deserialization(c: &mut Criterion) {
+    let body = history(500);
+    let mut group = c.benchmark_group("history");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+    group.bench_function("deserialize", |b| {
+        b.iter(|| serde_json::from_str::<Vec<ChatMessage>>(black_box(&body)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sse_parsing, history_deserialization);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+//! Compares the responses of claude.ai with the fields the crate knows, exiting with an error when a change
+//! breaks the crate:
+//!
+//! ```sh
+//! cargo run --example check_schema -- [conversation uuid]
+//! ```
+use claude::Client;
+use std::env::{ args, var };
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+    let cookies = format!(
+        "activitySessionId={}; sessionKey={}",
+        var("SESSION_ID").unwrap(),
+        var("SESSION_KEY").unwrap()
+    );
+    let chat_uuid = args().nth(1);
+
+    let client = Client::new(cookies).await;
+    let report = client.check_schema(chat_uuid.as_deref()).await;
+    print!("{}", report);
+    if report.is_breaking() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,15 @@
+use claude::Client;
+use std::env::var;
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+    tracing_subscriber::fmt().json().with_max_level(tracing::Level::DEBUG).init();
+    let cookies = format!(
+        "activitySessionId={}; sessionKey={}",
+        var("SESSION_ID").unwrap(),
+        var("SESSION_KEY").unwrap()
+    );
+    let client = Client::new(cookies).await;
+    client.list_all_conversations().await.unwrap();
+}
@@ -0,0 +1,35 @@
+//! Streams an answer as JSON lines on stdout, for programs in other languages to run as a subprocess:
+//!
+//! ```sh
+//! cargo run --example jsonl -- <conversation uuid> "Tell me a story"
+//! ```
+use claude::{ sse::{ json_lines, JsonLine }, Client };
+use futures_util::{ pin_mut, StreamExt };
+use std::{ env::{ args, var }, io::Write };
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+    let cookies = format!(
+        "activitySessionId={}; sessionKey={}",
+        var("SESSION_ID").unwrap(),
+        var("SESSION_KEY").unwrap()
+    );
+    let mut args = args().skip(1);
+    let chat_uuid = args.next().expect("missing conversation uuid");
+    let prompt = args.next().expect("missing prompt");
+
+    let client = Client::new(cookies).await;
+    let mut stdout = std::io::stdout().lock();
+    match client.send_message_stream(&chat_uuid, &prompt, None, None).await {
+        Ok(events) => {
+            let lines = json_lines(events);
+            pin_mut!(lines);
+            while let Some(line) = lines.next().await {
+                stdout.write_all(line.to_line().as_bytes()).unwrap();
+                stdout.flush().unwrap();
+            }
+        }
+        Err(e) => stdout.write_all(JsonLine::error(&e).to_line().as_bytes()).unwrap(),
+    }
+}
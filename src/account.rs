@@ -0,0 +1,261 @@
+use chrono::{ DateTime, Utc };
+use serde::{ Deserialize, Serialize };
+use serde_json::{ Map, Value };
+use std::time::Duration;
+
+use crate::{ Client, Context, Result };
+
+/// The authenticated user and the plan of the organization the client uses, returned by
+/// `Client::account_info`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccountInfo {
+    pub uuid: String,
+    pub email_address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_name: Option<String>,
+    /// The name claude.ai greets the user with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub plan: Plan,
+    /// The capabilities of the organization, such as `chat` or `claude_pro`, which the plan is derived from.
+    pub capabilities: Vec<String>,
+    /// The rate limit tier of the organization, such as `default_claude_ai`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit_tier: Option<String>,
+}
+
+/// The subscription plan of an organization, which sets the models available and the usage limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Plan {
+    Free,
+    Pro,
+    Max,
+    Team,
+    Enterprise,
+}
+
+impl Plan {
+    /// Returns the plan granted by the capabilities and rate limit tier of an organization.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use claude::account::Plan;
+    ///
+    /// assert_eq!(Plan::from_capabilities(&["chat"], None), Plan::Free);
+    /// assert_eq!(Plan::from_capabilities(&["chat", "claude_pro"], Some("default_claude_ai")), Plan::Pro);
+    /// assert_eq!(Plan::from_capabilities(&["chat", "raven"], Some("default_raven")), Plan::Team);
+    /// ```
+    pub fn from_capabilities(capabilities: &[impl AsRef<str>], rate_limit_tier: Option<&str>) -> Self {
+        let has = |capability: &str| capabilities.iter().any(|c| c.as_ref() == capability);
+        if has("raven") {
+            // Team and Enterprise organizations share the `raven` capability and differ by rate limit tier.
+            if rate_limit_tier.is_some_and(|tier| tier.contains("enterprise")) { Plan::Enterprise } else { Plan::Team }
+        } else if has("claude_max") {
+            Plan::Max
+        } else if has("claude_pro") {
+            Plan::Pro
+        } else {
+            Plan::Free
+        }
+    }
+
+    /// Returns whether the plan is paid for.
+    pub fn is_paid(&self) -> bool {
+        *self != Plan::Free
+    }
+}
+
+/// How much of its usage limits the organization has used, returned by `Client::usage`.
+///
+/// claude.ai limits usage over a rolling five-hour window and, on paid plans, over a week, with a separate
+/// weekly limit for the largest models. A window the plan does not have is `None`.
+///
+/// # Examples
+///
+/// ```
+/// use claude::account::UsageStatus;
+///
+/// let usage: UsageStatus = serde_json::from_value(serde_json::json!({
+///     "five_hour": { "utilization": 100.0, "resets_at": "2099-01-01T12:00:00Z" },
+///     "seven_day": { "utilization": 40.0, "resets_at": "2099-01-05T00:00:00Z" },
+///     "seven_day_opus": null,
+/// })).unwrap();
+/// assert!(usage.is_exhausted());
+/// assert!(usage.wait_time().unwrap().as_secs() > 0);
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct UsageStatus {
+    #[serde(default)]
+    pub five_hour: Option<UsageWindow>,
+    #[serde(default)]
+    pub seven_day: Option<UsageWindow>,
+    /// The weekly limit of the Opus models.
+    #[serde(default)]
+    pub seven_day_opus: Option<UsageWindow>,
+    /// Any other window, kept so that limits added by claude.ai are not lost.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The usage of one limit of `UsageStatus`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct UsageWindow {
+    /// The share of the limit used, in percent.
+    pub utilization: f64,
+    /// When the window resets, if anything was used in it.
+    #[serde(default)]
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+impl UsageWindow {
+    /// Returns whether the limit is reached.
+    pub fn is_exhausted(&self) -> bool {
+        self.utilization >= 100.0
+    }
+}
+
+impl UsageStatus {
+    /// Returns the windows of the plan.
+    pub fn windows(&self) -> impl Iterator<Item = &UsageWindow> {
+        [&self.five_hour, &self.seven_day].into_iter().flatten()
+    }
+
+    /// Returns whether a limit applying to every model is reached, so that no message can be sent until it resets.
+    pub fn is_exhausted(&self) -> bool {
+        self.windows().any(UsageWindow::is_exhausted)
+    }
+
+    /// Returns how long to wait until every exhausted limit applying to every model has reset, or `None` when
+    /// none is exhausted.
+    pub fn wait_time(&self) -> Option<Duration> {
+        let resets_at = self.windows().filter(|window| window.is_exhausted()).filter_map(|window| window.resets_at).max()?;
+        Some((resets_at - Utc::now()).to_std().unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Account {
+    uuid: String,
+    email_address: String,
+    #[serde(default)]
+    full_name: Option<String>,
+    #[serde(default)]
+    display_name: Option<String>,
+    #[serde(default)]
+    memberships: Vec<Membership>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Membership {
+    organization: MemberOrganization,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemberOrganization {
+    uuid: String,
+    #[serde(default)]
+    capabilities: Vec<String>,
+    #[serde(default)]
+    rate_limit_tier: Option<String>,
+}
+
+impl Client {
+    /// Retrieves the profile of the authenticated user and the plan of the organization the client uses.
+    ///
+    /// The plan tells which models are available and how generous the limits are, for example to pick a
+    /// cheaper model on the free plan.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<AccountInfo>` - The account and its plan, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ Client, Model };
+    /// # async fn example(mut client: Client) -> claude::Result<()> {
+    /// let account = client.account_info().await?;
+    /// println!("signed in as {} on the {:?} plan", account.email_address, account.plan);
+    /// if !account.plan.is_paid() {
+    ///     client.default_model = Model::Claude3Haiku;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn account_info(&self) -> Result<AccountInfo> {
+        let url = format!("{}/api/account", self.base_url);
+
+        let request = self.http.get(url);
+        let context = || "while fetching the account";
+        let account: Account = self.dispatch("account_info", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("account_info", None, &account);
+
+        // The organization the client uses, or the first one if the account has since left it.
+        let organizations = || account.memberships.iter().map(|membership| &membership.organization);
+        let organization = organizations().find(|organization| organization.uuid == self.org_uuid).or(organizations().next());
+        let capabilities = organization.map(|organization| organization.capabilities.clone()).unwrap_or_default();
+        let rate_limit_tier = organization.and_then(|organization| organization.rate_limit_tier.clone());
+
+        Ok(AccountInfo {
+            uuid: account.uuid,
+            email_address: account.email_address,
+            full_name: account.full_name,
+            display_name: account.display_name,
+            plan: Plan::from_capabilities(&capabilities, rate_limit_tier.as_deref()),
+            capabilities,
+            rate_limit_tier,
+        })
+    }
+
+    /// Retrieves how much of its usage limits the organization has used, and when they reset.
+    ///
+    /// Schedulers can check `UsageStatus::wait_time` before sending, rather than waiting for
+    /// `Error::RateLimited`, whose `Error::retry_after` tells the same once the limit is hit.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<UsageStatus>` - The usage of each limit, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// if let Some(wait) = client.usage().await?.wait_time() {
+    ///     tokio::time::sleep(wait).await;
+    /// }
+    /// let answer = client.send_message("chat_uuid", "Hello", None, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn usage(&self) -> Result<UsageStatus> {
+        let url = format!("{}/api/organizations/{}/usage", self.base_url, self.org_uuid);
+
+        let request = self.http.get(url);
+        let context = || "while fetching the usage limits";
+        let res: UsageStatus = self.dispatch("usage", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("usage", None, &res);
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,320 @@
+//! A client for the official Anthropic Messages API, mirroring the conversation API of the web `Client`.
+//!
+//! The Messages API is stateless, so `ApiClient` keeps conversations in memory and sends the whole history
+//! with every message. Conversations and messages use the same `Conversation` and `ChatMessage` types as the
+//! web client, which makes switching a prototype from claude.ai cookies to an API key a matter of changing
+//! the constructor.
+
+use reqwest::header::{ HeaderMap, HeaderValue, CONTENT_TYPE };
+use serde::Deserialize;
+use serde_json::{ json, Map, Value };
+use std::{ collections::HashMap, path::Path, sync::Mutex };
+use tracing::{ debug, info };
+
+use crate::{
+    builder::HttpOptions,
+    error_for_status,
+    execute,
+    utils,
+    ChatMessage,
+    Completion,
+    Context,
+    Conversation,
+    Error,
+    Model,
+    Result,
+    Sender,
+};
+
+/// The origin of the Messages API, unless overridden with `ApiClient::with_base_url`.
+pub const DEFAULT_API_BASE_URL: &str = "https://api.anthropic.com";
+
+/// The API version requested with the `anthropic-version` header.
+const API_VERSION: &str = "2023-06-01";
+
+/// A client for the official Messages API, authenticated with an API key.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::api::ApiClient;
+/// # async fn example() -> claude::Result<()> {
+/// let client = ApiClient::new(&std::env::var("ANTHROPIC_API_KEY").unwrap())?;
+/// let chat = client.create_new_chat();
+/// let answer = client.send_message(&chat.uuid, "What is Rust?", None, None).await?;
+/// println!("{}", answer);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    headers: HeaderMap,
+    base_url: String,
+    /// The model messages are sent to, unless overridden per call with `send_message_with_model`.
+    pub default_model: Model,
+    /// Maximum number of tokens of each answer.
+    pub max_tokens: u32,
+    /// A system prompt sent with every message.
+    pub system: Option<String>,
+    conversations: Mutex<HashMap<String, (Conversation, Vec<ChatMessage>)>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+    model: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentBlock {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+impl ApiClient {
+    /// Creates a client authenticated with `api_key`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key is not a valid header value or the HTTP client cannot be
+    /// built.
+    pub fn new(api_key: &str) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        let mut key = HeaderValue::from_str(api_key)?;
+        key.set_sensitive(true);
+        headers.insert("x-api-key", key);
+        headers.insert("anthropic-version", HeaderValue::from_static(API_VERSION));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let http = crate::build_client(headers.clone(), &HttpOptions { https_only: true, ..HttpOptions::default() })?;
+        Ok(Self {
+            http,
+            headers,
+            base_url: DEFAULT_API_BASE_URL.to_string(),
+            default_model: Model::Claude35Sonnet,
+            max_tokens: 4096,
+            system: None,
+            conversations: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Sends requests to `url` instead of `https://api.anthropic.com`, for example a proxy or a mock server.
+    ///
+    /// Plain `http://` URLs are allowed here, while the default only ever connects over HTTPS.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the HTTP client cannot be rebuilt.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Result<Self> {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        let https_only = !self.base_url.starts_with("http://");
+        self.http = crate::build_client(self.headers.clone(), &HttpOptions { https_only, ..HttpOptions::default() })?;
+        Ok(self)
+    }
+
+    /// Starts a new, empty conversation. Nothing is sent until the first message.
+    pub fn create_new_chat(&self) -> Conversation {
+        let conversation = Conversation {
+            uuid: uuid::Uuid::new_v4().to_string(),
+            name: String::new(),
+            summary: None,
+            starred: false,
+            project_uuid: None,
+            created_at: Some(chrono::Utc::now()),
+            updated_at: Some(chrono::Utc::now()),
+            model: None,
+            settings: None,
+            extra: Map::new(),
+        };
+        self.conversations
+            .lock()
+            .unwrap()
+            .insert(conversation.uuid.clone(), (conversation.clone(), vec![]));
+        info!(target: "claude::audit", event = "conversation.created", conversation_id = %conversation.uuid);
+        conversation
+    }
+
+    /// Lists the conversations of this client.
+    pub fn list_all_conversations(&self) -> Vec<Conversation> {
+        self.conversations
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(conversation, _)| conversation.clone())
+            .collect()
+    }
+
+    /// Returns the messages of a conversation, in order.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::ConversationNotFound` if the conversation does not exist.
+    pub fn chat_conversation_history(&self, chat_uuid: &str) -> Result<Vec<ChatMessage>> {
+        let conversations = self.conversations.lock().unwrap();
+        match conversations.get(chat_uuid) {
+            Some((_, messages)) => Ok(messages.clone()),
+            None => Err(Error::ConversationNotFound(chat_uuid.to_string())),
+        }
+    }
+
+    /// Renames a conversation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::ConversationNotFound` if the conversation does not exist.
+    pub fn rename_chat(&self, chat_uuid: &str, title: &str) -> Result<()> {
+        let mut conversations = self.conversations.lock().unwrap();
+        match conversations.get_mut(chat_uuid) {
+            Some((conversation, _)) => {
+                conversation.name = title.to_string();
+                info!(target: "claude::audit", event = "conversation.renamed", conversation_id = chat_uuid);
+                Ok(())
+            }
+            None => Err(Error::ConversationNotFound(chat_uuid.to_string())),
+        }
+    }
+
+    /// Deletes a conversation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::ConversationNotFound` if the conversation does not exist.
+    pub fn delete_conversation(&self, chat_uuid: &str) -> Result<()> {
+        match self.conversations.lock().unwrap().remove(chat_uuid) {
+            Some(_) => {
+                info!(target: "claude::audit", event = "conversation.deleted", conversation_id = chat_uuid);
+                Ok(())
+            }
+            None => Err(Error::ConversationNotFound(chat_uuid.to_string())),
+        }
+    }
+
+    /// Sends a message to a conversation and returns the answer.
+    ///
+    /// The text of each attached file is sent along with the prompt, as the web client does once claude.ai
+    /// has converted the document.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - The UUID of the conversation, as returned by `create_new_chat`.
+    /// * `prompt` - A string representing the message to be sent.
+    /// * `attachments` - An optional vector of paths to text files to attach.
+    /// * `timeout` - An optional timeout in seconds for the request. Defaults to 500 seconds.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversation does not exist, an attachment cannot be read,
+    /// the request fails or the API returns an error.
+    pub async fn send_message(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>
+    ) -> Result<String> {
+        let model = self.default_model.clone();
+        Ok(self.send_message_with_model(chat_uuid, prompt, attachments, timeout, model).await?.text)
+    }
+
+    /// Sends a message to a conversation with a specific model.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversation does not exist, an attachment cannot be read,
+    /// the request fails or the API returns an error. `Error::PromptTooLong` is returned without sending
+    /// anything when the conversation is estimated to leave less than `max_tokens` of the context window.
+    pub async fn send_message_with_model(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>,
+        model: impl Into<Model>
+    ) -> Result<Completion> {
+        let context = || format!("while sending a message to conversation {}", chat_uuid);
+        let model = model.into();
+
+        let mut text = String::new();
+        for path in attachments.unwrap_or_default() {
+            let contents = tokio::fs::read_to_string(path).await.with_context(context)?;
+            let name = utils::normalize_file_name(&Path::new(path).to_string_lossy());
+            text.push_str(&format!("<document name=\"{}\">\n{}\n</document>\n\n", name, contents));
+        }
+        text.push_str(prompt);
+
+        let mut messages = self.chat_conversation_history(chat_uuid).with_context(context)?;
+        messages.push(new_message(Sender::Human, messages.len(), text));
+
+        if let Some(window) = model.context_window() {
+            // The answer shares the context window with the conversation.
+            let limit = window.saturating_sub(self.max_tokens as usize);
+            let estimated = messages
+                .iter()
+                .map(|m| &m.text)
+                .chain(&self.system)
+                .map(|text| utils::estimate_tokens(text))
+                .sum();
+            if estimated > limit {
+                return Err(Error::PromptTooLong { estimated, limit }).with_context(context);
+            }
+        }
+
+        let mut payload =
+            json!({
+            "model": model.id(),
+            "max_tokens": self.max_tokens,
+            "messages": messages
+                .iter()
+                .map(|m| json!({ "role": role(&m.sender), "content": m.text }))
+                .collect::<Vec<Value>>(),
+        });
+        if let Some(system) = &self.system {
+            payload["system"] = system.as_str().into();
+        }
+
+        let url = format!("{}/v1/messages", self.base_url);
+        let request = self.http
+            .post(url)
+            .timeout(std::time::Duration::from_secs(timeout.unwrap_or(500)))
+            .json(&payload);
+        let res = execute("send_message", Some(chat_uuid), request, None).await.with_context(context)?;
+        info!(target: "claude::audit", event = "message.sent", conversation_id = chat_uuid, model = model.id());
+        let res = error_for_status(res).await.with_context(context)?;
+        let res: MessagesResponse = res.json().await.with_context(context)?;
+        let answer: String = res.content.into_iter().filter_map(|block| block.text).collect();
+        if answer.is_empty() {
+            return Err(Error::EmptyResponse).with_context(context);
+        }
+        debug!(operation = "send_message", conversation_id = chat_uuid, model = res.model, "answer received");
+        info!(target: "claude::audit", event = "message.received", conversation_id = chat_uuid, model = res.model);
+
+        let mut conversations = self.conversations.lock().unwrap();
+        if let Some((_, history)) = conversations.get_mut(chat_uuid) {
+            let prompt = messages.pop().expect("the prompt was just pushed");
+            history.push(new_message(Sender::Human, history.len(), prompt.text));
+            history.push(new_message(Sender::Assistant, history.len(), answer.clone()));
+        }
+
+        Ok(Completion { text: answer, model: res.model, truncated: vec![] })
+    }
+}
+
+/// Maps the sender of a web conversation message to a Messages API role.
+fn role(sender: &Sender) -> &'static str {
+    if *sender == Sender::Assistant { "assistant" } else { "user" }
+}
+
+fn new_message(sender: Sender, index: usize, text: String) -> ChatMessage {
+    ChatMessage {
+        uuid: uuid::Uuid::new_v4().to_string(),
+        attachments: vec![],
+        sender,
+        index,
+        text,
+        chat_feedback: None,
+        created_at: Some(chrono::Utc::now()),
+        updated_at: None,
+        parent_message_uuid: None,
+        extra: Map::new(),
+    }
+}
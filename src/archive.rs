@@ -0,0 +1,289 @@
+use std::{ collections::HashMap, fs, path::{ Path, PathBuf } };
+use serde::Serialize;
+use tracing::debug;
+
+use crate::{
+    similarity::word_counts,
+    snapshot::ConversationSnapshot,
+    topics::TopicReport,
+    ChatMessage,
+    Client,
+    Context,
+    Conversation,
+    Error,
+    Result,
+};
+
+/// Maximum number of archived messages given as context to `Client::ask_archive`.
+const MAX_EXCERPTS: usize = 8;
+/// Maximum number of characters of each archived message given as context to `Client::ask_archive`.
+const MAX_EXCERPT_CHARS: usize = 2_000;
+
+/// A local copy of the conversations of the account, searchable by keywords.
+///
+/// Each conversation is kept as a `ConversationSnapshot` in `conversations/<uuid>.json`, and brought up to date
+/// with `Client::sync_archive`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::{ archive::ChatArchive, Client };
+/// # async fn example(client: Client) -> claude::Result<()> {
+/// let mut archive = ChatArchive::open("archive")?;
+/// client.sync_archive(&mut archive).await?;
+/// for hit in archive.search("sourdough starter", 5) {
+///     println!("{} ({:.2}): {}", hit.conversation.name, hit.score, hit.message.text);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ChatArchive {
+    root: PathBuf,
+    snapshots: HashMap<String, ConversationSnapshot>,
+}
+
+/// A message of the archive matching a search.
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveHit<'a> {
+    pub conversation: &'a Conversation,
+    pub message: &'a ChatMessage,
+    /// How well the message matches the search, higher being better.
+    pub score: f64,
+}
+
+/// The answer of `Client::ask_archive`, with the conversations it draws on.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveAnswer {
+    pub text: String,
+    /// The UUID of the conversation the question was asked in, to ask follow-up questions.
+    pub chat_uuid: String,
+    /// The archived conversations given as context, in the order they are numbered in the answer.
+    pub sources: Vec<ArchiveSource>,
+}
+
+/// An archived conversation an answer draws on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchiveSource {
+    pub conversation_uuid: String,
+    pub name: String,
+    /// The link to the conversation in the web UI.
+    pub url: String,
+}
+
+impl ChatArchive {
+    /// Opens the archive in `root`, creating the directory if needed and loading the conversations it holds.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directory cannot be created, or if a conversation cannot be
+    /// read.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        let dir = root.join("conversations");
+        fs::create_dir_all(&dir).with_context(|| format!("while opening archive {}", root.display()))?;
+
+        let mut snapshots = HashMap::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                continue;
+            }
+            let context = || format!("while reading archived conversation {}", path.display());
+            let snapshot: ConversationSnapshot = serde_json
+                ::from_slice(&fs::read(&path).with_context(context)?)
+                .with_context(context)?;
+            snapshots.insert(snapshot.conversation().uuid.clone(), snapshot);
+        }
+        debug!(operation = "open_archive", root = %root.display(), conversations = snapshots.len());
+        Ok(Self { root, snapshots })
+    }
+
+    /// Returns the directory of the archive.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the number of archived conversations.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns whether the archive holds no conversation.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Returns the archived copy of the conversation `uuid`.
+    pub fn get(&self, uuid: &str) -> Option<&ConversationSnapshot> {
+        self.snapshots.get(uuid)
+    }
+
+    /// Returns the archived conversations, in no particular order.
+    pub fn snapshots(&self) -> impl Iterator<Item = &ConversationSnapshot> {
+        self.snapshots.values()
+    }
+
+    /// Archives `snapshot`, replacing any previous copy of the same conversation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversation cannot be written.
+    pub fn insert(&mut self, snapshot: ConversationSnapshot) -> Result<()> {
+        let uuid = snapshot.conversation().uuid.clone();
+        let path = self.root.join("conversations").join(format!("{}.json", uuid));
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_vec(&snapshot)?)?;
+        fs::rename(&partial, &path).with_context(|| format!("while archiving conversation {}", uuid))?;
+        self.snapshots.insert(uuid, snapshot);
+        Ok(())
+    }
+
+    /// Groups the archived conversations by topic, as described in `TopicReport::build`.
+    pub fn topic_report(&self, threshold: f64) -> TopicReport {
+        TopicReport::build(self.snapshots.values(), threshold)
+    }
+
+    /// Returns the `limit` archived messages best matching the words of `query`, best first.
+    ///
+    /// Words are compared case-insensitively. Each message scores the number of times it uses each word of the
+    /// query, weighted by how rare the word is across the archive, so that common words count less.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<ArchiveHit<'_>> {
+        let terms: Vec<String> = word_counts(query).into_keys().collect();
+        let messages: Vec<_> = self.snapshots
+            .values()
+            .flat_map(|snapshot| snapshot.messages().iter().map(move |message| (snapshot.conversation(), message)))
+            .map(|(conversation, message)| (conversation, message, word_counts(&message.text)))
+            .collect();
+
+        let total = messages.len() as f64;
+        let weights: Vec<f64> = terms
+            .iter()
+            .map(|term| {
+                let containing = messages.iter().filter(|(_, _, words)| words.contains_key(term)).count() as f64;
+                (1.0 + total / (1.0 + containing)).ln()
+            })
+            .collect();
+
+        let mut hits: Vec<_> = messages
+            .iter()
+            .filter_map(|(conversation, message, words)| {
+                let length = words.values().sum::<usize>().max(1) as f64;
+                let score: f64 = terms
+                    .iter()
+                    .zip(&weights)
+                    .map(|(term, weight)| (words.get(term).copied().unwrap_or_default() as f64 / length).sqrt() * weight)
+                    .sum();
+                (score > 0.0).then_some(ArchiveHit { conversation, message, score })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+impl Client {
+    /// Brings an archive up to date with the conversations of the account.
+    ///
+    /// Conversations missing from the archive, or updated since they were archived, are fetched again with
+    /// `conversation_snapshot`. Conversations deleted from the account are kept.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize>` - The number of conversations archived, if the synchronization succeeds. Otherwise, an
+    ///   error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversations cannot be listed or fetched, or if the archive
+    /// cannot be written.
+    pub async fn sync_archive(&self, archive: &mut ChatArchive) -> Result<usize> {
+        let conversations = self.list_all_conversations().await.context("while synchronizing the archive")?;
+        let mut archived = 0;
+        for conversation in conversations {
+            let up_to_date = archive.get(&conversation.uuid).is_some_and(|snapshot| {
+                conversation.updated_at.is_some() && snapshot.conversation().updated_at >= conversation.updated_at
+            });
+            if up_to_date {
+                continue;
+            }
+            let snapshot = self.conversation_snapshot(&conversation.uuid).await?;
+            archive.insert(snapshot)?;
+            archived += 1;
+        }
+        debug!(operation = "sync_archive", archived, conversations = archive.len());
+        Ok(archived)
+    }
+
+    /// Answers a question from past conversations.
+    ///
+    /// The archived messages best matching the question (see `ChatArchive::search`) are sent along with the
+    /// question in a new conversation, with the instruction to answer from them only and to cite them. The
+    /// conversation is kept, so follow-up questions can be sent to `ArchiveAnswer::chat_uuid`.
+    ///
+    /// # Arguments
+    ///
+    /// * `archive` - The archive to search, brought up to date with `sync_archive`.
+    /// * `question` - The question to answer.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ArchiveAnswer>` - The answer and the conversations it draws on, if the request is successful.
+    ///   Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::NoArchiveMatch` without sending anything if no archived message shares
+    /// a word with the question, or an error if the conversation cannot be created or answered.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ archive::ChatArchive, Client };
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let archive = ChatArchive::open("archive")?;
+    /// let answer = client.ask_archive(&archive, "Which hydration did we settle on for the bread?").await?;
+    /// println!("{}", answer.text);
+    /// for (i, source) in answer.sources.iter().enumerate() {
+    ///     println!("[{}] {} {}", i + 1, source.name, source.url);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ask_archive(&self, archive: &ChatArchive, question: &str) -> Result<ArchiveAnswer> {
+        let hits = archive.search(question, MAX_EXCERPTS);
+        if hits.is_empty() {
+            return Err(Error::NoArchiveMatch(question.to_string()));
+        }
+
+        let mut sources: Vec<ArchiveSource> = vec![];
+        let mut excerpts = String::new();
+        for hit in &hits {
+            let position = match sources.iter().position(|source| source.conversation_uuid == hit.conversation.uuid) {
+                Some(position) => position,
+                None => {
+                    sources.push(ArchiveSource {
+                        conversation_uuid: hit.conversation.uuid.clone(),
+                        name: hit.conversation.name.clone(),
+                        url: format!("{}/chat/{}", self.base_url, hit.conversation.uuid),
+                    });
+                    sources.len() - 1
+                }
+            };
+            let text: String = hit.message.text.chars().take(MAX_EXCERPT_CHARS).collect();
+            excerpts.push_str(&format!("[{}] {}: {}\n\n", position + 1, hit.message.sender, text.trim()));
+        }
+
+        let prompt = format!(
+            "Answer the question below using only these excerpts of my past conversations, each starting with the number of its conversation. Cite the conversations you use as [number]. If the excerpts do not contain the answer, say so.\n\n{}Question: {}",
+            excerpts,
+            question
+        );
+        let context = || "while asking the archive";
+        let chat = self.create_new_chat().await.with_context(context)?;
+        let text = self.send_message(&chat.uuid, &prompt, None, None).await.with_context(context)?;
+
+        Ok(ArchiveAnswer { text, chat_uuid: chat.uuid, sources })
+    }
+}
@@ -0,0 +1,184 @@
+//! Artifacts: the code and documents Claude writes in `<antArtifact>` tags instead of inline.
+//!
+//! An artifact looks like this in the text of an answer:
+//!
+//! ```text
+//! <antArtifact identifier="fizzbuzz" type="application/vnd.ant.code" language="python" title="FizzBuzz">
+//! for i in range(1, 101):
+//!     ...
+//! </antArtifact>
+//! ```
+//!
+//! `extract` splits a complete answer into its prose and its artifacts, while `ArtifactParser` picks the
+//! artifacts out of an answer as it is streamed.
+
+use serde::{ Deserialize, Serialize };
+
+use crate::{ Client, Result };
+
+const OPENING_TAG: &str = "<antArtifact";
+const CLOSING_TAG: &str = "</antArtifact>";
+
+/// A piece of code or a document written by Claude in an `<antArtifact>` tag.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Artifact {
+    /// The identifier of the artifact, shared by the successive versions of the same artifact.
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    /// The language of the code, unset for documents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// The text between the tags, without the line breaks next to them.
+    pub content: String,
+}
+
+/// Splits an answer into its text without the artifacts, and the artifacts in order of appearance.
+///
+/// An artifact whose closing tag is missing, as in a truncated answer, is left in the text.
+///
+/// # Examples
+///
+/// ```
+/// use claude::artifacts::extract;
+///
+/// let answer = "Here you go:\n\n<antArtifact identifier=\"greet\" type=\"application/vnd.ant.code\" \
+///     language=\"rust\" title=\"Greeting\">\nprintln!(\"Hello\");\n</antArtifact>\n\nEnjoy!";
+/// let (text, artifacts) = extract(answer);
+/// assert_eq!(text, "Here you go:\n\n\n\nEnjoy!");
+/// assert_eq!(artifacts[0].id, "greet");
+/// assert_eq!(artifacts[0].language.as_deref(), Some("rust"));
+/// assert_eq!(artifacts[0].content, "println!(\"Hello\");");
+/// ```
+pub fn extract(answer: &str) -> (String, Vec<Artifact>) {
+    let mut text = String::with_capacity(answer.len());
+    let mut artifacts = vec![];
+    let mut rest = answer;
+    while let Some((before, artifact, after)) = next_artifact(rest) {
+        text.push_str(before);
+        artifacts.push(artifact);
+        rest = after;
+    }
+    text.push_str(rest);
+    (text, artifacts)
+}
+
+/// Picks the artifacts out of an answer as its text is streamed.
+///
+/// # Examples
+///
+/// ```
+/// use claude::artifacts::ArtifactParser;
+///
+/// let mut parser = ArtifactParser::new();
+/// assert!(parser.push("Sure.\n<antArtifact identifier=\"poem\" type=\"text/markdown\" ti").is_empty());
+/// assert!(parser.push("tle=\"Poem\">\nRoses are red\n</antArt").is_empty());
+/// let artifacts = parser.push("ifact>\nAnything else?");
+/// assert_eq!(artifacts.len(), 1);
+/// assert_eq!(artifacts[0].title, "Poem");
+/// assert_eq!(artifacts[0].content, "Roses are red");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactParser {
+    pending: String,
+}
+
+impl ArtifactParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next piece of the answer, returning the artifacts it completes.
+    pub fn push(&mut self, delta: &str) -> Vec<Artifact> {
+        self.pending.push_str(delta);
+        let mut artifacts = vec![];
+        let mut consumed = 0;
+        while let Some((_, artifact, after)) = next_artifact(&self.pending[consumed..]) {
+            artifacts.push(artifact);
+            consumed = self.pending.len() - after.len();
+        }
+        // Only the text from a possibly unfinished opening tag onwards can still hold an artifact.
+        let keep = match self.pending[consumed..].find(OPENING_TAG) {
+            Some(start) => consumed + start,
+            None => partial_tag_start(&self.pending[consumed..]).map_or(self.pending.len(), |start| consumed + start),
+        };
+        self.pending.drain(..keep);
+        artifacts
+    }
+}
+
+/// Returns the text before the first complete artifact of `text`, the artifact, and the text after it.
+fn next_artifact(text: &str) -> Option<(&str, Artifact, &str)> {
+    let start = text.find(OPENING_TAG)?;
+    let tag_end = start + text[start..].find('>')?;
+    let content_end = tag_end + text[tag_end..].find(CLOSING_TAG)?;
+    let attributes = &text[start + OPENING_TAG.len()..tag_end];
+    let artifact = Artifact {
+        id: attribute(attributes, "identifier").unwrap_or_default(),
+        title: attribute(attributes, "title").unwrap_or_default(),
+        language: attribute(attributes, "language"),
+        content: text[tag_end + 1..content_end].trim_matches(|c| c == '\n' || c == '\r').to_string(),
+    };
+    Some((&text[..start], artifact, &text[content_end + CLOSING_TAG.len()..]))
+}
+
+/// Returns the value of the attribute `name` of a tag.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let pattern = format!("{}=\"", name);
+    let mut search = attributes;
+    loop {
+        let index = search.find(&pattern)?;
+        let preceded_by_space = search[..index].ends_with(char::is_whitespace) || index == 0;
+        let value = &search[index + pattern.len()..];
+        if preceded_by_space {
+            return value.find('"').map(|end| value[..end].to_string());
+        }
+        search = value;
+    }
+}
+
+/// Returns where `text` ends with the beginning of an opening tag, such as `<antArt`.
+fn partial_tag_start(text: &str) -> Option<usize> {
+    let start = text.rfind('<')?;
+    OPENING_TAG.starts_with(&text[start..]).then_some(start)
+}
+
+impl Client {
+    /// Sends a message to a chat conversation and splits the answer into its text and its artifacts.
+    ///
+    /// This function behaves like `send_message`, then applies `artifacts::extract` to the answer.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(String, Vec<Artifact>)>` - The answer without the artifacts, and the artifacts in order of
+    ///   appearance.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the errors of `send_message`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let (text, artifacts) = client
+    ///     .send_message_with_artifacts("chat_uuid", "Write FizzBuzz in Python", None, None).await?;
+    /// println!("{}", text);
+    /// for artifact in artifacts {
+    ///     std::fs::write(format!("{}.py", artifact.id), artifact.content)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_message_with_artifacts(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>
+    ) -> Result<(String, Vec<Artifact>)> {
+        let answer = self.send_message(chat_uuid, prompt, attachments, timeout).await?;
+        Ok(extract(&answer))
+    }
+}
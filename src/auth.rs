@@ -0,0 +1,48 @@
+use reqwest::Request;
+use std::{ fmt, future::Future, pin::Pin };
+
+use crate::Result;
+
+/// The future returned by `AuthHook::authenticate`.
+pub type AuthFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Adds authentication material to every request, such as a signature required by an authenticating proxy
+/// in front of claude.ai.
+///
+/// The hook runs right before each request is sent, retries included, once all other headers are set, so it
+/// can sign the final method, URL and headers. An error fails the request without sending it. Set the hook
+/// with `ClientBuilder::auth_hook`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::{ auth::{ AuthFuture, AuthHook }, utils, Client };
+/// use reqwest::Request;
+///
+/// #[derive(Debug)]
+/// struct ProxySignature {
+///     key: String,
+/// }
+///
+/// impl AuthHook for ProxySignature {
+///     fn authenticate<'a>(&'a self, request: &'a mut Request) -> AuthFuture<'a> {
+///         Box::pin(async move {
+///             let payload = format!("{}{} {}", self.key, request.method(), request.url());
+///             request.headers_mut().insert("x-proxy-signature", utils::sha256_hex(payload.as_bytes()).parse()?);
+///             Ok(())
+///         })
+///     }
+/// }
+///
+/// # async fn example(cookies: String) -> claude::Result<()> {
+/// let client = Client::builder(cookies)
+///     .base_url("https://claude-proxy.internal")
+///     .auth_hook(ProxySignature { key: "secret".to_string() })
+///     .build().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait AuthHook: fmt::Debug + Send + Sync {
+    /// Adds authentication material to `request`.
+    fn authenticate<'a>(&'a self, request: &'a mut Request) -> AuthFuture<'a>;
+}
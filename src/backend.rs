@@ -0,0 +1,96 @@
+use std::future::Future;
+
+use crate::{ api::ApiClient, ChatMessage, Client, Conversation, Result };
+
+/// The conversation operations shared by the web `Client` and the official-API `ApiClient`.
+///
+/// Write code against this trait to switch backends without changes, or to substitute a mock in tests.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::backend::ClaudeBackend;
+///
+/// async fn ask(backend: &impl ClaudeBackend, question: &str) -> claude::Result<String> {
+///     let chat = backend.create_conversation().await?;
+///     backend.send_message(&chat.uuid, question, &[]).await
+/// }
+/// ```
+pub trait ClaudeBackend {
+    /// Creates a new, empty conversation.
+    fn create_conversation(&self) -> impl Future<Output = Result<Conversation>> + Send;
+
+    /// Lists the conversations of the account.
+    fn list_conversations(&self) -> impl Future<Output = Result<Vec<Conversation>>> + Send;
+
+    /// Returns the messages of a conversation, in order.
+    fn conversation_history(&self, chat_uuid: &str) -> impl Future<Output = Result<Vec<ChatMessage>>> + Send;
+
+    /// Sends a message with the given attached files to a conversation and returns the answer.
+    fn send_message(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: &[&str]
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// Renames a conversation.
+    fn rename_conversation(&self, chat_uuid: &str, title: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Deletes a conversation.
+    fn delete_conversation(&self, chat_uuid: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl ClaudeBackend for Client {
+    async fn create_conversation(&self) -> Result<Conversation> {
+        self.create_new_chat().await
+    }
+
+    async fn list_conversations(&self) -> Result<Vec<Conversation>> {
+        self.list_all_conversations().await
+    }
+
+    async fn conversation_history(&self, chat_uuid: &str) -> Result<Vec<ChatMessage>> {
+        self.chat_conversation_history(chat_uuid).await
+    }
+
+    async fn send_message(&self, chat_uuid: &str, prompt: &str, attachments: &[&str]) -> Result<String> {
+        let attachments = (!attachments.is_empty()).then(|| attachments.to_vec());
+        Client::send_message(self, chat_uuid, prompt, attachments, None).await
+    }
+
+    async fn rename_conversation(&self, chat_uuid: &str, title: &str) -> Result<()> {
+        self.rename_chat(chat_uuid, title).await
+    }
+
+    async fn delete_conversation(&self, chat_uuid: &str) -> Result<()> {
+        Client::delete_conversation(self, chat_uuid).await
+    }
+}
+
+impl ClaudeBackend for ApiClient {
+    async fn create_conversation(&self) -> Result<Conversation> {
+        Ok(self.create_new_chat())
+    }
+
+    async fn list_conversations(&self) -> Result<Vec<Conversation>> {
+        Ok(self.list_all_conversations())
+    }
+
+    async fn conversation_history(&self, chat_uuid: &str) -> Result<Vec<ChatMessage>> {
+        self.chat_conversation_history(chat_uuid)
+    }
+
+    async fn send_message(&self, chat_uuid: &str, prompt: &str, attachments: &[&str]) -> Result<String> {
+        let attachments = (!attachments.is_empty()).then(|| attachments.to_vec());
+        ApiClient::send_message(self, chat_uuid, prompt, attachments, None).await
+    }
+
+    async fn rename_conversation(&self, chat_uuid: &str, title: &str) -> Result<()> {
+        self.rename_chat(chat_uuid, title)
+    }
+
+    async fn delete_conversation(&self, chat_uuid: &str) -> Result<()> {
+        ApiClient::delete_conversation(self, chat_uuid)
+    }
+}
@@ -0,0 +1,134 @@
+//! Session bootstrap through a real browser, for users who cannot copy cookies out of the developer tools.
+//!
+//! `BrowserLogin` opens claude.ai in Chrome or Chromium, lets the user sign in as usual, and returns the
+//! `sessionKey` cookie once the login completes. Enable the `browser` feature to use this module; Chrome or
+//! Chromium must be installed.
+
+use chromiumoxide::{ Browser, BrowserConfig };
+use futures_util::StreamExt;
+use std::{ path::PathBuf, time::Duration };
+use tokio::time::Instant;
+use tracing::{ debug, info };
+
+use crate::{ builder::DEFAULT_BASE_URL, Error, Result };
+
+/// Name of the cookie holding the claude.ai session.
+const SESSION_COOKIE: &str = "sessionKey";
+/// Default time the user has to complete the login.
+const DEFAULT_LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Pause between two checks of the cookies of the login page.
+const COOKIE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An interactive login to claude.ai in a browser window.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::{ browser::BrowserLogin, Client };
+/// # async fn example() -> claude::Result<()> {
+/// // Opens a browser window and waits for the user to sign in.
+/// let cookies = BrowserLogin::new().user_data_dir("/home/jane/.cache/claude-browser").run().await?;
+/// let client = Client::builder(cookies).build().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BrowserLogin {
+    base_url: String,
+    executable: Option<PathBuf>,
+    user_data_dir: Option<PathBuf>,
+    timeout: Duration,
+}
+
+impl Default for BrowserLogin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BrowserLogin {
+    /// Returns a login to claude.ai with the first Chrome or Chromium found, giving the user 5 minutes.
+    pub fn new() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            executable: None,
+            user_data_dir: None,
+            timeout: DEFAULT_LOGIN_TIMEOUT,
+        }
+    }
+
+    /// Opens `url` instead of `https://claude.ai`.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Runs the browser at `path` instead of looking for Chrome or Chromium.
+    pub fn executable(mut self, path: impl Into<PathBuf>) -> Self {
+        self.executable = Some(path.into());
+        self
+    }
+
+    /// Keeps the browser profile in `dir`, so a later login finds the user still signed in and completes
+    /// without interaction. A temporary profile is used when unset.
+    pub fn user_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.user_data_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets the time the user has to complete the login.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Opens the login page and waits until the user has signed in, returning the cookies to build a client
+    /// with. The browser is closed before returning.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::Browser` if the browser cannot be started or controlled, or if the
+    /// user does not complete the login within the timeout.
+    pub async fn run(&self) -> Result<String> {
+        let mut config = BrowserConfig::builder().with_head();
+        if let Some(executable) = &self.executable {
+            config = config.chrome_executable(executable);
+        }
+        if let Some(dir) = &self.user_data_dir {
+            config = config.user_data_dir(dir);
+        }
+        let config = config.build().map_err(Error::Browser)?;
+
+        let (mut browser, mut handler) = Browser::launch(config).await.map_err(browser_error)?;
+        let events = tokio::spawn(async move { while handler.next().await.is_some() {} });
+        let result = self.wait_for_session(&browser).await;
+        if let Err(e) = browser.close().await {
+            debug!(operation = "browser_login", error = %e, "cannot close the browser");
+        }
+        let _ = browser.wait().await;
+        events.abort();
+
+        let session = result?;
+        info!(target: "claude::audit", event = "login.verified");
+        Ok(session)
+    }
+
+    async fn wait_for_session(&self, browser: &Browser) -> Result<String> {
+        let page = browser.new_page(format!("{}/login", self.base_url)).await.map_err(browser_error)?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let cookies = page.get_cookies().await.map_err(browser_error)?;
+            if let Some(cookie) = cookies.iter().find(|cookie| cookie.name == SESSION_COOKIE) {
+                return Ok(format!("{}={}", SESSION_COOKIE, cookie.value));
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Browser(format!("the login was not completed within {:?}", self.timeout)));
+            }
+            tokio::time::sleep(COOKIE_POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn browser_error(e: chromiumoxide::error::CdpError) -> Error {
+    Error::Browser(e.to_string())
+}
@@ -0,0 +1,441 @@
+use reqwest::header::{ HeaderMap, HeaderName, HeaderValue };
+#[cfg(feature = "attachments")]
+use std::collections::HashMap;
+use std::{ sync::{ atomic::AtomicU64, Arc }, time::Duration };
+use tracing::debug;
+
+use crate::{
+    auth::AuthHook,
+    cache::AnswerCache,
+    rate_limit::RateLimiter,
+    BodyLogging,
+    Client,
+    CloudflarePolicy,
+    Model,
+    Result,
+    RetryPolicy,
+    RetryStrategy,
+    DEFAULT_DELETE_CONCURRENCY,
+    DEFAULT_FIRST_BYTE_TIMEOUT,
+};
+#[cfg(feature = "attachments")]
+use crate::{
+    cache::AttachmentCache,
+    ProgressCallback,
+    TruncationPolicy,
+    UploadProgress,
+    DEFAULT_ATTACHMENT_CACHE_CAPACITY,
+    DEFAULT_CONVERSION_ATTEMPTS,
+    DEFAULT_CONVERSION_TIMEOUT,
+    DEFAULT_UPLOAD_CONCURRENCY,
+};
+
+/// The claude.ai origin every request is sent to, unless overridden with `ClientBuilder::base_url`.
+pub const DEFAULT_BASE_URL: &str = "https://claude.ai";
+/// The time zone sent with messages when the local one cannot be detected.
+const FALLBACK_TIMEZONE: &str = "UTC";
+
+/// Transport settings shared by every HTTP client a `Client` builds.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpOptions {
+    pub connect_timeout: Option<Duration>,
+    pub request_timeout: Option<Duration>,
+    pub proxy: Option<String>,
+    /// Whether plain `http://` URLs are refused. Only disabled for a custom `http://` base URL.
+    pub https_only: bool,
+}
+
+/// Configures and builds a `Client`.
+///
+/// Created with `Client::builder`. Unlike `Client::new`, `build` returns an error instead of exiting the
+/// process when the client cannot be set up.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::Client;
+/// use std::time::Duration;
+/// # async fn example(cookies: String) -> claude::Result<()> {
+/// let client = Client::builder(cookies)
+///     .connect_timeout(Duration::from_secs(10))
+///     .proxy("socks5://127.0.0.1:1080")
+///     .header("x-request-source", "batch")?
+///     .build().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ClientBuilder {
+    cookies: String,
+    base_url: String,
+    options: HttpOptions,
+    headers: HeaderMap,
+    retry: Option<Arc<dyn RetryStrategy>>,
+    requests_per_minute: Option<u32>,
+    /// A limiter shared with earlier clients, which takes precedence over `requests_per_minute`.
+    rate_limiter: Option<Arc<RateLimiter>>,
+    auth_hook: Option<Arc<dyn AuthHook>>,
+    timezone: Option<String>,
+    cloudflare: Option<CloudflarePolicy>,
+    answer_cache_ttl: Option<Duration>,
+    #[cfg(feature = "attachments")]
+    max_attachment_size: Option<u64>,
+    #[cfg(feature = "attachments")]
+    attachment_cache_capacity: usize,
+    #[cfg(feature = "attachments")]
+    mime_types: HashMap<String, String>,
+    #[cfg(feature = "attachments")]
+    upload_progress: Option<ProgressCallback>,
+}
+
+impl std::fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("cookies", &"<redacted>")
+            .field("base_url", &self.base_url)
+            .field("options", &self.options)
+            .field("headers", &self.headers)
+            .field("requests_per_minute", &self.requests_per_minute)
+            .field("timezone", &self.timezone)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Returns a builder for a client authenticated with `cookies`.
+    pub fn builder(cookies: impl Into<String>) -> ClientBuilder {
+        ClientBuilder {
+            cookies: cookies.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            options: HttpOptions { https_only: true, ..HttpOptions::default() },
+            headers: HeaderMap::new(),
+            retry: None,
+            requests_per_minute: None,
+            rate_limiter: None,
+            auth_hook: None,
+            timezone: None,
+            cloudflare: None,
+            answer_cache_ttl: None,
+            #[cfg(feature = "attachments")]
+            max_attachment_size: None,
+            #[cfg(feature = "attachments")]
+            attachment_cache_capacity: DEFAULT_ATTACHMENT_CACHE_CAPACITY,
+            #[cfg(feature = "attachments")]
+            mime_types: HashMap::new(),
+            #[cfg(feature = "attachments")]
+            upload_progress: None,
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// Sets the maximum time to establish a connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum time of a whole request, from sending it until its body is read.
+    ///
+    /// This bounds streamed answers too, so it must be longer than the slowest expected answer. Prefer
+    /// `Client::first_byte_timeout` and the `send_message` timeout to bound answers.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.options.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the IANA time zone sent with messages, such as `Europe/Paris`, which the model uses to tell the
+    /// local time. The time zone of the machine is detected when unset, falling back to `UTC`.
+    pub fn timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Sends every request through an HTTP, HTTPS or SOCKS5 proxy, such as `socks5://127.0.0.1:1080`.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.options.proxy = Some(url.into());
+        self
+    }
+
+    /// Sends requests to `url` instead of `https://claude.ai`, for example a mock server in tests.
+    ///
+    /// Plain `http://` URLs are allowed here, while the default only ever connects over HTTPS.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Adds a header sent with every request, replacing any default header of the same name.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the name is not a valid header name, or if the value is not a valid
+    /// header value.
+    pub fn header(mut self, name: impl AsRef<str>, value: &str) -> Result<Self> {
+        self.headers.insert(HeaderName::from_bytes(name.as_ref().as_bytes())?, HeaderValue::from_str(value)?);
+        Ok(self)
+    }
+
+    /// Adds headers sent with every request, replacing any default headers of the same names.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Retries rate limited requests, server errors and connection failures according to `policy`.
+    ///
+    /// Every request sent by the client goes through the policy, including answers, uploads, listings and
+    /// deletions.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ Client, RetryPolicy };
+    /// # async fn example(cookies: String) -> claude::Result<()> {
+    /// let client = Client::builder(cookies)
+    ///     .retry_policy(RetryPolicy { max_retries: 5, ..RetryPolicy::default() })
+    ///     .build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn retry_policy(self, policy: RetryPolicy) -> Self {
+        self.retry_strategy(policy)
+    }
+
+    /// Retries failed requests according to a custom `strategy`, replacing any policy set with `retry_policy`.
+    ///
+    /// Every request sent by the client goes through the strategy, which decides for each failure whether to
+    /// retry and after how long.
+    pub fn retry_strategy(mut self, strategy: impl RetryStrategy + 'static) -> Self {
+        self.retry = Some(Arc::new(strategy));
+        self
+    }
+
+    /// Sends at most `requests` requests per minute, spacing them evenly.
+    ///
+    /// Bulk operations such as `Client::reset_all` otherwise easily trip the rate limits of claude.ai. The limit
+    /// applies to every request, retries included, and is shared by all clones of the client.
+    pub fn requests_per_minute(mut self, requests: u32) -> Self {
+        self.requests_per_minute = Some(requests);
+        self
+    }
+
+    /// Makes the client share `rate_limiter` with the clients it was taken from, such as the earlier clients of a
+    /// `ClientManager` user, instead of starting a limit of its own.
+    pub(crate) fn shared_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        if rate_limiter.is_some() {
+            self.rate_limiter = rate_limiter;
+        }
+        self
+    }
+
+    /// Runs `hook` on every request right before it is sent, to add authentication material such as a signed
+    /// header for an authenticating proxy. See `AuthHook`.
+    pub fn auth_hook(mut self, hook: impl AuthHook + 'static) -> Self {
+        self.auth_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Enables cooling down when Cloudflare blocks requests, instead of returning the block page immediately.
+    ///
+    /// See `CloudflarePolicy` for the available knobs. A client for each configured proxy is built by `build`, which
+    /// fails if a proxy URL is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ Client, CloudflarePolicy };
+    /// # async fn example(cookies: String) -> claude::Result<()> {
+    /// let client = Client::builder(cookies)
+    ///     .cloudflare_policy(CloudflarePolicy {
+    ///         proxies: vec!["socks5://127.0.0.1:1080".to_string()],
+    ///         ..CloudflarePolicy::default()
+    ///     })
+    ///     .build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cloudflare_policy(mut self, policy: CloudflarePolicy) -> Self {
+        self.cloudflare = Some(policy);
+        self
+    }
+
+    /// Enables caching answers for `ttl`.
+    ///
+    /// Answers are keyed on the prompt, the SHA-256 hashes of the attached files and the models asked, so
+    /// sending the same prompt with unchanged files again within `ttl` returns the previous answer without
+    /// sending anything, whichever conversation it is sent to. This makes re-running document pipelines free
+    /// when their inputs have not changed. Set `Client::bypass_answer_cache` to force fresh answers.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use std::time::Duration;
+    /// # async fn example(cookies: String) -> claude::Result<()> {
+    /// let client = Client::builder(cookies).answer_cache(Duration::from_secs(24 * 60 * 60)).build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn answer_cache(mut self, ttl: Duration) -> Self {
+        self.answer_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Splits text files larger than `bytes` into several attachments before sending them with a message.
+    ///
+    /// claude.ai rejects files over its size limit. With this setting, oversized text files such as logs and CSV
+    /// exports are cut between lines into parts named `name (part 1 of 3).ext`, each uploaded and attached to the
+    /// message. The header row of CSV and TSV files is repeated in every part. Other files are sent whole.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(cookies: String) -> claude::Result<()> {
+    /// let client = Client::builder(cookies).max_attachment_size(10 * 1024 * 1024).build().await?;
+    /// client.send_message("chat_uuid", "Summarize these logs", Some(vec!["server.log"]), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "attachments")]
+    pub fn max_attachment_size(mut self, bytes: u64) -> Self {
+        self.max_attachment_size = Some(bytes);
+        self
+    }
+
+    /// Sets how many converted documents are cached to avoid uploading the same contents again, 256 by default.
+    ///
+    /// The least recently used document is dropped when the cache is full. A capacity of 0 disables the cache.
+    #[cfg(feature = "attachments")]
+    pub fn attachment_cache_capacity(mut self, documents: usize) -> Self {
+        self.attachment_cache_capacity = documents;
+        self
+    }
+
+    /// Extends or overrides the table mapping file extensions to the MIME types sent with attachments.
+    ///
+    /// Keys are file extensions, with or without a leading dot, matched case-insensitively. Entries take
+    /// precedence over the built-in mapping, which is handy for niche formats that should be sent as text.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use std::collections::HashMap;
+    /// # async fn example(cookies: String) -> claude::Result<()> {
+    /// let client = Client::builder(cookies)
+    ///     .mime_types(HashMap::from([
+    ///         ("sql".to_string(), "text/plain".to_string()),
+    ///         (".proto".to_string(), "text/plain".to_string()),
+    ///         ("yaml".to_string(), "text/plain".to_string()),
+    ///     ]))
+    ///     .build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "attachments")]
+    pub fn mime_types(mut self, mime_types: HashMap<String, String>) -> Self {
+        crate::extend_mime_types(&mut self.mime_types, mime_types);
+        self
+    }
+
+    /// Sets a callback receiving the progress of every upload, such as to render a progress bar while a large
+    /// PDF is sent.
+    ///
+    /// The callback is called from the upload after each chunk is read from the file, so it should return
+    /// quickly. A document is sent again when its conversion is requested again, and its progress restarts
+    /// from zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(cookies: String) -> claude::Result<()> {
+    /// let client = Client::builder(cookies)
+    ///     .upload_progress(|progress| {
+    ///         eprint!("\r{}: {}/{} bytes", progress.file_name, progress.sent, progress.total);
+    ///     })
+    ///     .build().await?;
+    /// client.upload_attachment("manual.pdf").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "attachments")]
+    pub fn upload_progress(mut self, callback: impl Fn(&UploadProgress) + Send + Sync + 'static) -> Self {
+        self.upload_progress = Some(ProgressCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Builds the HTTP client and retrieves the organization ID of the account.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the HTTP client cannot be built, for example because of an
+    /// invalid proxy URL, including those of the Cloudflare policy, or if the organization ID cannot be retrieved
+    /// because the cookies are expired or invalid.
+    pub async fn build(mut self) -> Result<Client> {
+        self.options.https_only = !self.base_url.starts_with("http://");
+        let mut headers = crate::client_headers(&self.cookies)?;
+        headers.extend(self.headers);
+        let http = crate::build_client(headers.clone(), &self.options)?;
+        let proxy_clients = match &self.cloudflare {
+            Some(policy) => crate::build_proxy_clients(&headers, &self.options, policy)?,
+            None => vec![],
+        };
+        let org_uuid = Client::fetch_organization_id(&http, &self.base_url, self.auth_hook.as_deref()).await?;
+
+        Ok(Client {
+            cookies: self.cookies,
+            org_uuid,
+            http,
+            headers,
+            base_url: self.base_url,
+            http_options: self.options,
+            default_model: Model::default(),
+            fallback_models: vec![],
+            validate_before_send: false,
+            title_template: None,
+            timezone: self.timezone.unwrap_or_else(local_timezone),
+            bypass_answer_cache: false,
+            body_logging: BodyLogging::default(),
+            first_byte_timeout: DEFAULT_FIRST_BYTE_TIMEOUT,
+            delete_concurrency: DEFAULT_DELETE_CONCURRENCY,
+            strict_responses: false,
+            #[cfg(feature = "attachments")]
+            conversion_timeout: DEFAULT_CONVERSION_TIMEOUT,
+            #[cfg(feature = "attachments")]
+            max_conversion_attempts: DEFAULT_CONVERSION_ATTEMPTS,
+            #[cfg(feature = "attachments")]
+            attachment_budget: None,
+            #[cfg(feature = "attachments")]
+            truncation_policy: TruncationPolicy::default(),
+            #[cfg(feature = "attachments")]
+            max_attachment_size: self.max_attachment_size,
+            #[cfg(feature = "attachments")]
+            upload_concurrency: DEFAULT_UPLOAD_CONCURRENCY,
+            #[cfg(feature = "attachments")]
+            attachment_cache: Arc::new(AttachmentCache::new(self.attachment_cache_capacity)),
+            #[cfg(feature = "attachments")]
+            mime_types: Arc::new(self.mime_types),
+            #[cfg(feature = "attachments")]
+            upload_progress: self.upload_progress,
+            chats_created: Arc::new(AtomicU64::new(0)),
+            answer_cache: self.answer_cache_ttl.map(|ttl| Arc::new(AnswerCache::new(ttl))),
+            cloudflare: self.cloudflare,
+            proxy_clients,
+            retry: self.retry,
+            rate_limiter: self.rate_limiter.or_else(|| {
+                self.requests_per_minute.map(|requests| Arc::new(RateLimiter::new(requests)))
+            }),
+            auth_hook: self.auth_hook,
+        })
+    }
+}
+
+/// Returns the IANA time zone of the machine, or `UTC` if it cannot be detected.
+fn local_timezone() -> String {
+    iana_time_zone::get_timezone().unwrap_or_else(|e| {
+        debug!(operation = "build", error = %e, "cannot detect the local time zone, using {}", FALLBACK_TIMEZONE);
+        FALLBACK_TIMEZONE.to_string()
+    })
+}
@@ -0,0 +1,106 @@
+use std::{ collections::HashMap, sync::Mutex, time::{ Duration, Instant } };
+
+use crate::Completion;
+#[cfg(feature = "attachments")]
+use crate::UploadedAttachment;
+
+/// What an answer depends on: the prompt, the contents of the attached files and the models asked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct AnswerKey {
+    pub prompt: String,
+    /// Hex-encoded SHA-256 digests of the attached files, in order.
+    pub attachments: Vec<String>,
+    pub models: Vec<String>,
+}
+
+/// Answers kept for a limited time, keyed by their inputs.
+#[derive(Debug)]
+pub(crate) struct AnswerCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<AnswerKey, (Instant, Completion)>>,
+}
+
+impl AnswerCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the answer cached for `key`, unless it has expired.
+    pub fn get(&self, key: &AnswerKey) -> Option<Completion> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((stored, completion)) if stored.elapsed() < self.ttl => Some(completion.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Caches `completion` for `key`, dropping expired answers along the way.
+    pub fn insert(&self, key: AnswerKey, completion: Completion) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        entries.insert(key, (Instant::now(), completion));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Converted documents keyed by the SHA-256 hash of their contents, holding at most `capacity` of them.
+///
+/// The least recently used document is dropped when a new one would exceed the capacity.
+#[cfg(feature = "attachments")]
+#[derive(Debug)]
+pub(crate) struct AttachmentCache {
+    capacity: usize,
+    entries: Mutex<AttachmentEntries>,
+}
+
+#[cfg(feature = "attachments")]
+#[derive(Debug, Default)]
+struct AttachmentEntries {
+    /// Incremented on every use, so that a lower stamp means a less recent use.
+    clock: u64,
+    documents: HashMap<String, (u64, UploadedAttachment)>,
+}
+
+#[cfg(feature = "attachments")]
+impl AttachmentCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, entries: Mutex::new(AttachmentEntries::default()) }
+    }
+
+    /// Returns the document cached for `hash`, marking it as the most recently used.
+    pub fn get(&self, hash: &str) -> Option<UploadedAttachment> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let clock = entries.clock;
+        let (used, document) = entries.documents.get_mut(hash)?;
+        *used = clock;
+        Some(document.clone())
+    }
+
+    /// Caches `document` for `hash`, dropping the least recently used documents over the capacity.
+    pub fn insert(&self, hash: String, document: UploadedAttachment) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let clock = entries.clock;
+        entries.documents.insert(hash, (clock, document));
+        while entries.documents.len() > self.capacity {
+            let oldest = entries.documents
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(hash, _)| hash.clone())
+                .expect("the cache is over its capacity, so not empty");
+            entries.documents.remove(&oldest);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().documents.clear();
+    }
+}
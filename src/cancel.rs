@@ -0,0 +1,126 @@
+use std::{ future::Future, panic, pin::Pin, task::{ Context as TaskContext, Poll } };
+use tokio::task::JoinHandle;
+use tracing::info;
+
+use crate::{ deadline, Client, Completion, Context, Error, Result };
+
+/// A message being answered in the background, returned by `Client::send_message_cancellable`.
+///
+/// Awaiting the handle returns the answer like `Client::send_message_detailed`. Dropping it does not stop the
+/// answer; call `abort` for that.
+#[derive(Debug)]
+pub struct MessageHandle {
+    client: Client,
+    chat_uuid: String,
+    task: JoinHandle<Result<Completion>>,
+}
+
+impl Client {
+    /// Sends a message in a background task, returning a handle to await or abort the answer.
+    ///
+    /// The arguments are those of `send_message`. The deadline set by an enclosing `with_deadline`, if any,
+    /// still applies to the background task.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use std::time::Duration;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let handle = client.send_message_cancellable("chat_uuid", "Write a very long story", None, None);
+    /// tokio::time::sleep(Duration::from_secs(5)).await;
+    /// handle.abort().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_message_cancellable(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>
+    ) -> MessageHandle {
+        let client = self.clone();
+        let (chat, prompt) = (chat_uuid.to_string(), prompt.to_string());
+        let attachments: Option<Vec<String>> = attachments.map(|paths| paths.into_iter().map(str::to_string).collect());
+        let send = async move {
+            let attachments = attachments.as_ref().map(|paths| paths.iter().map(String::as_str).collect());
+            client.send_message_detailed(&chat, &prompt, attachments, timeout).await
+        };
+        // Task-local deadlines do not cross into spawned tasks, so the current one is set again.
+        let task = match deadline::current() {
+            Some(deadline) => tokio::spawn(deadline::with_deadline(deadline, send)),
+            None => tokio::spawn(send),
+        };
+        MessageHandle { client: self.clone(), chat_uuid: chat_uuid.to_string(), task }
+    }
+
+    /// Asks claude.ai to stop generating the answer in progress in a conversation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails.
+    pub async fn stop_generation(&self, chat_uuid: &str) -> Result<()> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}/stop_response",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+        let request = self.http.post(url);
+        self.dispatch("stop_generation", Some(chat_uuid), request)
+            .await
+            .with_context(|| format!("while stopping the answer in conversation {}", chat_uuid))?;
+        info!(target: "claude::audit", event = "message.stopped", conversation_id = chat_uuid);
+        Ok(())
+    }
+}
+
+impl MessageHandle {
+    /// Returns the UUID of the conversation the message was sent to.
+    pub fn chat_uuid(&self) -> &str {
+        &self.chat_uuid
+    }
+
+    /// Returns whether the answer is complete, so awaiting the handle returns immediately.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Stops the answer: the background task is cancelled and claude.ai is asked to stop generating.
+    ///
+    /// Nothing is stopped on the server when the answer was already complete.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the stop request fails.
+    pub async fn abort(self) -> Result<()> {
+        if self.task.is_finished() {
+            return Ok(());
+        }
+        self.task.abort();
+        // Wait for the task to release its connection before stopping the generation.
+        let _ = self.task.await;
+        self.client.stop_generation(&self.chat_uuid).await
+    }
+}
+
+impl Future for MessageHandle {
+    type Output = Result<Completion>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.task)
+            .poll(cx)
+            .map(|res| {
+                match res {
+                    Ok(res) => res,
+                    Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+                    Err(_) => Err(Error::Cancelled),
+                }
+            })
+    }
+}
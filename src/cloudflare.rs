@@ -0,0 +1,74 @@
+use reqwest::{ header::{ CONTENT_TYPE, SERVER }, Response, StatusCode };
+use std::time::Duration;
+
+/// How the client cools down when Cloudflare temporarily blocks a burst of requests.
+///
+/// Cloudflare blocks are sticky: retrying immediately tends to extend them. When a block is detected the
+/// client instead pauses for an exponentially growing cool-down before trying again, optionally presenting
+/// a different user agent or going through a different proxy on each attempt. This is separate from retries
+/// of ordinary transient failures.
+#[derive(Debug, Clone)]
+pub struct CloudflarePolicy {
+    /// Maximum number of cool-down rounds before the blocked response is returned to the caller.
+    pub max_attempts: u32,
+    /// Pause before the first retry.
+    pub initial_cool_down: Duration,
+    /// Upper bound for the pause between two attempts.
+    pub max_cool_down: Duration,
+    /// User agents rotated through on each retry. The default user agent is used when empty.
+    pub user_agents: Vec<String>,
+    /// Proxy URLs (`http://`, `https://` or `socks5://`) rotated through on each retry. Requests go out directly
+    /// when empty.
+    pub proxies: Vec<String>,
+}
+
+impl Default for CloudflarePolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_cool_down: Duration::from_secs(30),
+            max_cool_down: Duration::from_secs(300),
+            user_agents: vec![],
+            proxies: vec![],
+        }
+    }
+}
+
+impl CloudflarePolicy {
+    /// Returns the pause before retry number `attempt` (starting at 1), doubling each time up to `max_cool_down`.
+    pub fn cool_down(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_cool_down.saturating_mul(factor).min(self.max_cool_down)
+    }
+
+    /// Returns the user agent to present on retry number `attempt`, if any are configured.
+    pub(crate) fn user_agent(&self, attempt: u32) -> Option<&str> {
+        if self.user_agents.is_empty() {
+            return None;
+        }
+        let index = (attempt as usize - 1) % self.user_agents.len();
+        Some(&self.user_agents[index])
+    }
+}
+
+/// Returns whether a response is a Cloudflare block or challenge page rather than an answer from claude.ai.
+pub(crate) fn is_blocked(response: &Response) -> bool {
+    if !matches!(response.status(), StatusCode::FORBIDDEN | StatusCode::SERVICE_UNAVAILABLE) {
+        return false;
+    }
+    let headers = response.headers();
+    if headers.contains_key("cf-mitigated") {
+        return true;
+    }
+    let is_html = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/html"));
+    let from_cloudflare =
+        headers.contains_key("cf-ray") ||
+        headers
+            .get(SERVER)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.eq_ignore_ascii_case("cloudflare"));
+    is_html && from_cloudflare
+}
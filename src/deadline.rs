@@ -0,0 +1,48 @@
+use std::{ future::Future, time::Instant };
+
+use crate::{ Error, Result };
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Runs `future` with an absolute deadline that bounds every operation performed inside it.
+///
+/// All requests made by the client while `future` runs — uploads, completions, listings, retries of any
+/// kind — share this single budget instead of each applying its own timeout, and fail with
+/// `Error::DeadlineExceeded` once it is spent. `future` as a whole is bounded too, so reading a response body
+/// that stalls after its headers also fails. Deadlines nest: an inner call can only shorten the budget.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::{ with_deadline, Client };
+/// use std::time::{ Duration, Instant };
+/// # async fn handler(client: &Client) -> claude::Result<String> {
+/// let deadline = Instant::now() + Duration::from_secs(30);
+/// with_deadline(deadline, async {
+///     let chat = client.create_new_chat().await?;
+///     client.send_message(&chat.uuid, "Hello", None, None).await
+/// }).await
+/// # }
+/// ```
+pub async fn with_deadline<T, F: Future<Output = Result<T>>>(deadline: Instant, future: F) -> Result<T> {
+    let deadline = current().map_or(deadline, |current| current.min(deadline));
+    DEADLINE.scope(deadline, bound(future)).await?
+}
+
+/// Returns the deadline set by the innermost enclosing `with_deadline`, if any.
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Awaits `future`, failing with `Error::DeadlineExceeded` if the current deadline passes first.
+pub(crate) async fn bound<F: Future>(future: F) -> Result<F::Output> {
+    match current() {
+        Some(deadline) =>
+            tokio::time
+                ::timeout_at(tokio::time::Instant::from_std(deadline), future).await
+                .map_err(|_| Error::DeadlineExceeded),
+        None => Ok(future.await),
+    }
+}
@@ -1,11 +1,290 @@
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
 use thiserror::Error;
 
+/// The errors returned by this crate.
+///
+/// New variants may be added in any release, so match on `kind()` and the `is_*` predicates rather than
+/// exhaustively on the variants, and never on error messages.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("HTTP request failed: {0}")] HttpRequestFailure(#[from] reqwest::Error),
     #[error("JSON parsing failed: {0}")] JsonParsingFailure(#[from] serde_json::Error),
     #[error("Invalid HTTP header value: {0}")] InvalidHttpHeaderValue(
         #[from] reqwest::header::InvalidHeaderValue,
     ),
+    #[error("Invalid HTTP header name: {0}")] InvalidHttpHeaderName(#[from] reqwest::header::InvalidHeaderName),
     #[error("Input/Output operation failed: {0}")] IoOperationFailure(#[from] std::io::Error),
+    #[error("Model {model} is unavailable: {message}")] ModelUnavailable {
+        model: String,
+        message: String,
+    },
+    #[error("Model {model} is not available to this account (available: {})", available.join(", "))] UnknownModel {
+        model: String,
+        available: Vec<String>,
+    },
+    #[error("The response did not contain any completion")] EmptyResponse,
+    #[error("No response received within {0:?}")] FirstByteTimeout(std::time::Duration),
+    #[error("The answer was not completed within {0:?}")] GenerationTimeout(std::time::Duration),
+    #[error("The caller's deadline was exceeded")] DeadlineExceeded,
+    #[error("The operation was cancelled")] Cancelled,
+    #[error("Sending attachments requires the `attachments` feature")] AttachmentsDisabled,
+    #[error("Conversation {0} not found")] ConversationNotFound(String),
+    #[error("Message {0} not found")] MessageNotFound(String),
+    #[error("Message {0} was not sent by the user and cannot be edited")] NotEditable(String),
+    #[error("No session is registered for user {0}")] UnknownSession(String),
+    #[error("Not a claude.ai magic link: {0}")] InvalidMagicLink(String),
+    #[error("The login succeeded but did not return a session cookie")] MissingSessionCookie,
+    #[error("The account does not belong to any organization")] NoOrganization,
+    #[error("Browser automation failed: {0}")] Browser(String),
+    #[error("No archived message matches {0:?}")] NoArchiveMatch(String),
+    #[error("The response for {uuid} lacks {}", fields.join(", "))] MissingFields {
+        uuid: String,
+        fields: Vec<&'static str>,
+    },
+    #[error("The conversion of {file_name} did not complete after {attempts} uploads within {timeout:?}")] ConversionIncomplete {
+        file_name: String,
+        attempts: u32,
+        timeout: Duration,
+    },
+    #[error("The prompt is about {estimated} tokens long, over the limit of {limit}")] PromptTooLong {
+        estimated: usize,
+        limit: usize,
+    },
+    #[error("The text of {file_name} is {chars} characters long, over its budget of {budget}")] AttachmentTooLarge {
+        file_name: String,
+        chars: usize,
+        budget: usize,
+    },
+    #[error("The session cookies or API key were rejected: {0}")] Unauthorized(ApiError),
+    #[error("Rate limited: {message}")] RateLimited {
+        /// When the limit resets, if the API said so.
+        resets_at: Option<SystemTime>,
+        message: String,
+    },
+    #[error("API error: {0}")] Api(ApiError),
+    #[error("{context}: {source}")] Context {
+        context: String,
+        source: Box<Error>,
+    },
+}
+
+/// An error response of the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    /// The machine-readable error type, such as `not_found_error`, when the body carries one.
+    pub error_type: Option<String>,
+    /// The error message, or the raw body when it is not a JSON error.
+    pub message: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error_type {
+            Some(error_type) => write!(f, "{} ({}): {}", self.status, error_type, self.message),
+            None => write!(f, "{}: {}", self.status, self.message),
+        }
+    }
+}
+
+impl ApiError {
+    /// Parses an error body, in either the claude.ai or the Messages API format
+    /// (`{"error": {"type": ..., "message": ...}}`), falling back to the raw body.
+    pub fn from_body(status: StatusCode, body: &str) -> Self {
+        let error = serde_json::from_str::<Value>(body).ok().and_then(|v| v.get("error").cloned());
+        let field = |name: &str| error.as_ref()?.get(name)?.as_str().map(str::to_string);
+        Self {
+            status,
+            error_type: field("type"),
+            message: field("message").unwrap_or_else(|| body.trim().to_string()),
+        }
+    }
+}
+
+impl Error {
+    /// Builds the error for a response with an error status.
+    ///
+    /// `retry_after` is the value of the `Retry-After` header, if any, used when the body has no `resetsAt`
+    /// hint.
+    pub(crate) fn from_status(status: StatusCode, retry_after: Option<&str>, body: &str) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::Unauthorized(ApiError::from_body(status, body)),
+            StatusCode::TOO_MANY_REQUESTS => {
+                let from_body = serde_json
+                    ::from_str(body)
+                    .ok()
+                    .and_then(|body| find_resets_at(&body))
+                    .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+                let from_header = retry_after.and_then(|value| {
+                    // `Retry-After` is either a number of seconds or an HTTP date.
+                    let value = value.trim();
+                    match value.parse() {
+                        Ok(secs) => Some(SystemTime::now() + Duration::from_secs(secs)),
+                        Err(_) => chrono::DateTime::parse_from_rfc2822(value).ok().map(SystemTime::from),
+                    }
+                });
+                Error::RateLimited {
+                    resets_at: from_body.or(from_header),
+                    message: ApiError::from_body(status, body).message,
+                }
+            }
+            _ => Error::Api(ApiError::from_body(status, body)),
+        }
+    }
+}
+
+/// The category of an `Error`, stable across releases.
+///
+/// Serializes in snake case, such as `rate_limit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The connection failed, was interrupted, or timed out.
+    Transport,
+    /// The cookies or API key are missing, expired or invalid.
+    Auth,
+    /// Too many requests were sent, or the account is out of quota.
+    RateLimit,
+    /// The API answered, but with an error or an unusable response.
+    Api,
+    /// A response could not be parsed.
+    Parse,
+    /// A local file could not be read or written, or a local program such as a browser failed.
+    Io,
+    /// An argument or the configuration is invalid, so retrying cannot help.
+    Validation,
+    /// The caller cancelled the operation.
+    Cancelled,
+}
+
+impl Error {
+    /// Returns the category of the error, looking through any `Context` layers.
+    pub fn kind(&self) -> ErrorKind {
+        match self.root_cause() {
+            Error::HttpRequestFailure(e) if e.is_decode() => ErrorKind::Parse,
+            Error::HttpRequestFailure(e) => e.status().map_or(ErrorKind::Transport, status_kind),
+            Error::JsonParsingFailure(_) | Error::MissingFields { .. } => ErrorKind::Parse,
+            Error::IoOperationFailure(_) | Error::Browser(_) => ErrorKind::Io,
+            Error::FirstByteTimeout(_) | Error::GenerationTimeout(_) | Error::DeadlineExceeded => ErrorKind::Transport,
+            Error::Unauthorized(_) | Error::MissingSessionCookie | Error::NoOrganization => ErrorKind::Auth,
+            Error::RateLimited { .. } => ErrorKind::RateLimit,
+            Error::Api(e) => status_kind(e.status),
+            Error::ModelUnavailable { .. } | Error::EmptyResponse | Error::ConversationNotFound(_) => ErrorKind::Api,
+            Error::MessageNotFound(_) => ErrorKind::Api,
+            Error::ConversionIncomplete { .. } => ErrorKind::Api,
+            Error::InvalidHttpHeaderValue(_) | Error::InvalidHttpHeaderName(_) => ErrorKind::Validation,
+            Error::UnknownModel { .. } | Error::AttachmentsDisabled => {
+                ErrorKind::Validation
+            }
+            Error::AttachmentTooLarge { .. } | Error::PromptTooLong { .. } => ErrorKind::Validation,
+            Error::UnknownSession(_) | Error::InvalidMagicLink(_) | Error::NotEditable(_) => ErrorKind::Validation,
+            Error::NoArchiveMatch(_) => ErrorKind::Validation,
+            Error::Cancelled => ErrorKind::Cancelled,
+            Error::Context { .. } => unreachable!("root_cause never returns a Context"),
+        }
+    }
+
+    /// Returns whether sending the same request again may succeed.
+    ///
+    /// Transport failures, rate limiting, server errors, empty responses and incomplete document conversions
+    /// are retryable, as are unavailable models whose quota is known to reset (see `retry_after`). The caller's
+    /// own deadline passing is not, since a retry would exceed it too.
+    pub fn is_retryable(&self) -> bool {
+        match self.root_cause() {
+            Error::DeadlineExceeded => false,
+            Error::ModelUnavailable { .. } => self.retry_after().is_some(),
+            Error::EmptyResponse | Error::ConversionIncomplete { .. } => true,
+            Error::Api(e) if e.status.is_server_error() => true,
+            Error::HttpRequestFailure(e) if e.status().is_some_and(|s| s.is_server_error()) => true,
+            e => matches!(e.kind(), ErrorKind::Transport | ErrorKind::RateLimit),
+        }
+    }
+
+    /// Returns how long to wait before retrying, when the API said when its limit resets.
+    ///
+    /// claude.ai reports the end of rate limiting and quota periods as a `resetsAt` Unix timestamp in the
+    /// error body, sometimes inside a JSON-encoded `message` string; both forms are recognized, as is the
+    /// `Retry-After` header. A limit that has already reset yields a zero duration.
+    pub fn retry_after(&self) -> Option<Duration> {
+        let resets_at = match self.root_cause() {
+            Error::RateLimited { resets_at, .. } => (*resets_at)?,
+            Error::ModelUnavailable { message, .. } => {
+                UNIX_EPOCH + Duration::from_secs(find_resets_at(&serde_json::from_str(message).ok()?)?)
+            }
+            _ => {
+                return None;
+            }
+        };
+        Some(resets_at.duration_since(SystemTime::now()).unwrap_or_default())
+    }
+
+    /// Returns whether the error means the credentials are missing, expired or invalid.
+    pub fn is_auth(&self) -> bool {
+        self.kind() == ErrorKind::Auth
+    }
+
+    /// Returns whether the request was rejected because of rate limiting or quota.
+    pub fn is_rate_limit(&self) -> bool {
+        self.kind() == ErrorKind::RateLimit
+    }
+
+    /// Returns the innermost error, skipping any `Context` layers.
+    ///
+    /// Match on the root cause rather than on the error itself to handle a specific failure, since public
+    /// operations wrap their errors with a description of what they were doing.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root_cause(),
+            e => e,
+        }
+    }
+}
+
+/// Adds a description of the operation in progress to an error.
+pub trait Context<T> {
+    /// Wraps the error, if any, in an `Error::Context` with the given description.
+    fn context(self, context: impl Into<String>) -> Result<T, Error>;
+
+    /// Wraps the error, if any, in an `Error::Context` whose description is computed lazily.
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, f: F) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> Context<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, Error> {
+        self.with_context(|| context)
+    }
+
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, f: F) -> Result<T, Error> {
+        self.map_err(|e| Error::Context { context: f().into(), source: Box::new(e.into()) })
+    }
+}
+
+/// Maps an HTTP error status to the category of the error.
+fn status_kind(status: StatusCode) -> ErrorKind {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorKind::Auth,
+        StatusCode::TOO_MANY_REQUESTS => ErrorKind::RateLimit,
+        StatusCode::REQUEST_TIMEOUT => ErrorKind::Transport,
+        _ => ErrorKind::Api,
+    }
+}
+
+/// Looks for a `resetsAt` timestamp anywhere in an error body, including in JSON-encoded strings.
+fn find_resets_at(value: &Value) -> Option<u64> {
+    match value {
+        Value::Object(map) => {
+            if let Some(resets_at) = map.get("resetsAt").and_then(Value::as_u64) {
+                return Some(resets_at);
+            }
+            map.values().find_map(find_resets_at)
+        }
+        Value::Array(values) => values.iter().find_map(find_resets_at),
+        Value::String(s) if s.trim_start().starts_with('{') => find_resets_at(&serde_json::from_str(s).ok()?),
+        _ => None,
+    }
 }
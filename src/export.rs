@@ -0,0 +1,583 @@
+use flate2::{ write::GzEncoder, Compression };
+use serde::{ Deserialize, Serialize };
+use serde_json::{ json, Value };
+use std::{ fs::{ self, File }, io::{ Read, Seek, Write }, panic, path::PathBuf };
+
+use crate::{ ChatMessage, Client, Conversation, ConversationSnapshot, Error, Result, Sender };
+
+pub mod anonymize;
+
+/// A serializer for conversation transcripts.
+///
+/// Exporters receive a conversation in three steps — the header, each message in order, and a final call to
+/// `finish` — so custom formats (org-mode, AsciiDoc, ...) can be added by implementing this trait.
+pub trait Exporter {
+    /// Writes the conversation metadata that precedes the messages.
+    fn write_header(&mut self, conversation: &Conversation) -> Result<()>;
+
+    /// Writes a single message.
+    fn write_message(&mut self, message: &ChatMessage) -> Result<()>;
+
+    /// Completes the document and flushes the underlying writer.
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// The built-in export formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    Html,
+    /// One `{"messages": [{"role", "content"}, ...]}` line per conversation, as expected by OpenAI fine-tuning.
+    OpenAiJsonl,
+    /// One Messages API `{"messages": [...]}` line per conversation, with content blocks, as expected by
+    /// Anthropic fine-tuning and evaluation tools.
+    AnthropicJsonl,
+}
+
+impl ExportFormat {
+    /// Returns the file extension used for documents in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+            ExportFormat::OpenAiJsonl | ExportFormat::AnthropicJsonl => "jsonl",
+        }
+    }
+
+    /// Creates an exporter for this format writing to `writer`.
+    pub fn exporter<'w, W: Write + 'w>(&self, writer: W) -> Box<dyn Exporter + 'w> {
+        match self {
+            ExportFormat::Markdown => Box::new(MarkdownExporter::new(writer)),
+            ExportFormat::Json => Box::new(JsonExporter::new(writer)),
+            ExportFormat::Html => Box::new(HtmlExporter::new(writer)),
+            ExportFormat::OpenAiJsonl => Box::new(JsonlExporter::new(writer, JsonlDialect::OpenAi)),
+            ExportFormat::AnthropicJsonl => Box::new(JsonlExporter::new(writer, JsonlDialect::Anthropic)),
+        }
+    }
+}
+
+/// Feeds a conversation and its messages to an exporter.
+///
+/// # Errors
+///
+/// This function will return an error if the exporter fails to write.
+pub fn write_conversation(
+    exporter: &mut dyn Exporter,
+    conversation: &Conversation,
+    messages: &[ChatMessage]
+) -> Result<()> {
+    exporter.write_header(conversation)?;
+    for message in messages {
+        exporter.write_message(message)?;
+    }
+    exporter.finish()
+}
+
+/// A conversation with its messages, as written by `JsonExporter`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Transcript {
+    pub conversation: Conversation,
+    pub messages: Vec<ChatMessage>,
+}
+
+impl Transcript {
+    /// Reads a transcript exported in the `Json` format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading fails or if the document is not a JSON export.
+    pub fn read(reader: impl Read) -> Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Writes the transcript with an exporter, in any format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the exporter fails to write.
+    pub fn write(&self, exporter: &mut dyn Exporter) -> Result<()> {
+        write_conversation(exporter, &self.conversation, &self.messages)
+    }
+
+    /// Merges two transcripts chronologically, for a topic that was split across conversations.
+    ///
+    /// Messages are ordered by the time they were sent; a message without a timestamp stays right after the
+    /// message preceding it in its own conversation. Each message text starts with a provenance marker such as
+    /// `[from "CV review", message 3]` naming the conversation (or its UUID when it is unnamed) and the index
+    /// of the message in it, and messages are numbered again from 0. The merged conversation keeps the UUID of
+    /// `self` and combines both names and summaries.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::export::{ ExportFormat, Transcript };
+    /// use std::fs::File;
+    /// # fn example() -> claude::Result<()> {
+    /// let first = Transcript::read(File::open("first.json")?)?;
+    /// let second = Transcript::read(File::open("second.json")?)?;
+    /// let merged = first.merge(&second);
+    /// merged.write(&mut *ExportFormat::Markdown.exporter(File::create("merged.md")?))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn merge(&self, other: &Transcript) -> Transcript {
+        let mut messages: Vec<_> = [self, other]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(source, transcript)| {
+                let label = if transcript.conversation.name.is_empty() {
+                    &transcript.conversation.uuid
+                } else {
+                    &transcript.conversation.name
+                };
+                let mut sent_at = None;
+                transcript.messages.iter().enumerate().map(move |(position, message)| {
+                    sent_at = message.created_at.or(sent_at);
+                    let mut message = message.clone();
+                    message.text = format!("[from \"{}\", message {}]\n{}", label, message.index, message.text);
+                    ((sent_at, source, position), message)
+                })
+            })
+            .collect();
+        messages.sort_by_key(|(key, _)| *key);
+
+        let summary = |conversation: &Conversation| conversation.summary.clone().unwrap_or_default();
+        let join = |a: &str, b: &str| {
+            match (a.is_empty(), b.is_empty()) {
+                (true, _) => b.to_string(),
+                (_, true) => a.to_string(),
+                _ => format!("{} + {}", a, b),
+            }
+        };
+        let conversation = Conversation {
+            name: join(&self.conversation.name, &other.conversation.name),
+            summary: Some(join(&summary(&self.conversation), &summary(&other.conversation))).filter(|s| !s.is_empty()),
+            updated_at: self.conversation.updated_at.max(other.conversation.updated_at),
+            ..self.conversation.clone()
+        };
+        let messages = messages
+            .into_iter()
+            .enumerate()
+            .map(|(index, (_, message))| ChatMessage { index, ..message })
+            .collect();
+        Transcript { conversation, messages }
+    }
+}
+
+/// Exports conversations as Markdown, one section per message.
+#[derive(Debug)]
+pub struct MarkdownExporter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> MarkdownExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Exporter for MarkdownExporter<W> {
+    fn write_header(&mut self, conversation: &Conversation) -> Result<()> {
+        writeln!(self.writer, "# {}\n", conversation.name)?;
+        if let Some(summary) = conversation.summary.as_deref().filter(|summary| !summary.is_empty()) {
+            writeln!(self.writer, "> {}\n", summary)?;
+        }
+        Ok(())
+    }
+
+    fn write_message(&mut self, message: &ChatMessage) -> Result<()> {
+        writeln!(self.writer, "## {}\n", message.sender)?;
+        for attachment in &message.attachments {
+            writeln!(self.writer, "*Attachment: {}*\n", attachment.file_name)?;
+        }
+        writeln!(self.writer, "{}\n", message.text)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Exports conversations as a single JSON object holding the conversation and its messages.
+#[derive(Debug)]
+pub struct JsonExporter<W: Write> {
+    writer: W,
+    first: bool,
+}
+
+impl<W: Write> JsonExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, first: true }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Exporter for JsonExporter<W> {
+    fn write_header(&mut self, conversation: &Conversation) -> Result<()> {
+        self.first = true;
+        self.writer.write_all(b"{\"conversation\":")?;
+        serde_json::to_writer(&mut self.writer, conversation)?;
+        self.writer.write_all(b",\"messages\":[")?;
+        Ok(())
+    }
+
+    fn write_message(&mut self, message: &ChatMessage) -> Result<()> {
+        if !self.first {
+            self.writer.write_all(b",")?;
+        }
+        self.first = false;
+        serde_json::to_writer(&mut self.writer, message)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.write_all(b"]}\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Exports conversations as a standalone HTML page.
+#[derive(Debug)]
+pub struct HtmlExporter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> HtmlExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Exporter for HtmlExporter<W> {
+    fn write_header(&mut self, conversation: &Conversation) -> Result<()> {
+        let name = escape_html(&conversation.name);
+        writeln!(self.writer, "<!DOCTYPE html>")?;
+        writeln!(self.writer, "<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>", name)?;
+        writeln!(self.writer, "<body>\n<h1>{}</h1>", name)?;
+        if let Some(summary) = conversation.summary.as_deref().filter(|summary| !summary.is_empty()) {
+            writeln!(self.writer, "<p><em>{}</em></p>", escape_html(summary))?;
+        }
+        Ok(())
+    }
+
+    fn write_message(&mut self, message: &ChatMessage) -> Result<()> {
+        writeln!(self.writer, "<section class=\"{}\">", escape_html(message.sender.as_str()))?;
+        writeln!(self.writer, "<h2>{}</h2>", escape_html(message.sender.as_str()))?;
+        for attachment in &message.attachments {
+            writeln!(self.writer, "<p><em>Attachment: {}</em></p>", escape_html(&attachment.file_name))?;
+        }
+        writeln!(self.writer, "<pre>{}</pre>\n</section>", escape_html(&message.text))?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        writeln!(self.writer, "</body>\n</html>")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// The message layout of a `JsonlExporter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonlDialect {
+    /// Content is a single string, with attachments inlined as `<document>` blocks before the text.
+    OpenAi,
+    /// Content is a list of text blocks, one per attachment followed by one for the text.
+    Anthropic,
+}
+
+/// Exports conversations as fine-tuning datasets, one JSON line per conversation.
+///
+/// Messages from the account are given the `user` role and answers the `assistant` role. Each message is written
+/// as soon as it is received, and the line is closed by `finish`. The exporter can be fed several conversations
+/// in a row to build a whole dataset in one file.
+///
+/// # Examples
+///
+/// ```
+/// use claude::{ export::{ write_conversation, JsonlDialect, JsonlExporter }, ChatMessage, Conversation };
+///
+/// let conversation: Conversation = serde_json::from_str(
+///     r#"{"uuid": "1", "name": "Greeting", "summary": ""}"#
+/// ).unwrap();
+/// let messages: Vec<ChatMessage> = serde_json::from_str(r#"[
+///     {"uuid": "a", "attachments": [], "sender": "human", "index": 0, "text": "Hi"},
+///     {"uuid": "b", "attachments": [], "sender": "assistant", "index": 1, "text": "Hello!"}
+/// ]"#).unwrap();
+///
+/// let mut exporter = JsonlExporter::new(Vec::new(), JsonlDialect::OpenAi);
+/// write_conversation(&mut exporter, &conversation, &messages).unwrap();
+/// assert_eq!(
+///     String::from_utf8(exporter.into_inner()).unwrap(),
+///     "{\"messages\":[{\"content\":\"Hi\",\"role\":\"user\"},{\"content\":\"Hello!\",\"role\":\"assistant\"}]}\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct JsonlExporter<W: Write> {
+    writer: W,
+    dialect: JsonlDialect,
+    /// Whether a message was written on the current line, so the next one is preceded by a comma.
+    separated: bool,
+}
+
+impl<W: Write> JsonlExporter<W> {
+    pub fn new(writer: W, dialect: JsonlDialect) -> Self {
+        Self { writer, dialect, separated: false }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Exporter for JsonlExporter<W> {
+    fn write_header(&mut self, _conversation: &Conversation) -> Result<()> {
+        self.separated = false;
+        self.writer.write_all(b"{\"messages\":[")?;
+        Ok(())
+    }
+
+    fn write_message(&mut self, message: &ChatMessage) -> Result<()> {
+        let role = if message.sender == Sender::Assistant { "assistant" } else { "user" };
+        let content = match self.dialect {
+            JsonlDialect::OpenAi => {
+                let mut content = String::new();
+                for attachment in &message.attachments {
+                    content.push_str(
+                        &format!(
+                            "<document name=\"{}\">\n{}\n</document>\n\n",
+                            escape_html(&attachment.file_name),
+                            attachment.extracted_content
+                        )
+                    );
+                }
+                content.push_str(&message.text);
+                Value::from(content)
+            }
+            JsonlDialect::Anthropic => {
+                let documents = message.attachments.iter().map(|attachment| {
+                    let text = format!(
+                        "<document name=\"{}\">\n{}\n</document>",
+                        escape_html(&attachment.file_name),
+                        attachment.extracted_content
+                    );
+                    json!({ "type": "text", "text": text })
+                });
+                let text = json!({ "type": "text", "text": message.text });
+                Value::from(documents.chain(std::iter::once(text)).collect::<Vec<_>>())
+            }
+        };
+        if self.separated {
+            self.writer.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut self.writer, &json!({ "role": role, "content": content }))?;
+        self.separated = true;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.write_all(b"]}\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A temporary file holding one exported document at a time, removed when dropped.
+struct Spool {
+    path: PathBuf,
+    file: File,
+}
+
+impl Spool {
+    fn create() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("claude-export-{}.part", uuid::Uuid::new_v4()));
+        let file = File::options().read(true).write(true).create_new(true).open(&path)?;
+        Ok(Self { path, file })
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A `.tar.gz` archive being written, one exported conversation at a time.
+///
+/// Every method blocks on the file system and on `W`, so they run in `blocking`.
+struct ArchiveWriter<W: Write> {
+    archive: tar::Builder<GzEncoder<W>>,
+    spool: Spool,
+    format: ExportFormat,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    fn new(writer: W, format: ExportFormat) -> Result<Self> {
+        let archive = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+        Ok(Self { archive, spool: Spool::create()?, format })
+    }
+
+    /// Exports the conversation into the spool, then appends the spool as one entry of the archive.
+    fn append(&mut self, snapshot: ConversationSnapshot) -> Result<()> {
+        let (conversation, messages) = snapshot.into_parts();
+        let file = &mut self.spool.file;
+        file.set_len(0)?;
+        file.rewind()?;
+        let mut exporter = self.format.exporter(&mut *file);
+        exporter.write_header(&conversation)?;
+        // Each message is dropped as soon as it is written.
+        for message in messages {
+            exporter.write_message(&message)?;
+        }
+        exporter.finish()?;
+        drop(exporter);
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(file.stream_position()?);
+        header.set_mode(0o644);
+        header.set_cksum();
+        file.rewind()?;
+        let path = format!("{}.{}", conversation.uuid, self.format.extension());
+        self.archive.append_data(&mut header, path, &mut *file)?;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<W> {
+        Ok(self.archive.into_inner()?.finish()?)
+    }
+}
+
+/// Runs blocking file and compression work on the blocking thread pool, keeping the runtime responsive.
+async fn blocking<T: Send + 'static>(work: impl (FnOnce() -> Result<T>) + Send + 'static) -> Result<T> {
+    match tokio::task::spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+        Err(_) => Err(Error::Cancelled),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Client {
+    /// Backs up every conversation of the account into a gzip-compressed tar archive.
+    ///
+    /// Each conversation becomes one `<uuid>.<extension>` entry in `format`. Conversations are fetched one at a
+    /// time with `conversation_snapshot`, so the metadata and messages of each entry are from the same moment.
+    /// Their messages are handed to the exporter one by one into a temporary file, since a tar entry starts with
+    /// its size. Only the messages of a single conversation are held in memory, never the exported documents,
+    /// while the archive streams to `writer`. The spooling, compression and writes run on the blocking thread
+    /// pool, so only the requests are awaited on the runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination of the `.tar.gz` archive, such as a `File`.
+    /// * `format` - The format of each exported conversation.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<W>` - The writer, once the archive has been completed. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a conversation cannot be retrieved or if writing the archive fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ export::ExportFormat, Client };
+    /// use std::env::var;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     dotenv::dotenv().ok();
+    ///     let cookies = format!(
+    ///         "activitySessionId={}; sessionKey={}",
+    ///         var("SESSION_ID").unwrap(),
+    ///         var("SESSION_KEY").unwrap()
+    ///     );
+    ///     let client = Client::new(cookies).await;
+    ///     let file = std::fs::File::create("backup.tar.gz").unwrap();
+    ///     client.export_account_archive(file, ExportFormat::Markdown).await.unwrap();
+    /// }
+    /// ```
+    pub async fn export_account_archive<W: Write + Send + 'static>(&self, writer: W, format: ExportFormat) -> Result<W> {
+        let mut archive = blocking(move || ArchiveWriter::new(writer, format)).await?;
+
+        for listed in self.list_all_conversations().await? {
+            let snapshot = self.conversation_snapshot(&listed.uuid).await?;
+            archive = blocking(move || {
+                archive.append(snapshot)?;
+                Ok(archive)
+            }).await?;
+        }
+
+        blocking(move || archive.finish()).await
+    }
+
+    /// Exports every conversation of the account as a fine-tuning dataset, one JSON line per conversation.
+    ///
+    /// Each conversation is fetched with `conversation_snapshot`, in a single request.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination of the `.jsonl` dataset, such as a `File`.
+    /// * `dialect` - The message layout of each line.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<W>` - The writer, once every conversation has been written. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a conversation cannot be retrieved or if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ export::JsonlDialect, Client };
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let file = std::fs::File::create("dataset.jsonl")?;
+    /// client.export_dataset(file, JsonlDialect::OpenAi).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_dataset<W: Write>(&self, writer: W, dialect: JsonlDialect) -> Result<W> {
+        let mut exporter = JsonlExporter::new(writer, dialect);
+        for listed in self.list_all_conversations().await? {
+            self.conversation_snapshot(&listed.uuid).await?.write(&mut exporter)?;
+        }
+        Ok(exporter.into_inner())
+    }
+}
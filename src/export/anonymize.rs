@@ -0,0 +1,185 @@
+use crate::{ utils, ChatMessage, Conversation, Result };
+
+use super::Exporter;
+
+/// Scrubs transcripts before they are exported, so they can be shared without manual review.
+///
+/// Every replaced string becomes a pseudonym derived from its salted SHA-256 hash, such as `[name:3f2a9c1e]`,
+/// so the same person or address keeps the same pseudonym throughout a transcript and across transcripts
+/// anonymized with the same salt. Use a secret salt when the identifiers could be guessed and checked against
+/// their hashes.
+///
+/// # Examples
+///
+/// ```
+/// use claude::export::anonymize::Anonymizer;
+///
+/// let anonymizer = Anonymizer {
+///     identifiers: vec!["Ada Lovelace".to_string()],
+///     ..Anonymizer::default()
+/// };
+/// let text = anonymizer.anonymize_text("Ada Lovelace <ada@example.com> wrote this.");
+/// assert!(!text.contains("Ada") && !text.contains("example.com"));
+/// assert!(text.starts_with("[name:") && text.contains("<[email:"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    /// Strings identifying people, such as names or account handles, replaced wherever they appear.
+    pub identifiers: Vec<String>,
+    /// Mixed into every hash.
+    pub salt: String,
+    /// Whether email addresses are replaced.
+    pub redact_emails: bool,
+    /// Whether the extracted text of attachments is removed, keeping only their names and sizes.
+    pub strip_attachments: bool,
+    /// Whether the UUIDs of conversations, messages and attachments are replaced, so they cannot be looked up.
+    pub hash_ids: bool,
+}
+
+impl Default for Anonymizer {
+    fn default() -> Self {
+        Self {
+            identifiers: vec![],
+            salt: String::new(),
+            redact_emails: true,
+            strip_attachments: true,
+            hash_ids: true,
+        }
+    }
+}
+
+impl Anonymizer {
+    /// Returns the pseudonym of `value`, labeled with `kind`.
+    fn pseudonym(&self, kind: &str, value: &str) -> String {
+        let hash = utils::sha256_hex(format!("{}{}", self.salt, value).as_bytes());
+        format!("[{}:{}]", kind, &hash[..8])
+    }
+
+    /// Replaces the identifiers and, if enabled, the email addresses found in `text`.
+    pub fn anonymize_text(&self, text: &str) -> String {
+        let mut text = if self.redact_emails { self.replace_emails(text) } else { text.to_string() };
+        // Longer identifiers first, so a name is not partially replaced by one of its parts.
+        let mut identifiers: Vec<&String> = self.identifiers
+            .iter()
+            .filter(|identifier| !identifier.is_empty())
+            .collect();
+        identifiers.sort_by_key(|identifier| std::cmp::Reverse(identifier.len()));
+        for identifier in identifiers {
+            text = text.replace(identifier.as_str(), &self.pseudonym("name", identifier));
+        }
+        text
+    }
+
+    /// Replaces every email address of `text` with its pseudonym.
+    fn replace_emails(&self, text: &str) -> String {
+        let is_local = |c: char| c.is_ascii_alphanumeric() || "._%+-".contains(c);
+        let is_domain = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-';
+
+        let mut anonymized = String::with_capacity(text.len());
+        let mut copied = 0;
+        for (at, _) in text.match_indices('@') {
+            if at < copied {
+                continue;
+            }
+            let start = text[copied..at]
+                .char_indices()
+                .rev()
+                .take_while(|&(_, c)| is_local(c))
+                .last()
+                .map_or(at, |(index, _)| copied + index);
+            let domain = &text[at + 1..];
+            let domain = &domain[..domain.find(|c| !is_domain(c)).unwrap_or(domain.len())];
+            let domain = domain.trim_end_matches(['.', '-']);
+            if start == at || !domain.contains('.') || domain.starts_with('.') {
+                continue;
+            }
+            let end = at + 1 + domain.len();
+            anonymized.push_str(&text[copied..start]);
+            anonymized.push_str(&self.pseudonym("email", &text[start..end].to_lowercase()));
+            copied = end;
+        }
+        anonymized.push_str(&text[copied..]);
+        anonymized
+    }
+
+    /// Returns an anonymized copy of a conversation.
+    ///
+    /// Fields the crate does not know, kept in `Conversation::extra`, are dropped, since they cannot be scrubbed.
+    pub fn anonymize_conversation(&self, conversation: &Conversation) -> Conversation {
+        let mut conversation = conversation.clone();
+        if self.hash_ids {
+            conversation.uuid = self.pseudonym("conversation", &conversation.uuid);
+            conversation.project_uuid = conversation.project_uuid.map(|uuid| self.pseudonym("project", &uuid));
+        }
+        conversation.name = self.anonymize_text(&conversation.name);
+        conversation.summary = conversation.summary.as_deref().map(|summary| self.anonymize_text(summary));
+        conversation.extra.clear();
+        conversation
+    }
+
+    /// Returns an anonymized copy of a message.
+    ///
+    /// The parent of the message is hashed like its UUID, so branches still link up in the export. Fields the
+    /// crate does not know, kept in `ChatMessage::extra`, are dropped, since they cannot be scrubbed.
+    pub fn anonymize_message(&self, message: &ChatMessage) -> ChatMessage {
+        let mut message = message.clone();
+        if self.hash_ids {
+            message.uuid = self.pseudonym("message", &message.uuid);
+            message.parent_message_uuid = message.parent_message_uuid.map(|uuid| self.pseudonym("message", &uuid));
+        }
+        message.text = self.anonymize_text(&message.text);
+        message.extra.clear();
+        for attachment in &mut message.attachments {
+            if self.hash_ids {
+                attachment.id = self.pseudonym("attachment", &attachment.id);
+            }
+            attachment.file_name = self.anonymize_text(&attachment.file_name);
+            attachment.extracted_content = if self.strip_attachments {
+                String::new()
+            } else {
+                self.anonymize_text(&attachment.extracted_content)
+            };
+        }
+        message
+    }
+}
+
+/// Anonymizes conversations with an `Anonymizer` before passing them to another exporter.
+///
+/// # Examples
+///
+/// ```
+/// use claude::export::{ anonymize::{ AnonymizingExporter, Anonymizer }, MarkdownExporter };
+///
+/// let exporter = AnonymizingExporter::new(MarkdownExporter::new(Vec::new()), Anonymizer::default());
+/// ```
+#[derive(Debug)]
+pub struct AnonymizingExporter<E: Exporter> {
+    inner: E,
+    anonymizer: Anonymizer,
+}
+
+impl<E: Exporter> AnonymizingExporter<E> {
+    pub fn new(inner: E, anonymizer: Anonymizer) -> Self {
+        Self { inner, anonymizer }
+    }
+
+    /// Returns the wrapped exporter.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+
+impl<E: Exporter> Exporter for AnonymizingExporter<E> {
+    fn write_header(&mut self, conversation: &Conversation) -> Result<()> {
+        self.inner.write_header(&self.anonymizer.anonymize_conversation(conversation))
+    }
+
+    fn write_message(&mut self, message: &ChatMessage) -> Result<()> {
+        self.inner.write_message(&self.anonymizer.anonymize_message(message))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()
+    }
+}
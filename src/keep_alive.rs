@@ -0,0 +1,73 @@
+use std::{ sync::{ Arc, Weak }, time::Duration };
+use tokio::task::JoinHandle;
+use tracing::{ debug, warn };
+
+use crate::Client;
+
+/// Handle to a background keep-alive task started with `Client::spawn_keep_alive`.
+///
+/// The task stops when the handle is dropped, when `stop` is called, or once the client itself is dropped.
+#[derive(Debug)]
+pub struct KeepAlive {
+    task: JoinHandle<()>,
+}
+
+impl KeepAlive {
+    /// Stops the keep-alive task.
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Client {
+    /// Starts a background task that keeps the session and connection warm during idle periods.
+    ///
+    /// Every `interval`, the task performs a lightweight authenticated request (see `warm_up`). Failures are
+    /// logged and do not stop the task. The task only holds a weak reference to the client, so it ends on its
+    /// own once the client is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time between two keep-alive requests.
+    ///
+    /// # Returns
+    ///
+    /// * `KeepAlive` - A handle that stops the task when dropped.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use std::{ sync::Arc, time::Duration };
+    /// # async fn example(cookies: String) {
+    /// let client = Arc::new(Client::new(cookies).await);
+    /// let _keep_alive = client.spawn_keep_alive(Duration::from_secs(300));
+    /// # }
+    /// ```
+    pub fn spawn_keep_alive(self: &Arc<Self>, interval: Duration) -> KeepAlive {
+        let client: Weak<Client> = Arc::downgrade(self);
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let Some(client) = client.upgrade() else {
+                    debug!(operation = "keep_alive", "client dropped, stopping keep-alive");
+                    return;
+                };
+                if let Err(e) = client.warm_up(true).await {
+                    warn!(operation = "keep_alive", error = %e, "keep-alive request failed");
+                }
+            }
+        });
+        KeepAlive { task }
+    }
+}
@@ -1,45 +1,569 @@
+mod cache;
+pub mod account;
+pub mod api;
+#[cfg(feature = "store")]
+pub mod archive;
+pub mod artifacts;
+pub mod auth;
+pub mod backend;
+#[cfg(feature = "browser")]
+pub mod browser;
+pub mod builder;
+pub mod cancel;
+pub mod cloudflare;
+pub mod deadline;
 pub mod error;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod keep_alive;
+#[cfg(feature = "login")]
+pub mod login;
+pub mod manager;
+pub mod model;
+pub mod payload;
+#[cfg(feature = "otel")]
+mod otel;
+pub mod project;
+mod rate_limit;
+pub mod retry;
+pub mod session;
+pub mod similarity;
+pub mod snapshot;
+pub mod sse;
+#[cfg(feature = "store")]
+pub mod store;
+pub mod summarize;
+pub mod throttle;
+pub mod topics;
+pub mod tree;
+pub mod truncation;
+pub mod utils;
+pub mod watchdog;
 
 use reqwest::{
     header::{ HeaderValue, HeaderMap, ACCEPT, ORIGIN, REFERER, COOKIE, CONNECTION, USER_AGENT },
-    multipart::{ Part, Form },
-    Body,
+    Response,
+    StatusCode,
 };
-use serde_json::Value;
-use tokio::fs::File;
+#[cfg(feature = "attachments")]
+use reqwest::{ multipart::{ Part, Form }, Body };
+#[cfg(feature = "attachments")]
+use futures_util::FutureExt;
+use futures_util::{ future::BoxFuture, stream, Stream, StreamExt, TryStreamExt };
+use serde_json::{ Map, Value };
+use builder::{ HttpOptions, DEFAULT_BASE_URL };
+use cache::{ AnswerCache, AnswerKey };
+#[cfg(feature = "attachments")]
+use cache::AttachmentCache;
+use payload::{ AppendMessageRequest, CompletionParams };
+use auth::AuthHook;
+use rate_limit::RateLimiter;
+#[cfg(feature = "attachments")]
+use bytes::Bytes;
+#[cfg(feature = "attachments")]
+use tokio::{ fs::File, io::AsyncReadExt };
+#[cfg(feature = "attachments")]
 use tokio_util::codec::{ BytesCodec, FramedRead };
-use tracing::{ debug, error };
-use serde::Deserialize;
-use std::{ time::Duration, path::Path };
+use tracing::{ debug, error, field, info, info_span, warn, Instrument };
+use serde::{ Deserialize, Serialize };
+use std::{
+    collections::VecDeque,
+    sync::{ atomic::{ AtomicU64, Ordering }, Arc, OnceLock },
+    time::{ Duration, Instant },
+};
+#[cfg(feature = "attachments")]
+use std::{ collections::HashMap, path::Path };
 
-pub use error::Error;
+pub use artifacts::Artifact;
+pub use builder::ClientBuilder;
+pub use cancel::MessageHandle;
+pub use cloudflare::CloudflarePolicy;
+pub use deadline::with_deadline;
+pub use error::{ ApiError, Context, Error, ErrorKind };
+pub use model::Model;
+pub use project::{ Project, ProjectDoc, ProjectInfo };
+pub use retry::{ RetryPolicy, RetryStrategy };
+pub use session::ChatSession;
+pub use snapshot::ConversationSnapshot;
+pub use sse::StreamEvent;
+pub use truncation::{ TruncationPolicy, TruncationReport };
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Debug)]
+/// A claude.ai web client, authenticated with session cookies.
+///
+/// Cloning is cheap: clones share the connection pool and the attachment and answer caches, while their
+/// public settings can be changed independently. The session cookies are left out of `Debug` output.
+#[derive(Clone)]
 pub struct Client {
     pub org_uuid: String,
-    pub cookies: String,
+    /// The session cookies, read with `cookies`. The HTTP client reused by every request is built from them in `new`,
+    /// so they cannot be changed afterwards.
+    cookies: String,
+    http: reqwest::Client,
+    /// Default headers of `http`, including the cookies, reused when building proxy clients.
+    headers: HeaderMap,
+    base_url: String,
+    http_options: HttpOptions,
+    /// The model messages are sent to, unless overridden per call with `send_message_with_model`.
+    pub default_model: Model,
+    /// Models tried, in order, when the default model is unavailable or out of quota.
+    pub fallback_models: Vec<Model>,
+    /// When set, `send_message` checks that the conversation exists before uploading attachments.
+    pub validate_before_send: bool,
+    /// Title template given to conversations created by `create_new_chat`, so automated conversations are
+    /// identifiable in the web UI. `{date}`, `{time}` and `{n}` are filled as described in `utils::render_title`,
+    /// with `{n}` counting the conversations created by this client from 1. Conversations are nameless when unset.
+    pub title_template: Option<String>,
+    /// The IANA time zone sent with messages, such as `Europe/Paris`, which the model uses to tell the local time.
+    /// Detected from the machine by default, or set with `ClientBuilder::timezone`.
+    pub timezone: String,
+    /// When set, `send_message` always sends the message and refreshes the answer cache instead of reading from it.
+    pub bypass_answer_cache: bool,
+    /// Controls how much of each response body is written to debug logs.
+    pub body_logging: BodyLogging,
+    /// Maximum time `send_message` waits for the first byte of the answer, distinct from the total generation timeout.
+    pub first_byte_timeout: Duration,
+    /// Maximum number of conversations `reset_all` deletes at the same time. Values below 1 are treated as 1.
+    pub delete_concurrency: usize,
+    /// When set, fetched conversations and messages lacking a field the crate otherwise leaves as `None`, such
+    /// as `Conversation::summary`, the settings or the timestamps, fail with `Error::MissingFields`. Off by default, so that
+    /// claude.ai dropping a field does not break whole requests; turn it on in tests to notice.
+    pub strict_responses: bool,
+    /// Maximum time `upload_attachment` keeps requesting the conversion of a document whose text has not been
+    /// extracted yet.
+    #[cfg(feature = "attachments")]
+    pub conversion_timeout: Duration,
+    /// Maximum number of times `upload_attachment` uploads a document whose text has not been extracted yet,
+    /// whatever is left of `conversion_timeout`. Values below 1 are treated as 1.
+    #[cfg(feature = "attachments")]
+    pub max_conversion_attempts: u32,
+    /// Maximum number of characters of extracted text sent with a message across all its attachments, shared
+    /// equally between the longest ones. Unlimited when unset.
+    #[cfg(feature = "attachments")]
+    pub attachment_budget: Option<usize>,
+    /// How attachments over `attachment_budget` are truncated before sending, with a report of what was dropped
+    /// in `Completion::truncated`.
+    #[cfg(feature = "attachments")]
+    pub truncation_policy: TruncationPolicy,
+    /// Size, in bytes, over which the text files attached to a message are split into several attachments, set
+    /// with `ClientBuilder::max_attachment_size`. Other files are always sent whole.
+    #[cfg(feature = "attachments")]
+    pub max_attachment_size: Option<u64>,
+    /// Maximum number of files of a message uploaded at the same time. Values below 1 are treated as 1.
+    #[cfg(feature = "attachments")]
+    pub upload_concurrency: usize,
+    #[cfg(feature = "attachments")]
+    attachment_cache: Arc<AttachmentCache>,
+    #[cfg(feature = "attachments")]
+    mime_types: Arc<HashMap<String, String>>,
+    #[cfg(feature = "attachments")]
+    upload_progress: Option<ProgressCallback>,
+    chats_created: Arc<AtomicU64>,
+    answer_cache: Option<Arc<AnswerCache>>,
+    cloudflare: Option<CloudflarePolicy>,
+    proxy_clients: Vec<reqwest::Client>,
+    retry: Option<Arc<dyn RetryStrategy>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    auth_hook: Option<Arc<dyn AuthHook>>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("org_uuid", &self.org_uuid)
+            .field("cookies", &"<redacted>")
+            .field("base_url", &self.base_url)
+            .field("default_model", &self.default_model)
+            .field("fallback_models", &self.fallback_models)
+            .field("validate_before_send", &self.validate_before_send)
+            .field("title_template", &self.title_template)
+            .field("timezone", &self.timezone)
+            .field("bypass_answer_cache", &self.bypass_answer_cache)
+            .field("body_logging", &self.body_logging)
+            .field("first_byte_timeout", &self.first_byte_timeout)
+            .field("delete_concurrency", &self.delete_concurrency)
+            .field("strict_responses", &self.strict_responses)
+            .finish_non_exhaustive()
+    }
+}
+
+/// How response bodies are included in debug logs.
+///
+/// Bodies can contain whole conversation histories, so they are not logged unless explicitly enabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BodyLogging {
+    /// Bodies are never logged.
+    #[default]
+    Off,
+    /// Bodies are logged, cut after the given number of characters.
+    Truncated(usize),
+    /// Bodies are logged in full.
+    Full,
+}
+
+impl BodyLogging {
+    /// Renders `body` according to this setting, or returns `None` when bodies are not logged.
+    fn render(&self, body: &dyn std::fmt::Debug) -> Option<String> {
+        match *self {
+            BodyLogging::Off => None,
+            BodyLogging::Full => Some(format!("{:?}", body)),
+            BodyLogging::Truncated(max) => {
+                let body = format!("{:?}", body);
+                match body.char_indices().nth(max) {
+                    Some((end, _)) => Some(format!("{}... ({} bytes truncated)", &body[..end], body.len() - end)),
+                    None => Some(body),
+                }
+            }
+        }
+    }
+}
+
+/// An answer returned by `send_message_detailed`, along with the model that produced it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Completion {
+    pub text: String,
+    pub model: String,
+    /// What was dropped from the attachments to fit `Client::attachment_budget`, if anything.
+    pub truncated: Vec<TruncationReport>,
+}
+
+/// A model the account can send messages to, as listed by `list_models`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModelInfo {
+    /// The identifier sent with messages, such as `claude-2`.
+    #[serde(rename = "model")]
+    pub id: String,
+    /// The display name shown in the web UI, when the API provides one.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Organization {
+    pub uuid: String,
+    pub name: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Conversation {
     pub uuid: String,
     pub name: String,
-    pub summary: String,
+    /// The summary claude.ai writes of the conversation, empty until it is generated, or `None` when the API
+    /// does not say.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Whether the conversation is starred (shown in the favorites of the web UI).
+    #[serde(default, rename = "is_starred")]
+    pub starred: bool,
+    /// The project the conversation belongs to, if any.
+    #[serde(default)]
+    pub project_uuid: Option<String>,
+    /// When the conversation was created, if the API says so.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When a message was last sent to the conversation, or it was last renamed, if the API says so.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The model answering in the conversation, when it differs from the account default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<Model>,
+    /// The settings of the conversation, such as the preview features it uses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Map<String, Value>>,
+    /// Any other field of the conversation, kept so that fields added by claude.ai are not lost.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Which conversations `Client::list_conversations` returns, and in which order.
+///
+/// # Examples
+///
+/// ```
+/// use claude::{ ListParams, SortOrder };
+///
+/// // The third page of 20 conversations, least recently updated first.
+/// let params = ListParams { limit: Some(20), offset: 40, order: SortOrder::OldestFirst };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListParams {
+    /// Maximum number of conversations returned. All the remaining ones when unset.
+    pub limit: Option<usize>,
+    /// Number of conversations skipped, in `order`.
+    pub offset: usize,
+    pub order: SortOrder,
+}
+
+/// The order of listed conversations, by the time they were last updated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Most recently updated first, like the web UI.
+    #[default]
+    NewestFirst,
+    /// Least recently updated first.
+    ///
+    /// claude.ai only lists conversations most recently updated first, so every conversation is fetched
+    /// whatever the `limit` and `offset` of the page are.
+    OldestFirst,
+}
+
+/// How `Client::create_new_chat_with` sets up a new conversation.
+///
+/// # Examples
+///
+/// ```
+/// use claude::{ Model, NewChatOptions };
+///
+/// let options = NewChatOptions {
+///     name: Some("Code review".to_string()),
+///     system_prompt: Some("You are a strict but friendly code reviewer.".to_string()),
+///     model: Some(Model::Claude35Sonnet),
+///     ..NewChatOptions::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewChatOptions {
+    /// The name of the conversation. Rendered from `Client::title_template` when unset.
+    pub name: Option<String>,
+    /// Custom instructions for the whole conversation. The web backend has no system prompt, so they are sent
+    /// as the first message of the conversation.
+    pub system_prompt: Option<String>,
+    /// The model answering in the conversation, instead of the account default.
+    pub model: Option<Model>,
+    /// The project the conversation belongs to, if any.
+    pub project_uuid: Option<String>,
+}
+
+/// The outcome of `Client::reset_all`, which keeps deleting conversations when some deletions fail.
+#[derive(Debug, Default)]
+pub struct ResetSummary {
+    /// The UUIDs of the deleted conversations.
+    pub deleted: Vec<String>,
+    /// The UUIDs of the conversations that could not be deleted, with the reason.
+    pub failed: Vec<(String, Error)>,
 }
 
-#[derive(Debug, Deserialize)]
+impl ResetSummary {
+    /// Returns whether every conversation was deleted.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ChatMessage {
     pub uuid: String,
     pub attachments: Vec<Attachment>,
-    pub sender: String,
+    pub sender: Sender,
     pub index: usize,
     pub text: String,
     #[serde(default)]
     pub chat_feedback: Option<String>,
+    /// When the message was sent, if the API says so.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the message was last changed, if the API says so.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The message this one answers or follows, which tells apart the branches of an edited conversation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_message_uuid: Option<String>,
+    /// Any other field of the message, kept so that fields added by claude.ai are not lost.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Conversation {
+    /// Returns the fields claude.ai usually sends that the conversation lacks, as checked by
+    /// `Client::strict_responses`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use claude::Conversation;
+    ///
+    /// let conversation: Conversation = serde_json::from_value(serde_json::json!({
+    ///     "uuid": "1", "name": "Greeting", "created_at": "2024-01-01T00:00:00Z",
+    /// })).unwrap();
+    /// assert_eq!(conversation.missing_fields(), ["summary", "settings", "updated_at"]);
+    /// ```
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        let fields = [
+            ("summary", self.summary.is_none()),
+            ("settings", self.settings.is_none()),
+            ("created_at", self.created_at.is_none()),
+            ("updated_at", self.updated_at.is_none()),
+        ];
+        fields.into_iter().filter(|(_, missing)| *missing).map(|(field, _)| field).collect()
+    }
+}
+
+impl ChatMessage {
+    /// Returns the fields claude.ai usually sends that the message lacks, as checked by
+    /// `Client::strict_responses`.
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        let fields = [("created_at", self.created_at.is_none()), ("updated_at", self.updated_at.is_none())];
+        fields.into_iter().filter(|(_, missing)| *missing).map(|(field, _)| field).collect()
+    }
+}
+
+/// Who sent a `ChatMessage`.
+///
+/// `Other` keeps any sender claude.ai may introduce, so that messages from it still deserialize.
+///
+/// # Examples
+///
+/// ```
+/// use claude::Sender;
+///
+/// assert_eq!(Sender::from("assistant"), Sender::Assistant);
+/// assert_eq!(Sender::Human.as_str(), "human");
+/// assert_eq!(Sender::from("tool"), Sender::Other("tool".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Sender {
+    /// The user of the account.
+    Human,
+    /// The model.
+    Assistant,
+    /// Any other sender, by name.
+    Other(String),
+}
+
+impl Sender {
+    /// Returns the name claude.ai gives this sender.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Sender::Human => "human",
+            Sender::Assistant => "assistant",
+            Sender::Other(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for Sender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for Sender {
+    fn from(name: &str) -> Self {
+        match name {
+            "human" => Sender::Human,
+            "assistant" => Sender::Assistant,
+            name => Sender::Other(name.to_string()),
+        }
+    }
+}
+
+impl From<String> for Sender {
+    fn from(name: String) -> Self {
+        Sender::from(name.as_str())
+    }
+}
+
+impl Serialize for Sender {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Sender {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        Ok(Sender::from(String::deserialize(deserializer)?))
+    }
+}
+
+/// The result of `send_or_create`: the conversation that received the message and the answer.
+#[derive(Debug, Clone, Serialize)]
+pub struct SendOutcome {
+    /// UUID of the conversation the message was sent to, which differs from the requested one when `created` is set.
+    pub chat_uuid: String,
+    /// Whether a new conversation had to be created because the requested one did not exist.
+    pub created: bool,
+    pub completion: Completion,
+}
+
+/// A document converted by the API, ready to be attached to a message.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UploadedAttachment {
+    /// Hex-encoded SHA-256 digest of the uploaded file contents.
+    #[serde(skip)]
+    pub sha256: String,
+    #[serde(default)]
+    pub file_name: String,
+    #[serde(default)]
+    pub file_size: i64,
+    #[serde(default)]
+    pub file_type: String,
+    /// The text extracted from the document, which is what the model reads.
+    #[serde(default)]
+    pub extracted_content: String,
+    /// The number of pages of paginated documents such as PDFs.
+    #[serde(default, rename = "totalPages", skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<u32>,
+    /// Any other field of the converted document, sent back unchanged with the message.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An image uploaded for a vision-capable model, such as a screenshot.
+///
+/// Unlike documents, images are not converted to text: the message refers to them by `file_uuid`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UploadedImage {
+    pub file_uuid: String,
+    #[serde(default)]
+    pub file_name: String,
+    /// The kind of file claude.ai stored, `image` for images.
+    #[serde(default)]
+    pub file_kind: String,
+    /// Any other field of the upload response, such as the URLs of the thumbnail and preview.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// How much of a file has been sent, reported to the callback set with `ClientBuilder::upload_progress`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UploadProgress {
+    /// The name the file is uploaded under.
+    pub file_name: String,
+    /// Number of bytes sent so far.
+    pub sent: u64,
+    /// Size of the file, in bytes.
+    pub total: u64,
+}
+
+/// The callback set with `ClientBuilder::upload_progress`.
+#[cfg(feature = "attachments")]
+#[derive(Clone)]
+struct ProgressCallback(Arc<dyn Fn(&UploadProgress) + Send + Sync>);
+
+#[cfg(feature = "attachments")]
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// A file uploaded for a message.
+#[cfg(feature = "attachments")]
+enum Uploaded {
+    Document(UploadedAttachment),
+    Image(UploadedImage),
+}
+
+/// The files uploaded for a message: documents fitted to `attachment_budget`, what was truncated from them,
+/// and images.
+#[derive(Debug, Default)]
+struct Uploads {
+    documents: Vec<UploadedAttachment>,
+    truncated: Vec<TruncationReport>,
+    images: Vec<UploadedImage>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Attachment {
     pub id: String,
     pub extracted_content: String,
@@ -48,11 +572,50 @@ pub struct Attachment {
     pub file_type: String,
 }
 
+pub(crate) const DEFAULT_FIRST_BYTE_TIMEOUT: Duration = Duration::from_secs(60);
+pub(crate) const DEFAULT_DELETE_CONCURRENCY: usize = 5;
+#[cfg(feature = "attachments")]
+pub(crate) const DEFAULT_CONVERSION_TIMEOUT: Duration = Duration::from_secs(30);
+#[cfg(feature = "attachments")]
+pub(crate) const DEFAULT_UPLOAD_CONCURRENCY: usize = 4;
+#[cfg(feature = "attachments")]
+pub(crate) const DEFAULT_CONVERSION_ATTEMPTS: u32 = 3;
+#[cfg(feature = "attachments")]
+pub(crate) const DEFAULT_ATTACHMENT_CACHE_CAPACITY: usize = 256;
+/// Pause before requesting an incomplete conversion again, doubled after each attempt.
+#[cfg(feature = "attachments")]
+const CONVERSION_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+/// Number of conversations requested per page by `conversations_stream` and `list_conversations`.
+const CONVERSATION_PAGE_SIZE: usize = 50;
+/// The parent of the first message of a conversation.
+const ROOT_MESSAGE_UUID: &str = "00000000-0000-4000-8000-000000000000";
+/// Tokens counted for each image when estimating the length of a prompt, the most a single image costs.
+const IMAGE_TOKEN_ESTIMATE: usize = 1600;
+/// Size of the chunks in-memory attachments are sent in, so that their upload progress can be reported.
+#[cfg(feature = "attachments")]
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+/// Number of bytes read from a file without a known extension to guess its MIME type.
+#[cfg(feature = "attachments")]
+const MIME_SNIFF_LENGTH: u64 = 4096;
+
+/// The contents of a document to convert.
+#[cfg(feature = "attachments")]
+enum Upload<'a> {
+    File(&'a Path),
+    Bytes(Bytes),
+}
+
+/// Builds a request again for each attempt, for streamed bodies such as uploads, which cannot be cloned.
+type RequestFactory<'a> = dyn (Fn() -> BoxFuture<'a, Result<reqwest::RequestBuilder>>) + Send + Sync + 'a;
+
 static UA: &str =
     "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/117.0.0.0 Safari/537.36";
 
-lazy_static::lazy_static! {
-    static ref HEADERS: HeaderMap = {
+static DEFAULT_HEADERS: OnceLock<HeaderMap> = OnceLock::new();
+
+/// Returns the headers sent with every request, built on first use.
+fn default_headers() -> &'static HeaderMap {
+    DEFAULT_HEADERS.get_or_init(|| {
         let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
         headers.insert(ORIGIN, HeaderValue::from_static("https://claude.ai"));
@@ -60,22 +623,326 @@ lazy_static::lazy_static! {
         headers.insert(CONNECTION, HeaderValue::from_static("keep-alive"));
         headers.insert(USER_AGENT, HeaderValue::from_static(UA));
         headers
-    };
+    })
+}
+
+/// Number of TLS sessions remembered for resumption, shared by every client of the process.
+const TLS_SESSION_CACHE_SIZE: usize = 256;
+
+static TLS_CONFIG: OnceLock<rustls::ClientConfig> = OnceLock::new();
+
+/// Returns the TLS configuration shared by every HTTP client.
+///
+/// All clients share one session cache, so a reconnection to claude.ai, including one made by a freshly built
+/// proxy client, resumes the previous session with an abbreviated handshake instead of a full one.
+fn tls_config() -> &'static rustls::ClientConfig {
+    TLS_CONFIG.get_or_init(|| {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add_trust_anchors(
+            webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+                rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+                    anchor.subject,
+                    anchor.spki,
+                    anchor.name_constraints
+                )
+            })
+        );
+        let mut config = rustls::ClientConfig
+            ::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        config.resumption = rustls::client::Resumption
+            ::in_memory_sessions(TLS_SESSION_CACHE_SIZE)
+            .tls12_resumption(rustls::client::Tls12Resumption::SessionIdOrTickets);
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        config
+    })
+}
+
+/// Returns the default headers of a client authenticated with `cookie`.
+fn client_headers(cookie: &str) -> Result<HeaderMap> {
+    let mut headers = default_headers().clone();
+    let mut cookie = HeaderValue::from_str(cookie)?;
+    cookie.set_sensitive(true);
+    headers.insert(COOKIE, cookie);
+    Ok(headers)
 }
 
 fn build_request(cookie: &str) -> Result<reqwest::Client> {
-    let mut headers = HEADERS.clone();
-    headers.insert(COOKIE, HeaderValue::from_str(cookie)?);
+    build_client(client_headers(cookie)?, &HttpOptions { https_only: true, ..HttpOptions::default() })
+}
+
+/// Builds a client for each proxy of `policy`, rotated through while Cloudflare blocks requests.
+fn build_proxy_clients(
+    headers: &HeaderMap,
+    options: &HttpOptions,
+    policy: &CloudflarePolicy
+) -> Result<Vec<reqwest::Client>> {
+    policy.proxies
+        .iter()
+        .map(|proxy| {
+            let options = HttpOptions { proxy: Some(proxy.clone()), ..options.clone() };
+            build_client(headers.clone(), &options)
+        })
+        .collect()
+}
+
+/// Adds `mime_types` to `table`, keyed on lowercase extensions without a leading dot.
+#[cfg(feature = "attachments")]
+fn extend_mime_types(table: &mut HashMap<String, String>, mime_types: HashMap<String, String>) {
+    for (extension, mime) in mime_types {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        table.insert(extension, mime);
+    }
+}
 
-    let client = reqwest::Client
+fn build_client(headers: HeaderMap, options: &HttpOptions) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client
         ::builder()
-        .use_rustls_tls()
+        .use_preconfigured_tls(tls_config().clone())
         .default_headers(headers)
-        .https_only(true)
+        .https_only(options.https_only)
         .user_agent(UA)
-        .gzip(true)
-        .build()?;
-    Ok(client)
+        .gzip(true);
+    if let Some(timeout) = options.connect_timeout {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = options.request_timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Sends a request and emits a structured tracing event describing its outcome.
+///
+/// Every call to the API goes through this function so log pipelines can index the `operation`,
+/// `conversation_id`, `status` and `duration_ms` fields without parsing messages. Each call also runs inside
+/// a `claude.request` span carrying the OpenTelemetry HTTP semantic attributes; with the `otel` feature the
+/// span context is propagated to the server through the `traceparent` header.
+async fn execute(
+    operation: &'static str,
+    conversation_id: Option<&str>,
+    request: reqwest::RequestBuilder,
+    auth_hook: Option<&dyn AuthHook>
+) -> Result<Response> {
+    let (client, request) = request.build_split();
+    let mut request = request?;
+    if let Some(hook) = auth_hook {
+        deadline::bound(hook.authenticate(&mut request)).await??;
+    }
+
+    let span =
+        info_span!(
+        "claude.request",
+        otel.name = operation,
+        otel.kind = "client",
+        http.request.method = %request.method(),
+        url.full = %request.url(),
+        http.response.status_code = field::Empty,
+        conversation_id,
+    );
+
+    #[cfg(feature = "otel")]
+    otel::inject_context(&span, request.headers_mut());
+
+    let started = Instant::now();
+    let res = deadline::bound(client.execute(request).instrument(span.clone())).await?;
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let _enter = span.enter();
+    match res {
+        Ok(res) => {
+            span.record("http.response.status_code", res.status().as_u16());
+            debug!(
+                operation,
+                conversation_id,
+                status = res.status().as_u16(),
+                duration_ms,
+                "request completed"
+            );
+            Ok(res)
+        }
+        Err(e) => {
+            debug!(operation, conversation_id, duration_ms, error = %e, "request failed");
+            Err(e.into())
+        }
+    }
+}
+
+/// Returns the response unchanged if its status is a success, or the typed error parsed from its body.
+async fn error_for_status(res: Response) -> Result<Response> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+    let retry_after = retry_after_header(&res);
+    let body = res.text().await?;
+    Err(Error::from_status(status, retry_after.as_deref(), &body))
+}
+
+/// Returns the `Retry-After` header of a response, if any.
+fn retry_after_header(res: &Response) -> Option<String> {
+    res.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// The state of a streamed answer, between two events.
+struct AnswerStream {
+    conversation_id: String,
+    model: String,
+    /// The response the answer is read from, until it ends or fails.
+    response: Option<Response>,
+    deadlines: MessageDeadlines,
+    decoder: sse::EventDecoder,
+    pending: VecDeque<StreamEvent>,
+    received: bool,
+}
+
+impl AnswerStream {
+    /// Returns the next event of the answer, reading more of the response when none is pending.
+    async fn next(mut self) -> Option<(Result<StreamEvent>, Self)> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some((Ok(event), self));
+            }
+            let response = self.response.as_mut()?;
+            match tokio::time::timeout_at(self.deadlines.limit(self.received), response.chunk()).await {
+                Ok(Ok(Some(chunk))) => {
+                    self.received = true;
+                    self.pending.extend(self.decoder.push(&chunk));
+                }
+                Ok(Ok(None)) => {
+                    self.response = None;
+                    self.pending.extend(std::mem::take(&mut self.decoder).finish());
+                    info!(
+                        target: "claude::audit",
+                        event = "message.received",
+                        conversation_id = self.conversation_id,
+                        model = self.model
+                    );
+                }
+                Ok(Err(e)) => {
+                    self.response = None;
+                    return Some((Err(e.into()), self));
+                }
+                Err(_) => {
+                    self.response = None;
+                    let error = self.deadlines.timed_out(self.received);
+                    return Some((Err(error), self));
+                }
+            }
+        }
+    }
+}
+
+/// The state of a paginated listing of conversations, between two pages.
+struct ConversationPages {
+    client: Client,
+    offset: usize,
+    page_size: usize,
+    exhausted: bool,
+}
+
+impl ConversationPages {
+    /// Returns the next page of conversations.
+    async fn next(mut self) -> Option<(Result<Vec<Conversation>>, Self)> {
+        if self.exhausted {
+            return None;
+        }
+        match self.client.list_conversations_page(self.offset, self.page_size).await {
+            Ok(page) => {
+                // A short page is the last one. So is a long one, from a server ignoring the page size.
+                self.exhausted = page.len() != self.page_size;
+                self.offset += page.len();
+                if page.is_empty() { None } else { Some((Ok(page), self)) }
+            }
+            Err(e) => {
+                self.exhausted = true;
+                Some((Err(e), self))
+            }
+        }
+    }
+}
+
+/// The deadlines bounding the answer to one message: the first-byte timeout, the generation timeout and the
+/// caller's deadline, if any.
+#[derive(Debug, Clone, Copy)]
+struct MessageDeadlines {
+    first_byte_timeout: Duration,
+    generation_timeout: Duration,
+    generation_deadline: Instant,
+    caller_deadline: Option<Instant>,
+    first_byte_deadline: tokio::time::Instant,
+    deadline: tokio::time::Instant,
+}
+
+impl MessageDeadlines {
+    /// Starts the clock for a message sent now.
+    fn new(first_byte_timeout: Duration, generation_timeout: Duration) -> Self {
+        let started = Instant::now();
+        let generation_deadline = started + generation_timeout;
+        let caller_deadline = deadline::current();
+        Self {
+            first_byte_timeout,
+            generation_timeout,
+            generation_deadline,
+            caller_deadline,
+            first_byte_deadline: tokio::time::Instant::from_std(started + first_byte_timeout),
+            deadline: tokio::time::Instant::from_std(
+                caller_deadline.map_or(generation_deadline, |d| d.min(generation_deadline))
+            ),
+        }
+    }
+
+    /// Returns the instant by which the next part of the answer must arrive.
+    fn limit(&self, received: bool) -> tokio::time::Instant {
+        if received { self.deadline } else { self.first_byte_deadline.min(self.deadline) }
+    }
+
+    /// Returns the error describing which deadline passed.
+    fn timed_out(&self, received: bool) -> Error {
+        if !received && self.first_byte_deadline < self.deadline {
+            Error::FirstByteTimeout(self.first_byte_timeout)
+        } else if self.caller_deadline.is_some_and(|d| d < self.generation_deadline) {
+            Error::DeadlineExceeded
+        } else {
+            Error::GenerationTimeout(self.generation_timeout)
+        }
+    }
+}
+
+/// Error types and codes with which claude.ai refuses a model the account cannot use.
+const MODEL_ERROR_CODES: &[&str] = &["model_not_found", "model_not_available", "model_not_allowed", "invalid_model"];
+
+/// Returns whether a failed completion request means the model itself cannot be used right now,
+/// either because the account has no access to it or because its quota is exhausted.
+///
+/// A 429 only concerns the model when its body flags a per-model limit (`"perModelLimit": true`, possibly inside
+/// the JSON-encoded message); other rate limits apply to the whole account, so falling back cannot help. A 400,
+/// 403 or 404 concerns the model when its error type or `details.error_code` is one of `MODEL_ERROR_CODES`.
+fn is_model_unavailable(status: StatusCode, body: &str) -> bool {
+    let Ok(body) = serde_json::from_str::<Value>(body) else {
+        return false;
+    };
+    let error = &body["error"];
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => {
+            let message = error["message"].as_str().and_then(|message| serde_json::from_str::<Value>(message).ok());
+            [error, message.as_ref().unwrap_or(&Value::Null)]
+                .iter()
+                .any(|value| value["perModelLimit"].as_bool() == Some(true))
+        }
+        StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN | StatusCode::NOT_FOUND =>
+            [&error["type"], &error["details"]["error_code"]]
+                .iter()
+                .any(|code| code.as_str().is_some_and(|code| MODEL_ERROR_CODES.contains(&code))),
+        _ => false,
+    }
 }
 
 impl Client {
@@ -91,16 +958,17 @@ impl Client {
     ///
     /// # Returns
     ///
-    /// * `Self` - An instance of the struct, with the `cookies` field set to the input `cookies` string,
-    /// and the `org_uuid` field set to the retrieved organization ID.
+    /// * `Self` - An instance of the struct, authenticated with the input `cookies` string, and with the `org_uuid`
+    ///   field set to the retrieved organization ID.
     ///
     /// # Errors
     ///
-    /// This function will exit the process if the organization ID cannot be retrieved.
+    /// This function will exit the process if the organization ID cannot be retrieved. Use `Client::builder` to
+    /// handle that error instead, or to configure timeouts, a proxy or the base URL.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use claude::Client;
     /// use std::env::var;
     /// #[tokio::main]
@@ -113,24 +981,267 @@ impl Client {
     ///         var("SESSION_KEY").unwrap()
     ///     );
     ///     let client = Client::new(cookies).await;
-    ///     tracing::info!("Client created, {:?}", client);
+    ///     tracing::info!("Client created for organization {}", client.org_uuid);
     /// }
     /// ```
     pub async fn new(cookies: String) -> Self {
-        let org_uuid = match Self::get_organization_id(cookies.clone()).await {
-            Ok(id) => id,
+        match Self::builder(cookies).build().await {
+            Ok(client) => client,
             Err(e) => {
-                error!("failed to get organization id: {}, cookies are expired or invalid", e);
+                error!(operation = "new", error = %e, "cookies are expired or invalid");
                 std::process::exit(1);
             }
+        }
+    }
+
+    /// Returns the session cookies the client is authenticated with.
+    pub fn cookies(&self) -> &str {
+        &self.cookies
+    }
+
+    /// Fails with `Error::MissingFields` when `strict_responses` is set and the fetched conversation or message
+    /// `uuid` lacks `missing` fields. Otherwise, only logs them.
+    fn check_fields(&self, operation: &'static str, uuid: &str, missing: Vec<&'static str>) -> Result<()> {
+        if missing.is_empty() {
+            return Ok(());
+        }
+        if self.strict_responses {
+            return Err(Error::MissingFields { uuid: uuid.to_string(), fields: missing });
+        }
+        debug!(operation, uuid, ?missing, "response lacks fields");
+        Ok(())
+    }
+
+    /// Logs a response body at debug level, honoring `body_logging`.
+    fn log_body(&self, operation: &'static str, conversation_id: Option<&str>, body: &dyn std::fmt::Debug) {
+        if let Some(body) = self.body_logging.render(body) {
+            debug!(operation, conversation_id, body, "response body");
+        }
+    }
+
+    /// Extends or overrides the table mapping file extensions to the MIME types sent with attachments.
+    ///
+    /// See `ClientBuilder::mime_types`.
+    #[cfg(feature = "attachments")]
+    #[deprecated(note = "use `ClientBuilder::mime_types` instead")]
+    pub fn with_mime_types(mut self, mime_types: HashMap<String, String>) -> Self {
+        extend_mime_types(Arc::make_mut(&mut self.mime_types), mime_types);
+        self
+    }
+
+    /// Sets a callback receiving the progress of every upload.
+    ///
+    /// See `ClientBuilder::upload_progress`.
+    #[cfg(feature = "attachments")]
+    #[deprecated(note = "use `ClientBuilder::upload_progress` instead")]
+    pub fn with_upload_progress(mut self, callback: impl Fn(&UploadProgress) + Send + Sync + 'static) -> Self {
+        self.upload_progress = Some(ProgressCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Enables cooling down when Cloudflare blocks requests, instead of returning the block page immediately.
+    ///
+    /// See `ClientBuilder::cloudflare_policy`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a proxy URL is invalid.
+    #[deprecated(note = "use `ClientBuilder::cloudflare_policy` instead")]
+    pub fn with_cloudflare_policy(mut self, policy: CloudflarePolicy) -> Result<Self> {
+        self.proxy_clients = build_proxy_clients(&self.headers, &self.http_options, &policy)?;
+        self.cloudflare = Some(policy);
+        Ok(self)
+    }
+
+    /// Enables caching answers for `ttl`.
+    ///
+    /// See `ClientBuilder::answer_cache`.
+    #[deprecated(note = "use `ClientBuilder::answer_cache` instead")]
+    pub fn with_answer_cache(mut self, ttl: Duration) -> Self {
+        self.answer_cache = Some(Arc::new(AnswerCache::new(ttl)));
+        self
+    }
+
+    /// Sends messages with the IANA time zone `timezone`, such as `Europe/Paris`, instead of the local one.
+    ///
+    /// See `ClientBuilder::timezone`. The `timezone` field of a clone can be set to override the time zone of a
+    /// single call.
+    #[deprecated(note = "use `ClientBuilder::timezone` instead")]
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = timezone.into();
+        self
+    }
+
+    /// Clears the answer cache enabled with `ClientBuilder::answer_cache`.
+    pub fn clear_answer_cache(&self) {
+        if let Some(cache) = &self.answer_cache {
+            cache.clear();
+        }
+    }
+
+    /// Sends a request through `dispatch_raw`, turning error statuses into typed errors with
+    /// `error_for_status`.
+    async fn dispatch(
+        &self,
+        operation: &'static str,
+        conversation_id: Option<&str>,
+        request: reqwest::RequestBuilder
+    ) -> Result<Response> {
+        error_for_status(self.dispatch_raw(operation, conversation_id, request).await?).await
+    }
+
+    /// Sends a request through `execute`, cooling down and retrying according to the Cloudflare policy
+    /// when the response is a Cloudflare block.
+    async fn dispatch_raw(
+        &self,
+        operation: &'static str,
+        conversation_id: Option<&str>,
+        request: reqwest::RequestBuilder
+    ) -> Result<Response> {
+        let Some(policy) = &self.cloudflare else {
+            return self.execute_with_retries(operation, conversation_id, request, None).await;
+        };
+
+        let mut request = request;
+        let mut attempt = 0;
+        loop {
+            let next = request.try_clone();
+            let res = self.execute_with_retries(operation, conversation_id, request, None).await?;
+            // Streaming bodies such as uploads cannot be replayed.
+            let Some(next) = next.filter(|_| attempt < policy.max_attempts && cloudflare::is_blocked(&res)) else {
+                return Ok(res);
+            };
+
+            attempt += 1;
+            let cool_down = policy.cool_down(attempt);
+            warn!(
+                operation,
+                conversation_id,
+                attempt,
+                cool_down_ms = cool_down.as_millis() as u64,
+                "blocked by Cloudflare, cooling down"
+            );
+            deadline::bound(tokio::time::sleep(cool_down)).await?;
+
+            request = next;
+            if !self.proxy_clients.is_empty() {
+                let client = &self.proxy_clients[(attempt as usize - 1) % self.proxy_clients.len()];
+                let (_, built) = request.build_split();
+                request = reqwest::RequestBuilder::from_parts(client.clone(), built?);
+            }
+            if let Some(user_agent) = policy.user_agent(attempt) {
+                request = request.header(USER_AGENT, user_agent);
+            }
+        }
+    }
+
+    /// Sends an upload through `execute_with_retries`, turning error statuses into typed errors with
+    /// `error_for_status`.
+    ///
+    /// Streamed bodies cannot be cloned, so `rebuild` builds the request again, reopening its file, for every
+    /// retry. Cloudflare blocks are returned as is, since `dispatch_raw` cannot replay them either.
+    #[cfg(feature = "attachments")]
+    async fn dispatch_upload(
+        &self,
+        operation: &'static str,
+        rebuild: &RequestFactory<'_>
+    ) -> Result<Response> {
+        let request = rebuild().await?;
+        error_for_status(self.execute_with_retries(operation, None, request, Some(rebuild)).await?).await
+    }
+
+    /// Sends a request through `execute`, retrying transient failures according to the retry policy.
+    ///
+    /// Every attempt waits for the rate limiter first. A request whose body cannot be cloned is only retried
+    /// when `rebuild` can build it again. Cloudflare blocks are left to `dispatch_raw`, which handles them with
+    /// its own policy.
+    async fn execute_with_retries(
+        &self,
+        operation: &'static str,
+        conversation_id: Option<&str>,
+        request: reqwest::RequestBuilder,
+        rebuild: Option<&RequestFactory<'_>>
+    ) -> Result<Response> {
+        let Some(strategy) = &self.retry else {
+            self.throttle().await?;
+            return execute(operation, conversation_id, request, self.auth_hook.as_deref()).await;
         };
-        Self { cookies, org_uuid }
+
+        let mut request = request;
+        let mut attempt = 0;
+        loop {
+            let next = request.try_clone();
+            self.throttle().await?;
+            let result = execute(operation, conversation_id, request, self.auth_hook.as_deref()).await;
+            let delay = match &result {
+                _ if next.is_none() && rebuild.is_none() => None,
+                Ok(res) if res.status().is_success() || cloudflare::is_blocked(res) => None,
+                Ok(res) => {
+                    // The body is left for the caller, so the strategy only sees the status and headers.
+                    let error = Error::from_status(res.status(), retry_after_header(res).as_deref(), "");
+                    strategy.should_retry(attempt + 1, &error)
+                }
+                Err(e) => strategy.should_retry(attempt + 1, e),
+            };
+            let Some(delay) = delay else {
+                return result;
+            };
+
+            attempt += 1;
+            warn!(
+                operation,
+                conversation_id,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                status = result.as_ref().ok().map(|res| res.status().as_u16()),
+                "transient failure, retrying"
+            );
+            deadline::bound(tokio::time::sleep(delay)).await?;
+            request = match (next, rebuild) {
+                (Some(next), _) => next,
+                (None, Some(rebuild)) => rebuild().await?,
+                (None, None) => unreachable!("requests that cannot be sent again are not retried"),
+            };
+        }
+    }
+
+    /// Waits until the rate limiter set with `ClientBuilder::requests_per_minute` allows another request.
+    async fn throttle(&self) -> Result<()> {
+        match &self.rate_limiter {
+            Some(limiter) => limiter.acquire().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the MIME type sent for a file: the override set with `ClientBuilder::mime_types` for its extension,
+    /// else the type known for its extension, else the type guessed from its first bytes.
+    #[cfg(feature = "attachments")]
+    async fn mime_type(&self, file_path: &Path) -> Result<String> {
+        if let Some(mime) = self.known_mime_type(file_path) {
+            return Ok(mime);
+        }
+        let mut head = Vec::with_capacity(MIME_SNIFF_LENGTH as usize);
+        File::open(file_path).await?.take(MIME_SNIFF_LENGTH).read_to_end(&mut head).await?;
+        Ok(utils::sniff_content_type(&head).unwrap_or("application/octet-stream").to_string())
+    }
+
+    /// Returns the MIME type of a file from its extension, honoring the overrides set with `ClientBuilder::mime_types`.
+    #[cfg(feature = "attachments")]
+    fn known_mime_type(&self, file_path: &Path) -> Option<String> {
+        let extension = file_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.and_then(|extension| self.mime_types.get(&extension)) {
+            Some(mime) => Some(mime.clone()),
+            None => utils::get_content_type(file_path).map(str::to_string),
+        }
     }
 
     /// Retrieves the organization ID from the API.
     ///
-    /// This function sends a GET request to the API and deserializes the response into a vector of `Response` structs.
-    /// The `uuid` field of the first `Response` struct in the vector is then returned.
+    /// This function sends a GET request to the API and deserializes the response into a vector of `Organization` structs.
+    /// The `uuid` field of the first `Organization` struct in the vector is then returned.
     ///
     /// # Arguments
     ///
@@ -142,26 +1253,76 @@ impl Client {
     ///
     /// # Errors
     ///
-    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    /// This function will return an error if the request fails or if the response cannot be deserialized, and
+    /// `Error::NoOrganization` if the account does not belong to any organization.
     pub async fn get_organization_id(cookies: String) -> Result<String> {
-        let url = "https://claude.ai/api/organizations";
+        Self::fetch_organization_id(&build_request(&cookies)?, DEFAULT_BASE_URL, None).await
+    }
 
-        #[derive(Deserialize, Debug)]
-        struct Response {
-            uuid: String,
-        }
+    async fn fetch_organization_id(
+        http: &reqwest::Client,
+        base_url: &str,
+        auth_hook: Option<&dyn AuthHook>
+    ) -> Result<String> {
+        let url = format!("{}/api/organizations", base_url);
 
-        let res: Vec<Response> = build_request(&cookies)?.get(url).send().await?.json().await?;
+        let request = http.get(url);
+        let context = || "while fetching organizations";
+        let res = execute("get_organization_id", None, request, auth_hook).await.with_context(context)?;
+        let res: Vec<Organization> = error_for_status(res)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
 
-        debug!("response: {:#?}", res);
+        res.into_iter()
+            .next()
+            .map(|organization| organization.uuid)
+            .ok_or(Error::NoOrganization)
+    }
 
-        Ok(res[0].uuid.clone())
+    /// Establishes the connection to claude.ai ahead of time.
+    ///
+    /// The client keeps a pool of connections, so performing the TLS/HTTP2 handshake and getting past
+    /// Cloudflare once up front means the first user-facing `send_message` does not pay that latency.
+    /// With `authenticated` set, a cheap authenticated GET is issued instead of a bare HEAD request, which also
+    /// confirms that the session is still valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `authenticated` - Whether to perform an authenticated GET rather than an unauthenticated HEAD request.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the connection cannot be established, or if the authenticated
+    /// request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(cookies: String) {
+    /// let client = Client::new(cookies).await;
+    /// client.warm_up(false).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn warm_up(&self, authenticated: bool) -> Result<()> {
+        if authenticated {
+            Self::fetch_organization_id(&self.http, &self.base_url, self.auth_hook.as_deref()).await?;
+        } else {
+            let request = self.http.head(format!("{}/", self.base_url));
+            execute("warm_up", None, request, self.auth_hook.as_deref())
+                .await
+                .context("while warming up the connection")?;
+        }
+        Ok(())
     }
 
     /// Creates a new chat conversation.
     ///
     /// This function sends a POST request to the API to create a new chat conversation.
-    /// The payload for the request includes a randomly generated UUID and an empty name.
+    /// The payload for the request includes a randomly generated UUID and a name rendered from `title_template`,
+    /// or an empty name when no template is set.
     ///
     /// # Returns
     ///
@@ -173,7 +1334,7 @@ impl Client {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use claude::Client;
     /// use std::env::var;
     /// #[tokio::main]
@@ -191,24 +1352,126 @@ impl Client {
     /// }
     /// ```
     pub async fn create_new_chat(&self) -> Result<Conversation> {
+        self.create_new_chat_with(NewChatOptions::default()).await
+    }
+
+    /// Creates a new chat conversation with a name, custom instructions or a model of its own.
+    ///
+    /// The custom instructions of `options.system_prompt` are sent as the first message of the conversation,
+    /// answered by the model of `options.model` if set, before the conversation is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - How to set up the conversation; the default options behave like `create_new_chat`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Conversation>` - The created chat conversation, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized. If the
+    /// custom instructions cannot be sent, the conversation has been created but the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ Client, NewChatOptions };
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let chat = client.create_new_chat_with(NewChatOptions {
+    ///     name: Some("Pirate talk".to_string()),
+    ///     system_prompt: Some("Answer every question like a pirate would.".to_string()),
+    ///     ..NewChatOptions::default()
+    /// }).await?;
+    /// let answer = client.send_message(&chat.uuid, "What is Rust?", None, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_new_chat_with(&self, options: NewChatOptions) -> Result<Conversation> {
         let url = format!(
-            "https://claude.ai/api/organizations/{}/chat_conversations",
+            "{}/api/organizations/{}/chat_conversations",
+            self.base_url,
             self.org_uuid
         );
 
-        let payload =
+        let n = self.chats_created.fetch_add(1, Ordering::Relaxed) + 1;
+        let name = match (options.name, &self.title_template) {
+            (Some(name), _) => name,
+            (None, Some(template)) => utils::render_title(template, n, &chrono::Local::now()),
+            (None, None) => String::new(),
+        };
+
+        let mut payload =
             serde_json::json!({
             "uuid": uuid::Uuid::new_v4(),
-            "name": "".to_string(),
+            "name": name,
         });
+        if let Some(model) = &options.model {
+            payload["model"] = model.id().into();
+        }
+        let project_uuid = options.project_uuid.as_deref();
+        if let Some(project_uuid) = project_uuid {
+            payload["project_uuid"] = project_uuid.into();
+        }
+
+        let request = self.http.post(url).json(&payload);
+        let context = || "while creating a conversation";
+        let res: Conversation = self.dispatch("create_new_chat", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("create_new_chat", Some(&res.uuid), &res);
+        info!(target: "claude::audit", event = "conversation.created", conversation_id = %res.uuid, project_uuid);
+
+        if let Some(system_prompt) = &options.system_prompt {
+            let context = || format!("while sending the custom instructions of conversation {}", res.uuid);
+            match options.model {
+                Some(model) => {
+                    self.send_message_with_model(&res.uuid, system_prompt, None, None, model).await.with_context(context)?;
+                }
+                None => {
+                    self.send_message(&res.uuid, system_prompt, None, None).await.with_context(context)?;
+                }
+            }
+        }
+
+        Ok(res)
+    }
 
-        let res: Conversation = build_request(&self.cookies)?
-            .post(url)
-            .json(&payload)
-            .send().await?
-            .json().await?;
+    /// Lists the models available to the account.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ModelInfo>>` - The models messages can be sent to, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// for model in client.list_models().await? {
+    ///     println!("{}", model.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let url = format!("{}/api/organizations/{}/models", self.base_url, self.org_uuid);
+        let request = self.http.get(url);
+        let context = || "while listing models";
+        let res: Vec<ModelInfo> = self.dispatch("list_models", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
 
-        debug!("response: {:#?}", res);
+        self.log_body("list_models", None, &res);
 
         Ok(res)
     }
@@ -229,7 +1492,7 @@ impl Client {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use claude::Client;
     /// use std::env::var;
     /// #[tokio::main]
@@ -248,19 +1511,343 @@ impl Client {
     /// ```
     pub async fn list_all_conversations(&self) -> Result<Vec<Conversation>> {
         let url = format!(
-            "https://claude.ai/api/organizations/{}/chat_conversations",
+            "{}/api/organizations/{}/chat_conversations",
+            self.base_url,
+            self.org_uuid
+        );
+        let request = self.http.get(url);
+        let context = || "while listing conversations";
+        let res: Vec<Conversation> = self.dispatch("list_all_conversations", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("list_all_conversations", None, &res);
+        for conversation in &res {
+            self.check_fields("list_all_conversations", &conversation.uuid, conversation.missing_fields())?;
+        }
+
+        Ok(res)
+    }
+
+    /// Lists a page of the chat conversations, sorted by the time they were last updated.
+    ///
+    /// Conversations most recently updated first are fetched a page at a time from the API. Listing them
+    /// oldest first fetches them all, since claude.ai only returns them most recently updated first.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The page to list, and its order.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Conversation>>` - The conversations of the page, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a request fails or if a response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ Client, ListParams };
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let recent = client.list_conversations(ListParams { limit: Some(10), ..ListParams::default() }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_conversations(&self, params: ListParams) -> Result<Vec<Conversation>> {
+        match (params.order, params.limit) {
+            (SortOrder::NewestFirst, Some(limit)) => self.list_conversations_page(params.offset, limit).await,
+            (SortOrder::NewestFirst, None) => {
+                let mut conversations = vec![];
+                let pages = self.conversations_paged_from(params.offset, CONVERSATION_PAGE_SIZE);
+                let mut pages = std::pin::pin!(pages);
+                while let Some(page) = pages.next().await {
+                    conversations.extend(page?);
+                }
+                Ok(conversations)
+            }
+            (SortOrder::OldestFirst, limit) => {
+                let mut conversations = self.list_all_conversations().await?;
+                // Reversing before the stable sort puts conversations updated at the same time in the reverse
+                // order of the API, so the page is the exact reverse of the newest first listing.
+                conversations.reverse();
+                conversations.sort_by_key(|conversation| conversation.updated_at);
+                Ok(conversations.into_iter().skip(params.offset).take(limit.unwrap_or(usize::MAX)).collect())
+            }
+        }
+    }
+
+    /// Lists the chat conversations lazily, a page of `page_size` conversations at a time, most recently
+    /// updated first.
+    ///
+    /// Each page is fetched when the stream is polled, so stopping early never fetches the remaining ones. The
+    /// stream ends after the first error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use futures_util::StreamExt;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let mut pages = std::pin::pin!(client.conversations_paged(20));
+    /// while let Some(page) = pages.next().await {
+    ///     for conversation in page? {
+    ///         println!("{}", conversation.name);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn conversations_paged(&self, page_size: usize) -> impl Stream<Item = Result<Vec<Conversation>>> {
+        self.conversations_paged_from(0, page_size)
+    }
+
+    /// Lists the chat conversations lazily, a page at a time, starting at `offset`.
+    fn conversations_paged_from(
+        &self,
+        offset: usize,
+        page_size: usize
+    ) -> impl Stream<Item = Result<Vec<Conversation>>> {
+        let state = ConversationPages { client: self.clone(), offset, page_size: page_size.max(1), exhausted: false };
+        stream::unfold(state, ConversationPages::next)
+    }
+
+    /// Lists the chat conversations lazily, fetching them a page at a time as the stream is polled.
+    ///
+    /// Unlike `list_all_conversations`, stopping early never fetches the remaining pages, which matters for
+    /// accounts with thousands of conversations. The stream ends after the first error.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Stream<Item = Result<Conversation>>` - The conversations, most recently updated first.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use futures_util::StreamExt;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let mut conversations = std::pin::pin!(client.conversations_stream());
+    /// while let Some(conversation) = conversations.next().await {
+    ///     let conversation = conversation?;
+    ///     if conversation.name == "Release notes" {
+    ///         println!("{}", conversation.uuid);
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn conversations_stream(&self) -> impl Stream<Item = Result<Conversation>> {
+        self.conversations_paged(CONVERSATION_PAGE_SIZE)
+            .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Retrieves one page of the chat conversations, skipping the first `offset`.
+    async fn list_conversations_page(&self, offset: usize, limit: usize) -> Result<Vec<Conversation>> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations",
+            self.base_url,
             self.org_uuid
         );
-        let res: Vec<Conversation> = build_request(&self.cookies)?
-            .get(url)
-            .send().await?
-            .json().await?;
+        let request = self.http.get(url).query(&[("limit", limit), ("offset", offset)]);
+        let context = || format!("while listing conversations from {}", offset);
+        let res: Vec<Conversation> = self.dispatch("list_conversations_page", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("list_conversations_page", None, &res);
+        for conversation in &res {
+            self.check_fields("list_conversations_page", &conversation.uuid, conversation.missing_fields())?;
+        }
+
+        Ok(res)
+    }
+
+    /// Retrieves a single chat conversation.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - A string representing the UUID of the chat conversation.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Conversation>` - The conversation, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    pub async fn get_conversation(&self, chat_uuid: &str) -> Result<Conversation> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+
+        let request = self.http.get(url);
+        let context = || format!("while fetching conversation {}", chat_uuid);
+        let mut res: Conversation = self.dispatch("get_conversation", Some(chat_uuid), request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+        // The messages are returned by `chat_conversation_history`, not kept with the conversation.
+        res.extra.remove("chat_messages");
 
-        debug!("response: {:#?}", res);
+        self.log_body("get_conversation", Some(chat_uuid), &res);
+        self.check_fields("get_conversation", chat_uuid, res.missing_fields())?;
 
         Ok(res)
     }
 
+    /// Refreshes the name and summary of a conversation from the API, updating it in place.
+    ///
+    /// The summary held by a `Conversation` is a snapshot taken when it was listed, and claude.ai only
+    /// regenerates it from time to time. Use `generate_summary` when the server-side summary stays empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `conversation` - The conversation to refresh.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversation cannot be retrieved.
+    pub async fn refresh_summary(&self, conversation: &mut Conversation) -> Result<()> {
+        let fresh = self.get_conversation(&conversation.uuid).await?;
+        conversation.name = fresh.name;
+        conversation.summary = fresh.summary;
+        Ok(())
+    }
+
+    /// Generates a summary of a conversation with a helper prompt and stores it in `conversation.summary`.
+    ///
+    /// The history of the conversation is sent to a temporary conversation, which is deleted afterwards, so
+    /// the summarized conversation itself is left untouched. The generated summary only lives in the returned
+    /// struct; it is not saved on claude.ai. This costs one message of the account's quota.
+    ///
+    /// # Arguments
+    ///
+    /// * `conversation` - The conversation to summarize.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the history cannot be retrieved or if the helper prompt fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use std::env::var;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     dotenv::dotenv().ok();
+    ///     let cookies = format!(
+    ///         "activitySessionId={}; sessionKey={}",
+    ///         var("SESSION_ID").unwrap(),
+    ///         var("SESSION_KEY").unwrap()
+    ///     );
+    ///     let client = Client::new(cookies).await;
+    ///     for mut chat in client.list_all_conversations().await.unwrap() {
+    ///         if chat.summary.as_deref().unwrap_or_default().is_empty() {
+    ///             client.generate_summary(&mut chat).await.unwrap();
+    ///         }
+    ///         println!("{}: {}", chat.name, chat.summary.unwrap_or_default());
+    ///     }
+    /// }
+    /// ```
+    pub async fn generate_summary(&self, conversation: &mut Conversation) -> Result<()> {
+        const MAX_TRANSCRIPT_CHARS: usize = 20_000;
+
+        let messages = self.chat_conversation_history(&conversation.uuid).await?;
+        let mut transcript = String::new();
+        for message in &messages {
+            transcript.push_str(&format!("{}: {}\n\n", message.sender, message.text));
+        }
+        if let Some((end, _)) = transcript.char_indices().nth(MAX_TRANSCRIPT_CHARS) {
+            transcript.truncate(end);
+        }
+
+        let prompt = format!(
+            "Summarize the following conversation in one or two sentences. Reply with the summary only.\n\n{}",
+            transcript
+        );
+
+        conversation.summary = Some(self.ask_helper(&prompt).await?);
+        Ok(())
+    }
+
+    /// Lists the starred chat conversations, mirroring the favorites of the web UI.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Conversation>>` - The starred conversations, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    pub async fn list_starred_conversations(&self) -> Result<Vec<Conversation>> {
+        let conversations = self.list_all_conversations().await?;
+        Ok(
+            conversations
+                .into_iter()
+                .filter(|conversation| conversation.starred)
+                .collect()
+        )
+    }
+
+    /// Stars a chat conversation.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - A string representing the UUID of the chat conversation to be starred.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails.
+    pub async fn star_conversation(&self, chat_uuid: &str) -> Result<()> {
+        self.set_starred(chat_uuid, true).await
+    }
+
+    /// Removes the star from a chat conversation.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - A string representing the UUID of the chat conversation to be unstarred.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails.
+    pub async fn unstar_conversation(&self, chat_uuid: &str) -> Result<()> {
+        self.set_starred(chat_uuid, false).await
+    }
+
+    async fn set_starred(&self, chat_uuid: &str, starred: bool) -> Result<()> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}?rendering_mode=raw",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+
+        let payload = serde_json::json!({
+            "is_starred": starred,
+        });
+
+        let request = self.http.put(url).json(&payload);
+        self.dispatch("set_starred", Some(chat_uuid), request)
+            .await
+            .with_context(|| format!("while starring conversation {}", chat_uuid))?;
+
+        Ok(())
+    }
+
     /// Retrieves the history of a chat conversation.
     ///
     /// This function sends a GET request to the API to retrieve the history of a chat conversation.
@@ -272,16 +1859,724 @@ impl Client {
     ///
     /// # Returns
     ///
-    /// * `Result<Vec<ChatMessage>>` - A vector of `ChatMessage` structs, if the request is successful. Otherwise, an error.
+    /// * `Result<Vec<ChatMessage>>` - A vector of `ChatMessage` structs, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use std::env::var;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     dotenv::dotenv().ok();
+    ///     tracing_subscriber::fmt::init();
+    ///     let cookies = format!(
+    ///         "activitySessionId={}; sessionKey={}",
+    ///         var("SESSION_ID").unwrap(),
+    ///         var("SESSION_KEY").unwrap()
+    ///     );
+    ///     let client = Client::new(cookies).await;
+    ///     let chat_hist = client.chat_conversation_history("chat_uuid").await.unwrap();
+    ///     tracing::info!("{:#?}", chat_hist);
+    /// }
+    /// ```
+    pub async fn chat_conversation_history(&self, chat_uuid: &str) -> Result<Vec<ChatMessage>> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+
+        #[derive(Deserialize, Debug)]
+        struct History {
+            chat_messages: Vec<ChatMessage>,
+        }
+
+        let request = self.http.get(url);
+        let context = || format!("while fetching history for conversation {}", chat_uuid);
+        let res: History = self.dispatch("chat_conversation_history", Some(chat_uuid), request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("chat_conversation_history", Some(chat_uuid), &res.chat_messages);
+        for message in &res.chat_messages {
+            self.check_fields("chat_conversation_history", &message.uuid, message.missing_fields())?;
+        }
+
+        Ok(res.chat_messages)
+    }
+
+    /// Computes the checksum of a conversation as stored by claude.ai, to compare with
+    /// `utils::conversation_checksum` over a local copy of its messages.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the history of the conversation cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ utils, Client, ChatMessage };
+    /// # async fn example(client: Client, mirror: Vec<ChatMessage>) -> claude::Result<()> {
+    /// if client.conversation_checksum("chat_uuid").await? != utils::conversation_checksum(&mirror) {
+    ///     println!("the local copy is out of date");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn conversation_checksum(&self, chat_uuid: &str) -> Result<String> {
+        let messages = self.chat_conversation_history(chat_uuid).await?;
+        Ok(utils::conversation_checksum(&messages))
+    }
+
+    /// Checks that a chat conversation exists.
+    ///
+    /// This function sends a GET request to the API for the conversation and inspects only the status code,
+    /// making it a cheap way to fail early before expensive operations such as attachment uploads.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - A string representing the UUID of the chat conversation.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An empty `Result`, if the conversation exists. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::ConversationNotFound` if the API responds with 404, and an error if the request fails.
+    pub async fn validate_conversation(&self, chat_uuid: &str) -> Result<()> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+
+        let request = self.http.get(url);
+        match self.dispatch("validate_conversation", Some(chat_uuid), request).await {
+            Ok(_) => Ok(()),
+            Err(Error::Api(e)) if e.status == StatusCode::NOT_FOUND => {
+                Err(Error::ConversationNotFound(chat_uuid.to_string()))
+            }
+            Err(e) => Err(e).with_context(|| format!("while validating conversation {}", chat_uuid)),
+        }
+    }
+
+    /// Exports a chat conversation through an `Exporter`.
+    ///
+    /// This function retrieves a snapshot of the conversation and feeds the header and each message to the
+    /// exporter as they are written, then finishes the document. The header uses the metadata of the snapshot,
+    /// which is fresher than that of `conversation`.
+    ///
+    /// # Arguments
+    ///
+    /// * `conversation` - The conversation to export.
+    /// * `exporter` - The exporter writing the output format.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An empty `Result`, if the conversation was exported. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the history cannot be retrieved or if the exporter fails to write.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ export::MarkdownExporter, Client };
+    /// use std::env::var;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     dotenv::dotenv().ok();
+    ///     let cookies = format!(
+    ///         "activitySessionId={}; sessionKey={}",
+    ///         var("SESSION_ID").unwrap(),
+    ///         var("SESSION_KEY").unwrap()
+    ///     );
+    ///     let client = Client::new(cookies).await;
+    ///     let chats = client.list_all_conversations().await.unwrap();
+    ///     let mut exporter = MarkdownExporter::new(std::io::stdout());
+    ///     client.export_conversation(&chats[0], &mut exporter).await.unwrap();
+    /// }
+    /// ```
+    #[cfg(feature = "export")]
+    pub async fn export_conversation(
+        &self,
+        conversation: &Conversation,
+        exporter: &mut dyn export::Exporter
+    ) -> Result<()> {
+        self.conversation_snapshot(&conversation.uuid).await?.write(exporter)
+    }
+
+    /// Deletes a chat conversation.
+    ///
+    /// This function sends a DELETE request to the API to delete a chat conversation.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - A string representing the UUID of the chat conversation to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An empty `Result`, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use std::env::var;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     dotenv::dotenv().ok();
+    ///     tracing_subscriber::fmt::init();
+    ///     let cookies = format!(
+    ///         "activitySessionId={}; sessionKey={}",
+    ///         var("SESSION_ID").unwrap(),
+    ///         var("SESSION_KEY").unwrap()
+    ///     );
+    ///     let client = Client::new(cookies).await;
+    ///     let chat_hist = client.delete_conversation("chat_uuid_string").await.unwrap();
+    /// }
+    /// ```
+    pub async fn delete_conversation(&self, chat_uuid: &str) -> Result<()> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+
+        let payload =
+            serde_json::json!({
+            "conversation_id": chat_uuid.to_string(),
+            });
+
+        let request = self.http.delete(url).json(&payload);
+        self.dispatch("delete_conversation", Some(chat_uuid), request)
+            .await
+            .with_context(|| format!("while deleting conversation {}", chat_uuid))?;
+        info!(target: "claude::audit", event = "conversation.deleted", conversation_id = chat_uuid);
+
+        Ok(())
+    }
+
+    /// Resets all chat conversations.
+    ///
+    /// This function retrieves all chat conversations and deletes them, `delete_concurrency` at a time.
+    /// Each deletion is a separate request, so set `ClientBuilder::requests_per_minute` to avoid being rate
+    /// limited when there are many conversations. A failed deletion does not stop the others.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ResetSummary>` - The conversations deleted and those that could not be, if the conversations can
+    ///   be listed. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the retrieval of chat conversations fails. Failed deletions are
+    /// reported in `ResetSummary::failed` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(mut client: Client) -> claude::Result<()> {
+    /// client.delete_concurrency = 10;
+    /// let summary = client.reset_all().await?;
+    /// for (uuid, e) in &summary.failed {
+    ///     eprintln!("could not delete {}: {}", uuid, e);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reset_all(&self) -> Result<ResetSummary> {
+        let conversations = self.list_all_conversations().await.context("while resetting all conversations")?;
+
+        // Collected first, since a stream holding the mapping closure is not `Send`.
+        let pending: Vec<_> = conversations
+            .into_iter()
+            .map(|conversation| async move {
+                let result = self.delete_conversation(&conversation.uuid).await;
+                (conversation.uuid, result)
+            })
+            .collect();
+        let results: Vec<_> = stream::iter(pending).buffer_unordered(self.delete_concurrency.max(1)).collect().await;
+
+        let mut summary = ResetSummary::default();
+        for (uuid, result) in results {
+            match result {
+                Ok(()) => summary.deleted.push(uuid),
+                Err(e) => summary.failed.push((uuid, e)),
+            }
+        }
+        debug!(operation = "reset_all", deleted = summary.deleted.len(), failed = summary.failed.len());
+        Ok(summary)
+    }
+
+    /// Uploads an attachment to the API.
+    ///
+    /// This function sends a POST request to the API to upload a document.
+    /// The document is read from the file at the specified path and included in the request as a multipart form data.
+    /// The MIME type of the document is determined based on its file extension and the overrides set with
+    /// `ClientBuilder::mime_types`, or sniffed from its first bytes when the extension is missing or unknown, defaulting to
+    /// `application/octet-stream` when neither gives an answer.
+    /// Only the normalized file name, never the local directory, is sent to the API (see `utils::normalize_file_name`).
+    /// Converted documents are cached by the SHA-256 hash of their contents, so uploading the same file again
+    /// returns the cached payload without another round trip. The cache keeps the most recently used documents
+    /// up to the capacity set with `ClientBuilder::attachment_cache_capacity`.
+    /// The API sometimes answers before the text of the document is extracted. claude.ai offers no way to fetch the
+    /// result of a conversion later, so the whole document is uploaded again, with a growing pause, until its text
+    /// is extracted, `max_conversion_attempts` uploads were made or `conversion_timeout` passes. An empty document
+    /// is thus never attached silently, but a document without text, such as a scanned PDF, is uploaded
+    /// `max_conversion_attempts` times before failing.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path to the file to be uploaded.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<UploadedAttachment>` - The converted document and the hash of its contents, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened, if the request fails, if the response cannot be deserialized,
+    /// or `Error::ConversionIncomplete` if no text was extracted from a non-empty file within `max_conversion_attempts` uploads and `conversion_timeout`.
+    ///
+    #[cfg(feature = "attachments")]
+    pub async fn upload_attachment(&self, file_path: impl AsRef<Path>) -> Result<UploadedAttachment> {
+        let file_path = file_path.as_ref();
+        let file_name = utils::normalize_file_name(&file_path.to_string_lossy());
+        self.upload_attachment_as(file_path, &file_name).await
+    }
+
+    /// Uploads an attachment to the API under a custom file name.
+    ///
+    /// This function behaves like `upload_attachment`, but `file_name` is shown to the model and in the web UI
+    /// instead of the name of the local file. The name is normalized with `utils::normalize_file_name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path to the file to be uploaded.
+    /// * `file_name` - The name displayed for the attachment.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<UploadedAttachment>` - The converted document and the hash of its contents, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened, if the request fails, or if the response cannot be deserialized.
+    ///
+    #[cfg(feature = "attachments")]
+    pub async fn upload_attachment_as(
+        &self,
+        file_path: impl AsRef<Path>,
+        file_name: &str
+    ) -> Result<UploadedAttachment> {
+        let file_path = utils::long_path(file_path.as_ref());
+        let file_path = file_path.as_ref();
+        let file_name = utils::normalize_file_name(file_name);
+        let context = || format!("while uploading attachment {}", file_path.display());
+
+        let hash = utils::hash_file(file_path).await.with_context(context)?;
+        if let Some(cached) = self.cached_attachment(&hash, &file_name) {
+            return Ok(cached);
+        }
+        let is_empty = tokio::fs::metadata(file_path).await.with_context(context)?.len() == 0;
+        let mime = self.mime_type(file_path).await.with_context(context)?;
+        self.convert_until_extracted(Upload::File(file_path), file_name, &mime, hash, is_empty)
+            .await
+            .with_context(context)
+    }
+
+    /// Uploads an attachment held in memory, such as a report rendered at runtime, without writing it to a file.
+    ///
+    /// This function behaves like `upload_attachment_as`, and shares its cache of converted documents.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name displayed for the attachment, normalized with `utils::normalize_file_name`.
+    /// * `bytes` - The contents of the attachment.
+    /// * `mime` - The MIME type of the contents. If `None`, it is determined from the extension of `name` like
+    ///   for files, or sniffed from the contents.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<UploadedAttachment>` - The converted document and the hash of its contents, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `mime` is not a valid MIME type, if the request fails, if the response cannot be deserialized,
+    /// or `Error::ConversionIncomplete` if no text was extracted from non-empty contents within `max_conversion_attempts` uploads and `conversion_timeout`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let report = format!("# Daily report\n\n{} jobs succeeded.", 42);
+    /// let attachment = client.upload_attachment_bytes("report.md", report.into(), None).await?;
+    /// println!("{}", attachment.extracted_content);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "attachments")]
+    pub async fn upload_attachment_bytes(
+        &self,
+        name: &str,
+        bytes: Bytes,
+        mime: Option<&str>
+    ) -> Result<UploadedAttachment> {
+        let file_name = utils::normalize_file_name(name);
+        let context = || format!("while uploading attachment {}", file_name);
+
+        let hash = utils::sha256_hex(&bytes);
+        if let Some(cached) = self.cached_attachment(&hash, &file_name) {
+            return Ok(cached);
+        }
+        let mime = match mime {
+            Some(mime) => mime.to_string(),
+            None =>
+                self.known_mime_type(Path::new(&file_name)).unwrap_or_else(|| {
+                    let head = &bytes[..bytes.len().min(MIME_SNIFF_LENGTH as usize)];
+                    utils::sniff_content_type(head).unwrap_or("application/octet-stream").to_string()
+                }),
+        };
+        let is_empty = bytes.is_empty();
+        self.convert_until_extracted(Upload::Bytes(bytes), file_name.clone(), &mime, hash, is_empty)
+            .await
+            .with_context(context)
+    }
+
+    /// Returns the converted document cached for the contents hashed to `hash`, renamed to `file_name`.
+    #[cfg(feature = "attachments")]
+    fn cached_attachment(&self, hash: &str, file_name: &str) -> Option<UploadedAttachment> {
+        let cached = self.attachment_cache.get(hash)?;
+        debug!(operation = "upload_attachment", file_name, sha256 = hash, "reusing cached attachment");
+        Some(UploadedAttachment { file_name: file_name.to_string(), ..cached })
+    }
+
+    /// Requests the conversion of a document until its text is extracted, then caches the result under `hash`.
+    #[cfg(feature = "attachments")]
+    async fn convert_until_extracted(
+        &self,
+        source: Upload<'_>,
+        file_name: String,
+        mime: &str,
+        hash: String,
+        is_empty: bool
+    ) -> Result<UploadedAttachment> {
+        let deadline = Instant::now() + self.conversion_timeout;
+        let mut interval = CONVERSION_RETRY_INTERVAL;
+        let mut attempts = 0;
+        let mut uploaded = loop {
+            let res = self.convert_document(&source, &file_name, mime).await?;
+            attempts += 1;
+            if !res.extracted_content.trim().is_empty() || is_empty {
+                break res;
+            }
+            if attempts >= self.max_conversion_attempts.max(1) || Instant::now() + interval > deadline {
+                return Err(Error::ConversionIncomplete { file_name, attempts, timeout: self.conversion_timeout });
+            }
+            debug!(
+                operation = "upload_attachment",
+                file_name,
+                attempts,
+                interval_ms = interval.as_millis() as u64,
+                "conversion incomplete, uploading the document again"
+            );
+            deadline::bound(tokio::time::sleep(interval)).await?;
+            interval *= 2;
+        };
+
+        uploaded.sha256 = hash.clone();
+        self.attachment_cache.insert(hash, uploaded.clone());
+
+        Ok(uploaded)
+    }
+
+    /// Returns the body of an upload, reporting its progress to the callback set with `ClientBuilder::upload_progress`.
+    #[cfg(feature = "attachments")]
+    fn upload_body<S, B>(&self, chunks: S, file_name: &str, total: u64) -> Body
+        where S: Stream<Item = std::io::Result<B>> + Send + Sync + 'static, B: AsRef<[u8]>, Bytes: From<B>
+    {
+        let Some(ProgressCallback(callback)) = self.upload_progress.clone() else {
+            return Body::wrap_stream(chunks);
+        };
+        let file_name = file_name.to_string();
+        let mut sent = 0;
+        Body::wrap_stream(
+            chunks.inspect_ok(move |chunk| {
+                sent += chunk.as_ref().len() as u64;
+                callback(&UploadProgress { file_name: file_name.clone(), sent, total });
+            })
+        )
+    }
+
+    /// Uploads a document to be converted, returning the converted document.
+    #[cfg(feature = "attachments")]
+    async fn convert_document(
+        &self,
+        source: &Upload<'_>,
+        file_name: &str,
+        mime: &str
+    ) -> Result<UploadedAttachment> {
+        let url = format!("{}/api/convert_document", self.base_url);
+
+        let request = || {
+            let url = url.clone();
+            async move {
+                let body = match source {
+                    Upload::File(file_path) => {
+                        let file = File::open(file_path).await?;
+                        let total = file.metadata().await?.len();
+                        self.upload_body(FramedRead::new(file, BytesCodec::new()), file_name, total)
+                    }
+                    Upload::Bytes(bytes) => {
+                        let chunks: Vec<std::io::Result<Bytes>> = (0..bytes.len())
+                            .step_by(UPLOAD_CHUNK_SIZE)
+                            .map(|start| Ok(bytes.slice(start..bytes.len().min(start + UPLOAD_CHUNK_SIZE))))
+                            .collect();
+                        self.upload_body(stream::iter(chunks), file_name, bytes.len() as u64)
+                    }
+                };
+                let part = Part::stream(body).file_name(file_name.to_string()).mime_str(mime)?;
+                let form = Form::new().part("file", part).text("orgUuid", self.org_uuid.clone());
+                Ok(self.http.post(url).multipart(form))
+            }.boxed()
+        };
+        let res = self.dispatch_upload("upload_attachment", &request).await?.json::<UploadedAttachment>().await?;
+        self.log_body("upload_attachment", None, &res);
+        Ok(res)
+    }
+
+    /// Clears the cache of converted attachments, forcing the next upload of every file to hit the API.
+    #[cfg(feature = "attachments")]
+    pub fn clear_attachment_cache(&self) {
+        self.attachment_cache.clear();
+    }
+
+    /// Uploads an image, such as a screenshot, to be shown to a vision-capable model.
+    ///
+    /// Images go through the file upload of claude.ai rather than the document conversion of `upload_attachment`.
+    /// `send_message` calls this function for attached files whose MIME type is an image type, so it is only
+    /// needed to upload an image ahead of time.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path to the PNG, JPEG, GIF or WebP image to be uploaded.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<UploadedImage>` - The uploaded image, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened, if the request fails, or if the response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let image = client.upload_image("screenshot.png").await?;
+    /// println!("{}", image.file_uuid);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "attachments")]
+    pub async fn upload_image(&self, file_path: impl AsRef<Path>) -> Result<UploadedImage> {
+        let file_path = utils::long_path(file_path.as_ref());
+        let file_path = file_path.as_ref();
+        let file_name = utils::normalize_file_name(&file_path.to_string_lossy());
+        let context = || format!("while uploading image {}", file_path.display());
+
+        let url = format!("{}/api/{}/upload", self.base_url, self.org_uuid);
+        let mime = self.mime_type(file_path).await.with_context(context)?;
+        let request = || {
+            let (url, file_name, mime) = (url.clone(), file_name.clone(), mime.clone());
+            async move {
+                let file = File::open(file_path).await?;
+                let total = file.metadata().await?.len();
+                let body = self.upload_body(FramedRead::new(file, BytesCodec::new()), &file_name, total);
+                let part = Part::stream(body).file_name(file_name).mime_str(&mime)?;
+                Ok(self.http.post(url).multipart(Form::new().part("file", part)))
+            }.boxed()
+        };
+        let res = self
+            .dispatch_upload("upload_image", &request).await
+            .with_context(context)?
+            .json::<UploadedImage>().await
+            .with_context(context)?;
+        self.log_body("upload_image", None, &res);
+        Ok(res)
+    }
+
+    /// Uploads a file attached to a message, with `upload_image` if it is an image and `upload_attachment`
+    /// otherwise, split into parts if it is a text file over `max_attachment_size`.
+    #[cfg(feature = "attachments")]
+    async fn upload_any(&self, file_path: &str) -> Result<Vec<Uploaded>> {
+        let context = || format!("while uploading attachment {}", file_path);
+        let mime = self.mime_type(Path::new(file_path)).await.with_context(context)?;
+        if mime.starts_with("image/") {
+            return Ok(vec![Uploaded::Image(self.upload_image(file_path).await?)]);
+        }
+        if let Some(limit) = self.max_attachment_size.filter(|_| mime.starts_with("text/")) {
+            if utils::get_file_size(file_path).await.with_context(context)? > limit {
+                let text = tokio::fs::read(file_path).await.with_context(context)?;
+                if let Ok(text) = String::from_utf8(text) {
+                    return self.upload_parts(file_path, &text, &mime, limit).await;
+                }
+            }
+        }
+        Ok(vec![Uploaded::Document(self.upload_attachment(file_path).await?)])
+    }
+
+    /// Uploads a text file as several attachments of at most `limit` bytes each, named `name (part i of n).ext`.
+    /// The header row of CSV and TSV files is repeated in every part.
+    #[cfg(feature = "attachments")]
+    async fn upload_parts(&self, file_path: &str, text: &str, mime: &str, limit: u64) -> Result<Vec<Uploaded>> {
+        let file_name = utils::normalize_file_name(file_path);
+        let (stem, extension) = match file_name.rfind('.') {
+            Some(dot) if dot > 0 => file_name.split_at(dot),
+            _ => (file_name.as_str(), ""),
+        };
+        let tabular = mime == "text/csv" || mime == "text/tab-separated-values";
+        let parts = utils::split_text(text, usize::try_from(limit).unwrap_or(usize::MAX), tabular);
+        debug!(operation = "upload_attachment", file_name, parts = parts.len(), "splitting oversized attachment");
+
+        let mut uploaded = vec![];
+        for (i, part) in parts.iter().enumerate() {
+            let name = format!("{} (part {} of {}){}", stem, i + 1, parts.len(), extension);
+            let document = self.upload_attachment_bytes(&name, Bytes::from(part.clone()), Some(mime)).await?;
+            uploaded.push(Uploaded::Document(document));
+        }
+        Ok(uploaded)
+    }
+
+    /// Uploads the files attached to a message, `upload_concurrency` at a time, fitting the converted documents
+    /// to `attachment_budget`. Documents and images keep the order they were attached in.
+    #[cfg(feature = "attachments")]
+    async fn upload_attachments(&self, attachments: Vec<&str>) -> Result<Uploads> {
+        // Collected first, since a stream holding the mapping closure is not `Send`.
+        let pending: Vec<_> = attachments.into_iter().map(|a| self.upload_any(a)).collect();
+        let uploaded: Vec<Vec<Uploaded>> = stream
+            ::iter(pending)
+            .buffered(self.upload_concurrency.max(1))
+            .try_collect().await?;
+        let mut uploads = Uploads::default();
+        for upload in uploaded.into_iter().flatten() {
+            match upload {
+                Uploaded::Document(document) => uploads.documents.push(document),
+                Uploaded::Image(image) => uploads.images.push(image),
+            }
+        }
+        let Some(budget) = self.attachment_budget else {
+            return Ok(uploads);
+        };
+        uploads.truncated = truncation::fit_documents(&mut uploads.documents, budget, self.truncation_policy)?;
+        for report in &uploads.truncated {
+            warn!(
+                operation = "send_message",
+                file_name = report.file_name,
+                original_chars = report.original_chars,
+                kept_chars = report.kept_chars,
+                "attachment truncated to fit the budget"
+            );
+        }
+        Ok(uploads)
+    }
+
+    /// Rejects attached files, since uploading them requires the `attachments` feature.
+    #[cfg(not(feature = "attachments"))]
+    async fn upload_attachments(&self, attachments: Vec<&str>) -> Result<Uploads> {
+        if attachments.is_empty() { Ok(Uploads::default()) } else { Err(Error::AttachmentsDisabled) }
+    }
+
+    /// Sends a message to a chat conversation.
+    ///
+    /// This function sends a POST request to the API to append a message to a chat conversation.
+    /// The message can include attachments, which are uploaded to the API before the message is sent.
+    /// Images among them are uploaded with `upload_image` for vision-capable models (see `Model::supports_vision`);
+    /// models that do not accept images are skipped like unavailable ones.
+    /// The function waits for a response from the API for a specified amount of time before timing out.
+    /// If the default model is unavailable or out of its own quota, the models in `fallback_models` are tried in
+    /// order. A rate limit of the whole account is returned as `Error::RateLimited` without trying them.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - A string representing the UUID of the chat conversation.
+    /// * `prompt` - A string representing the message to be sent.
+    /// * `attachments` - An optional vector of strings representing the paths to the files to be uploaded as attachments.
+    /// * `timeout` - An optional number representing the total amount of time (in seconds) allowed for generating the answer, 500 by default.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The API response, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an attachment cannot be uploaded, if the request fails, or if the response cannot be deserialized.
+    /// `Error::FirstByteTimeout` is returned when the backend does not start answering within `first_byte_timeout`,
+    /// and `Error::GenerationTimeout` when the whole answer takes longer than `timeout`.
+    /// Models whose context window the prompt and attachments are estimated to exceed (see `utils::estimate_tokens`)
+    /// are skipped, and `Error::PromptTooLong` is returned without sending anything when no model of the chain fits.
+    /// If the response stream carries no completion text, `Error::EmptyResponse` is returned rather than an empty answer.
+    ///
+    pub async fn send_message(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>
+    ) -> Result<String> {
+        let completion = self.send_message_detailed(chat_uuid, prompt, attachments, timeout).await?;
+        Ok(completion.text)
+    }
+
+    /// Sends a message to a chat conversation and reports which model answered.
+    ///
+    /// This function behaves like `send_message`, but returns a `Completion` carrying the name of the model
+    /// that actually produced the answer. The default model is tried first, followed by each entry of
+    /// `fallback_models`. A model is skipped only when the API reports it as unavailable or out of quota;
+    /// any other failure is returned immediately.
+    /// When `validate_before_send` is set, the conversation is checked with `validate_conversation` before any
+    /// attachment is uploaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - A string representing the UUID of the chat conversation.
+    /// * `prompt` - A string representing the message to be sent.
+    /// * `attachments` - An optional vector of strings representing the paths to the files to be uploaded as attachments.
+    /// * `timeout` - An optional number representing the total amount of time (in seconds) allowed for generating the answer, 500 by default.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Completion>` - The answer and the model that produced it, if the request is successful. Otherwise, an error.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    /// This function will return an error if an attachment cannot be uploaded, if the request fails, or if every
+    /// model in the chain is unavailable, in which case the root cause is the last `Error::ModelUnavailable`.
     ///
     /// # Examples
     ///
-    /// ```
-    /// use claude::Client;
+    /// ```no_run
+    /// use claude::{ Client, Model };
     /// use std::env::var;
     /// #[tokio::main]
     /// async fn main() {
@@ -292,229 +2587,624 @@ impl Client {
     ///         var("SESSION_ID").unwrap(),
     ///         var("SESSION_KEY").unwrap()
     ///     );
-    ///     let client = Client::new(cookies).await;
-    ///     let chat_hist = client.chat_conversation_history("chat_uuid").await.unwrap();
-    ///     tracing::info!("{:#?}", chat_hist);
+    ///     let mut client = Client::new(cookies).await;
+    ///     client.fallback_models = vec![Model::Claude3Haiku];
+    ///     let completion = client
+    ///         .send_message_detailed("chat_uuid", "Hello", None, None).await
+    ///         .unwrap();
+    ///     tracing::info!("{} answered: {}", completion.model, completion.text);
     /// }
     /// ```
-    pub async fn chat_conversation_history(&self, chat_uuid: &str) -> Result<Vec<ChatMessage>> {
-        let url = format!(
-            "https://claude.ai/api/organizations/{}/chat_conversations/{}",
-            self.org_uuid,
-            chat_uuid
-        );
-
-        #[derive(Deserialize, Debug)]
-        struct Response {
-            chat_messages: Vec<ChatMessage>,
-        }
-
-        let res: Response = build_request(&self.cookies)?.get(url).send().await?.json().await?;
-
-        debug!("response: {:#?}", res.chat_messages);
-
-        Ok(res.chat_messages)
+    pub async fn send_message_detailed(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>
+    ) -> Result<Completion> {
+        self.send_with_models(chat_uuid, prompt, attachments, timeout, self.model_chain()).await
     }
 
-    /// Deletes a chat conversation.
+    /// Sends a message to a chat conversation and streams the answer as it is generated.
     ///
-    /// This function sends a DELETE request to the API to delete a chat conversation.
+    /// This function behaves like `send_message`, but returns as soon as the answer starts, yielding each
+    /// fragment of it as it arrives instead of buffering the whole answer. The stream ends after the last
+    /// fragment. Timeouts still apply while the stream is consumed: if no fragment arrives before a deadline
+    /// passes, the stream yields a timeout error and ends.
     ///
     /// # Arguments
     ///
-    /// * `chat_uuid` - A string representing the UUID of the chat conversation to be deleted.
+    /// * `chat_uuid` - The UUID of the chat conversation.
+    /// * `prompt` - A string representing the message to be sent.
+    /// * `attachments` - An optional vector of strings representing the paths to the files to be uploaded as attachments.
+    /// * `timeout` - An optional timeout in seconds for the whole answer. Defaults to 500 seconds.
     ///
     /// # Returns
     ///
-    /// * `Result<()>` - An empty `Result`, if the request is successful. Otherwise, an error.
+    /// * `Result<impl Stream<Item = Result<StreamEvent>>>` - The events of the answer, if the request is successful. Otherwise, an error.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the request fails.
+    /// This function will return an error if an attachment cannot be uploaded, if the request fails, or if every
+    /// model of the fallback chain is unavailable.
     ///
     /// # Examples
     ///
-    /// ```
-    /// use claude::Client;
-    /// use std::env::var;
-    /// #[tokio::main]
-    /// async fn main() {
-    ///     dotenv::dotenv().ok();
-    ///     tracing_subscriber::fmt::init();
-    ///     let cookies = format!(
-    ///         "activitySessionId={}; sessionKey={}",
-    ///         var("SESSION_ID").unwrap(),
-    ///         var("SESSION_KEY").unwrap()
-    ///     );
-    ///     let client = Client::new(cookies).await;
-    ///     let chat_hist = client.delete_conversation("chat_uuid_string").await.unwrap();
+    /// ```no_run
+    /// use claude::{ sse::StreamEvent, Client };
+    /// use futures_util::StreamExt;
+    /// use std::io::Write;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let stream = client.send_message_stream("chat_uuid", "Tell me a story", None, None).await?;
+    /// futures_util::pin_mut!(stream);
+    /// while let Some(event) = stream.next().await {
+    ///     if let StreamEvent::Completion(text) = event? {
+    ///         print!("{}", text);
+    ///         std::io::stdout().flush()?;
+    ///     }
     /// }
+    /// # Ok(())
+    /// # }
     /// ```
-    pub async fn delete_conversation(&self, chat_uuid: &str) -> Result<()> {
-        let url = format!(
-            "https://claude.ai/api/organizations/{}/chat_conversations/{}",
-            self.org_uuid,
-            chat_uuid
-        );
+    pub async fn send_message_stream(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>
+    ) -> Result<impl Stream<Item = Result<StreamEvent>>> {
+        let (response, deadlines, model, _) = self
+            .open_with_models(chat_uuid, prompt, attachments, timeout, self.model_chain()).await
+            .with_context(|| format!("while sending a message to conversation {}", chat_uuid))?;
+        debug!(operation = "send_message_stream", conversation_id = chat_uuid, model, "streaming answer");
 
-        let payload =
-            serde_json::json!({
-            "conversation_id": chat_uuid.to_string(),
-            });
+        let state = AnswerStream {
+            conversation_id: chat_uuid.to_string(),
+            model,
+            response: Some(response),
+            deadlines,
+            decoder: sse::EventDecoder::default(),
+            pending: VecDeque::new(),
+            received: false,
+        };
+        Ok(stream::unfold(state, AnswerStream::next))
+    }
+
+    /// Returns the default model followed by the fallback models.
+    fn model_chain(&self) -> Vec<String> {
+        std::iter
+            ::once(&self.default_model)
+            .chain(&self.fallback_models)
+            .map(|model| model.id().to_string())
+            .collect()
+    }
 
-        let res = build_request(&self.cookies)?.delete(url).json(&payload).send().await?;
+    /// Sends a message to a chat conversation with a specific model, without falling back to other models.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - The UUID of the chat conversation.
+    /// * `prompt` - A string representing the message to be sent.
+    /// * `attachments` - An optional vector of strings representing the paths to the files to be uploaded as attachments.
+    /// * `timeout` - An optional timeout in seconds for the request. Defaults to 500 seconds.
+    /// * `model` - The model, or its identifier as listed by `list_models`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an attachment cannot be uploaded, if the request fails, or if the
+    /// model is unavailable.
+    pub async fn send_message_with_model(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>,
+        model: impl Into<Model>
+    ) -> Result<Completion> {
+        let model = model.into().id().to_string();
+        self.send_with_models(chat_uuid, prompt, attachments, timeout, vec![model]).await
+    }
 
-        debug!("response: {:#?}", res);
+    /// Sends a message with each model of `models` in turn, until one of them is available.
+    async fn send_with_models(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>,
+        models: Vec<String>
+    ) -> Result<Completion> {
+        let context = || format!("while sending a message to conversation {}", chat_uuid);
+        let key = match &self.answer_cache {
+            Some(cache) => {
+                let mut hashes = vec![];
+                for path in attachments.iter().flatten() {
+                    hashes.push(utils::hash_file(path).await.with_context(context)?);
+                }
+                let key = AnswerKey { prompt: prompt.to_string(), attachments: hashes, models: models.clone() };
+                if !self.bypass_answer_cache {
+                    if let Some(completion) = cache.get(&key) {
+                        debug!(operation = "send_message", conversation_id = chat_uuid, "reusing cached answer");
+                        info!(
+                            target: "claude::audit",
+                            event = "message.cached",
+                            conversation_id = chat_uuid,
+                            model = completion.model
+                        );
+                        return Ok(completion);
+                    }
+                }
+                Some(key)
+            }
+            None => None,
+        };
 
-        Ok(())
+        let (response, deadlines, model, truncated) = self
+            .open_with_models(chat_uuid, prompt, attachments, timeout, models).await
+            .with_context(context)?;
+        let text = self.read_answer(chat_uuid, response, &deadlines).await.with_context(context)?;
+        info!(target: "claude::audit", event = "message.received", conversation_id = chat_uuid, model);
+        let completion = Completion { text, model, truncated };
+        if let (Some(cache), Some(key)) = (&self.answer_cache, key) {
+            cache.insert(key, completion.clone());
+        }
+        Ok(completion)
     }
 
-    /// Resets all chat conversations.
+    /// Uploads the attachments and opens the answer with each model of `models` in turn, until one of them is
+    /// available. Returns the response, the deadlines bounding it, the model answering and what was truncated
+    /// from the attachments.
+    async fn open_with_models(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        attachments: Option<Vec<&str>>,
+        timeout: Option<u64>,
+        models: Vec<String>
+    ) -> Result<(Response, MessageDeadlines, String, Vec<TruncationReport>)> {
+        if self.validate_before_send {
+            self.validate_conversation(chat_uuid).await?;
+        }
+
+        let uploads = self.upload_attachments(attachments.unwrap_or_default()).await?;
+        let estimated =
+            uploads.documents
+                .iter()
+                .map(|attachment| utils::estimate_tokens(&attachment.extracted_content))
+                .sum::<usize>() +
+            uploads.images.len() * IMAGE_TOKEN_ESTIMATE +
+            utils::estimate_tokens(prompt);
+
+        let timeout = Duration::from_secs(timeout.unwrap_or(500));
+
+        let mut last_error = None;
+        // The largest context window among the models skipped because the prompt does not fit.
+        let mut largest_window = 0;
+        for model in models {
+            if let Some(limit) = Model::from(model.as_str()).context_window() {
+                if estimated > limit {
+                    debug!(
+                        operation = "send_message",
+                        conversation_id = chat_uuid,
+                        model,
+                        estimated,
+                        limit,
+                        "prompt too long for the model, trying the next model"
+                    );
+                    largest_window = largest_window.max(limit);
+                    continue;
+                }
+            }
+            let deadlines = MessageDeadlines::new(self.first_byte_timeout, timeout);
+            let opened = if !uploads.images.is_empty() && !Model::from(model.as_str()).supports_vision() {
+                let message = format!("{} does not accept images", model);
+                Err(Error::ModelUnavailable { model: model.clone(), message })
+            } else {
+                self.open_message(chat_uuid, prompt, &uploads, &deadlines, &model).await
+            };
+            match opened {
+                Ok(response) => {
+                    info!(
+                        target: "claude::audit",
+                        event = "message.sent",
+                        conversation_id = chat_uuid,
+                        model,
+                        attachments = uploads.documents.len(),
+                        images = uploads.images.len()
+                    );
+                    return Ok((response, deadlines, model, uploads.truncated));
+                }
+                Err(e @ Error::ModelUnavailable { .. }) => {
+                    warn!(
+                        operation = "send_message",
+                        conversation_id = chat_uuid,
+                        error = %e,
+                        "model unavailable, falling back to the next model"
+                    );
+                    last_error = Some(e);
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            }
+        }
+
+        // A model that was found unavailable explains the failure better than the windows that are too small.
+        Err(last_error.unwrap_or(Error::PromptTooLong { estimated, limit: largest_window }))
+    }
+
+    /// Reads the whole answer from an `append_message` response.
+    async fn read_answer(
+        &self,
+        chat_uuid: &str,
+        mut response: Response,
+        deadlines: &MessageDeadlines
+    ) -> Result<String> {
+        let mut body = Vec::new();
+        loop {
+            match tokio::time::timeout_at(deadlines.limit(!body.is_empty()), response.chunk()).await {
+                Ok(Ok(Some(chunk))) => body.extend_from_slice(&chunk),
+                Ok(Ok(None)) => {
+                    break;
+                }
+                Ok(Err(e)) => {
+                    return Err(e.into());
+                }
+                Err(_) => {
+                    return Err(deadlines.timed_out(!body.is_empty()));
+                }
+            }
+        }
+
+        let decoded_data = String::from_utf8_lossy(&body);
+        let answer = sse::parse_completions(&decoded_data).join("");
+
+        if answer.is_empty() {
+            return Err(Error::EmptyResponse);
+        }
+
+        self.log_body("send_message", Some(chat_uuid), &answer);
+
+        Ok(answer)
+    }
+
+    /// Sends the `append_message` request and returns the response once its status shows the answer is coming.
+    async fn open_message(
+        &self,
+        chat_uuid: &str,
+        prompt: &str,
+        uploads: &Uploads,
+        deadlines: &MessageDeadlines,
+        model: &str
+    ) -> Result<Response> {
+        let url = format!("{}/api/append_message", self.base_url);
+
+        let payload = AppendMessageRequest {
+            completion: CompletionParams { prompt, timezone: &self.timezone, model },
+            organization_uuid: &self.org_uuid,
+            conversation_uuid: chat_uuid,
+            parent_message_uuid: None,
+            text: prompt,
+            attachments: &uploads.documents,
+            files: uploads.images.iter().map(|image| image.file_uuid.as_str()).collect(),
+        };
+
+        let request = self.http.post(url).json(&payload);
+        self.open_answer("send_message", chat_uuid, request, deadlines, model).await
+    }
+
+    /// Sends a request answered by a streamed completion, such as `append_message`, and returns the response
+    /// once its status shows the answer is coming.
+    async fn open_answer(
+        &self,
+        operation: &'static str,
+        chat_uuid: &str,
+        request: reqwest::RequestBuilder,
+        deadlines: &MessageDeadlines,
+        model: &str
+    ) -> Result<Response> {
+        let response = tokio::time
+            ::timeout_at(deadlines.limit(false), self.dispatch_raw(operation, Some(chat_uuid), request)).await
+            .map_err(|_| deadlines.timed_out(false))??;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(&response);
+            let body = response.text().await?;
+            return Err(if is_model_unavailable(status, &body) {
+                Error::ModelUnavailable { model: model.to_string(), message: body }
+            } else {
+                Error::from_status(status, retry_after.as_deref(), &body)
+            });
+        }
+
+        Ok(response)
+    }
+
+    /// Regenerates the last answer of a chat conversation, like the "Retry" button of the web UI.
+    ///
+    /// The previous answer is replaced by the new one in the conversation. The default model answers, falling
+    /// back to the fallback models when it is unavailable.
     ///
-    /// This function retrieves all chat conversations and deletes each one.
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - The UUID of the chat conversation.
+    /// * `timeout` - An optional timeout in seconds for the whole answer. Defaults to 500 seconds.
     ///
     /// # Returns
     ///
-    /// * `Result<()>` - An empty `Result`, if all chat conversations are successfully deleted. Otherwise, an error.
+    /// * `Result<String>` - The new answer, if the request is successful. Otherwise, an error.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the retrieval of chat conversations fails or if any chat conversation cannot be deleted.
+    /// This function will return an error if the request fails, if every model of the fallback chain is
+    /// unavailable, or if the answer is empty or times out.
     ///
-    pub async fn reset_all(&self) -> Result<()> {
-        let conversations = self.list_all_conversations().await?;
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let answer = client.send_message("chat_uuid", "Write a haiku about Rust", None, None).await?;
+    /// let another = client.retry_message("chat_uuid", None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn retry_message(&self, chat_uuid: &str, timeout: Option<u64>) -> Result<String> {
+        let context = || format!("while regenerating the last answer of conversation {}", chat_uuid);
+        let (response, deadlines, model) = self.open_retry(chat_uuid, timeout).await.with_context(context)?;
+        let text = self.read_answer(chat_uuid, response, &deadlines).await.with_context(context)?;
+        info!(target: "claude::audit", event = "message.received", conversation_id = chat_uuid, model);
+        Ok(text)
+    }
+
+    /// Regenerates the last answer of a chat conversation, streaming the new answer as it is generated.
+    ///
+    /// This function behaves like `retry_message`, but yields each fragment of the answer as it arrives, like
+    /// `send_message_stream`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if every model of the fallback chain is
+    /// unavailable.
+    pub async fn retry_message_stream(
+        &self,
+        chat_uuid: &str,
+        timeout: Option<u64>
+    ) -> Result<impl Stream<Item = Result<StreamEvent>>> {
+        let (response, deadlines, model) = self
+            .open_retry(chat_uuid, timeout).await
+            .with_context(|| format!("while regenerating the last answer of conversation {}", chat_uuid))?;
+        debug!(operation = "retry_message_stream", conversation_id = chat_uuid, model, "streaming answer");
+
+        let state = AnswerStream {
+            conversation_id: chat_uuid.to_string(),
+            model,
+            response: Some(response),
+            deadlines,
+            decoder: sse::EventDecoder::default(),
+            pending: VecDeque::new(),
+            received: false,
+        };
+        Ok(stream::unfold(state, AnswerStream::next))
+    }
+
+    /// Opens a regenerated answer with each model of the chain in turn, until one of them is available.
+    /// Returns the response, the deadlines bounding it and the model answering.
+    async fn open_retry(&self, chat_uuid: &str, timeout: Option<u64>) -> Result<(Response, MessageDeadlines, String)> {
+        let url = format!("{}/api/retry_message", self.base_url);
+        let request = AppendMessageRequest {
+            completion: CompletionParams { prompt: "", timezone: &self.timezone, model: "" },
+            organization_uuid: &self.org_uuid,
+            conversation_uuid: chat_uuid,
+            parent_message_uuid: None,
+            text: "",
+            attachments: &[],
+            files: vec![],
+        };
+        let opened = self.open_with_payload("retry_message", chat_uuid, &url, timeout, request).await?;
+        info!(target: "claude::audit", event = "message.retried", conversation_id = chat_uuid, model = opened.2);
+        Ok(opened)
+    }
+
+    /// Posts `payload` to `url` with each model of the chain in turn, until one of them is available. Returns the
+    /// response, the deadlines bounding it and the model answering.
+    async fn open_with_payload(
+        &self,
+        operation: &'static str,
+        chat_uuid: &str,
+        url: &str,
+        timeout: Option<u64>,
+        payload: AppendMessageRequest<'_>
+    ) -> Result<(Response, MessageDeadlines, String)> {
+        let timeout = Duration::from_secs(timeout.unwrap_or(500));
 
-        for conversation in conversations {
-            self.delete_conversation(&conversation.uuid).await?;
+        let mut last_error = None;
+        for model in self.model_chain() {
+            let deadlines = MessageDeadlines::new(self.first_byte_timeout, timeout);
+            let completion = CompletionParams { model: &model, ..payload.completion.clone() };
+            let request = self.http.post(url).json(&AppendMessageRequest { completion, ..payload.clone() });
+            match self.open_answer(operation, chat_uuid, request, &deadlines, &model).await {
+                Ok(response) => {
+                    return Ok((response, deadlines, model));
+                }
+                Err(e @ Error::ModelUnavailable { .. }) => {
+                    warn!(
+                        operation,
+                        conversation_id = chat_uuid,
+                        error = %e,
+                        "model unavailable, falling back to the next model"
+                    );
+                    last_error = Some(e);
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            }
         }
-        Ok(())
+
+        Err(last_error.expect("the model chain is never empty"))
     }
 
-    /// Uploads an attachment to the API.
+    /// Replaces a message sent by the user with `text`, and returns the answer to it, like editing a message in
+    /// the web UI.
     ///
-    /// This function sends a POST request to the API to upload a document.
-    /// The document is read from the file at the specified path and included in the request as a multipart form data.
-    /// The MIME type of the document is determined based on its file extension.
+    /// The edited message starts a new branch of the conversation from the message it followed; the original
+    /// message and the replies to it remain in the previous branch. The default model answers, falling back to
+    /// the fallback models when it is unavailable.
     ///
     /// # Arguments
     ///
-    /// * `file_path` - A string representing the path to the file to be uploaded.
+    /// * `chat_uuid` - The UUID of the chat conversation.
+    /// * `message_uuid` - The UUID of the message to edit, as listed by `chat_conversation_history`.
+    /// * `text` - The new text of the message.
+    /// * `timeout` - An optional timeout in seconds for the whole answer. Defaults to 500 seconds.
     ///
     /// # Returns
     ///
-    /// * `Result<Value>` - The API response, if the request is successful. Otherwise, an error.
+    /// * `Result<String>` - The answer to the edited message, if the request is successful. Otherwise, an error.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the file cannot be opened, if the request fails, or if the response cannot be deserialized.
+    /// This function will return `Error::MessageNotFound` if the conversation has no such message,
+    /// `Error::NotEditable` if the message was not sent by the user, or an error if a request fails, if every model
+    /// of the fallback chain is unavailable, or if the answer is empty or times out.
     ///
-    pub async fn upload_attachment(&self, file_path: &str) -> Result<Value> {
-        let url = "https://claude.ai/api/convert_document";
-        let mut headers = HEADERS.clone();
-        headers.insert(COOKIE, HeaderValue::from_str(&self.cookies)?);
-
-        let client = build_request(&self.cookies)?;
-
-        let file = File::open(file_path).await?;
-        let stream = FramedRead::new(file, BytesCodec::new());
-        let extension = Path::new(file_path).extension().unwrap().to_str().unwrap();
-
-        let mine = match extension {
-            "txt" => "text/plain".to_string(),
-            _ => format!("application/{}", extension),
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ Client, Sender };
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let history = client.chat_conversation_history("chat_uuid").await?;
+    /// if let Some(last) = history.iter().rev().find(|message| message.sender == Sender::Human) {
+    ///     let answer = client.edit_message("chat_uuid", &last.uuid, "Answer in French, please", None).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn edit_message(
+        &self,
+        chat_uuid: &str,
+        message_uuid: &str,
+        text: &str,
+        timeout: Option<u64>
+    ) -> Result<String> {
+        let context = || format!("while editing message {} of conversation {}", message_uuid, chat_uuid);
+        let history = self.chat_conversation_history(chat_uuid).await.with_context(context)?;
+        let message = history
+            .iter()
+            .find(|message| message.uuid == message_uuid)
+            .ok_or_else(|| Error::MessageNotFound(message_uuid.to_string()))
+            .with_context(context)?;
+        if message.sender != Sender::Human {
+            return Err(Error::NotEditable(message_uuid.to_string())).with_context(context);
+        }
+        // Conversations recorded before claude.ai tracked parents are linear, so the parent is the previous message.
+        let parent = match &message.parent_message_uuid {
+            Some(parent) => parent.clone(),
+            None =>
+                history
+                    .iter()
+                    .filter(|other| other.index < message.index)
+                    .max_by_key(|other| other.index)
+                    .map_or_else(|| ROOT_MESSAGE_UUID.to_string(), |other| other.uuid.clone()),
         };
-        let part = Part::stream(Body::wrap_stream(stream))
-            .file_name(file_path.to_string())
-            .mime_str(&mine)?;
-        let form = Form::new().part("file", part).text("orgUuid", self.org_uuid.clone());
-        let res = client.post(url).multipart(form).send().await?.json::<Value>().await?;
-        debug!("response: {:#?}", res);
 
-        Ok(res)
+        let url = format!("{}/api/append_message", self.base_url);
+        let request = AppendMessageRequest {
+            completion: CompletionParams { prompt: text, timezone: &self.timezone, model: "" },
+            organization_uuid: &self.org_uuid,
+            conversation_uuid: chat_uuid,
+            parent_message_uuid: Some(&parent),
+            text,
+            attachments: &[],
+            files: vec![],
+        };
+        let (response, deadlines, model) = self
+            .open_with_payload("edit_message", chat_uuid, &url, timeout, request).await
+            .with_context(context)?;
+        info!(target: "claude::audit", event = "message.edited", conversation_id = chat_uuid, model);
+        let text = self.read_answer(chat_uuid, response, &deadlines).await.with_context(context)?;
+        info!(target: "claude::audit", event = "message.received", conversation_id = chat_uuid, model);
+        Ok(text)
     }
 
-    /// Sends a message to a chat conversation.
+    /// Sends a message, creating a new chat conversation if the requested one no longer exists.
     ///
-    /// This function sends a POST request to the API to append a message to a chat conversation.
-    /// The message can include attachments, which are uploaded to the API before the message is sent.
-    /// The function waits for a response from the API for a specified amount of time before timing out.
+    /// This function checks the conversation with `validate_conversation`. If it is missing (for example it was
+    /// deleted in the web UI), a new conversation is created, optionally renamed to `name`, and the message is sent
+    /// there instead. This is convenient for bots that persist conversation UUIDs which may go stale.
     ///
     /// # Arguments
     ///
     /// * `chat_uuid` - A string representing the UUID of the chat conversation.
     /// * `prompt` - A string representing the message to be sent.
     /// * `attachments` - An optional vector of strings representing the paths to the files to be uploaded as attachments.
-    /// * `timeout` - An optional number representing the amount of time (in seconds) to wait for a response before timing out.
+    /// * `timeout` - An optional number representing the total amount of time (in seconds) allowed for generating the answer, 500 by default.
+    /// * `name` - An optional name given to the conversation if a new one has to be created.
     ///
     /// # Returns
     ///
-    /// * `Result<String>` - The API response, if the request is successful. Otherwise, an error.
+    /// * `Result<SendOutcome>` - The UUID of the conversation that was used and the answer, if the request is successful. Otherwise, an error.
     ///
     /// # Errors
     ///
-    /// This function will return an error if an attachment cannot be uploaded, if the request fails, if the response cannot be deserialized, or if the request times out.
+    /// This function will return an error if the conversation cannot be validated or created, or if sending the message fails.
     ///
-    pub async fn send_message(
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// use std::env::var;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     dotenv::dotenv().ok();
+    ///     tracing_subscriber::fmt::init();
+    ///     let cookies = format!(
+    ///         "activitySessionId={}; sessionKey={}",
+    ///         var("SESSION_ID").unwrap(),
+    ///         var("SESSION_KEY").unwrap()
+    ///     );
+    ///     let client = Client::new(cookies).await;
+    ///     let outcome = client
+    ///         .send_or_create("stale_chat_uuid", "Hello", None, None, Some("My bot")).await
+    ///         .unwrap();
+    ///     tracing::info!("answered in {}: {}", outcome.chat_uuid, outcome.completion.text);
+    /// }
+    /// ```
+    pub async fn send_or_create(
         &self,
         chat_uuid: &str,
         prompt: &str,
         attachments: Option<Vec<&str>>,
-        timeout: Option<u64>
-    ) -> Result<String> {
-        let url = "https://claude.ai/api/append_message";
-        let attachments = match attachments {
-            Some(attachments) => {
-                let mut res: Vec<Value> = vec![];
-                for a in attachments {
-                    let attachment = self.upload_attachment(a).await?;
-                    res.push(attachment);
+        timeout: Option<u64>,
+        name: Option<&str>
+    ) -> Result<SendOutcome> {
+        let (chat_uuid, created) = match self.validate_conversation(chat_uuid).await {
+            Ok(()) => (chat_uuid.to_string(), false),
+            Err(e) if matches!(e.root_cause(), Error::ConversationNotFound(_)) => {
+                let chat = self.create_new_chat().await?;
+                if let Some(name) = name {
+                    self.rename_chat(&chat.uuid, name).await?;
                 }
-                res
+                debug!(
+                    operation = "send_or_create",
+                    conversation_id = chat_uuid,
+                    new_conversation_id = %chat.uuid,
+                    "conversation not found, created a new one"
+                );
+                (chat.uuid, true)
             }
-            None => vec![],
-        };
-
-        let timeout = timeout.unwrap_or(500);
-
-        let payload =
-            serde_json::json!({
-             "completion": {
-                "prompt": prompt,
-                "timezone": "Asia/Saigon",
-                "model": "claude-2"
-            },
-            "organization_uuid": self.org_uuid.clone(),
-            "conversation_uuid": chat_uuid,
-            "text": prompt,
-            "attachments": attachments
-            });
-
-        let response = build_request(&self.cookies)?
-            .post(url)
-            .json(&payload)
-            .timeout(Duration::from_secs(timeout))
-            .send().await?;
-
-        let decoded_data = response.text().await?;
-        let re = regex::Regex::new(r"\n+").unwrap();
-        let decoded_data = re.replace_all(&decoded_data, "\n").trim().to_string();
-
-        let data_strings: Vec<&str> = decoded_data.split('\n').collect();
-        let mut completions = Vec::new();
-
-        for data_string in data_strings {
-            let json_str = &data_string[6..].trim();
-            let data: serde_json::Value = serde_json::from_str(json_str)?;
-            if data.get("completion").is_some() {
-                completions.push(data["completion"].as_str().unwrap().to_string());
+            Err(e) => {
+                return Err(e);
             }
-        }
-
-        let answer = completions.join("");
+        };
 
-        debug!("response: {:#?}", answer);
+        // The conversation was just checked or created, so `validate_before_send` would only check it again.
+        let client = Client { validate_before_send: false, ..self.clone() };
+        let completion = client.send_message_detailed(&chat_uuid, prompt, attachments, timeout).await?;
 
-        Ok(answer)
+        Ok(SendOutcome { chat_uuid, created, completion })
     }
 
     /// Renames a chat conversation.
@@ -534,7 +3224,7 @@ impl Client {
     ///
     /// This function will return an error if the request fails.
     pub async fn rename_chat(&self, chat_uuid: &str, title: &str) -> Result<()> {
-        let url = "https://claude.ai/api/rename_chat";
+        let url = format!("{}/api/rename_chat", self.base_url);
 
         let payload =
             serde_json::json!( {
@@ -543,10 +3233,71 @@ impl Client {
             "title": title.to_string(),
         });
 
-        let res = build_request(&self.cookies)?.post(url).json(&payload).send().await?;
-
-        debug!("response: {:#?}", res);
+        let request = self.http.post(url).json(&payload);
+        self.dispatch("rename_chat", Some(chat_uuid), request)
+            .await
+            .with_context(|| format!("while renaming conversation {}", chat_uuid))?;
+        info!(target: "claude::audit", event = "conversation.renamed", conversation_id = chat_uuid);
 
         Ok(())
     }
+
+    /// Sends an authenticated GET request to `path`, for endpoints this crate does not support yet.
+    ///
+    /// `path` is relative to the claude.ai origin, such as `/api/organizations/{org_uuid}/chat_conversations`;
+    /// the organization ID is in `org_uuid`. The request goes through the same headers, retries, rate limit and
+    /// error handling as every other request.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Value>` - The JSON response, or `Value::Null` for an empty body, if the request is successful.
+    ///   Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response is not JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let path = format!("/api/organizations/{}/projects", client.org_uuid);
+    /// let projects = client.raw_get(&path).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_get(&self, path: &str) -> Result<Value> {
+        let request = self.http.get(self.raw_url(path));
+        self.send_raw("raw_get", path, request).await
+    }
+
+    /// Sends an authenticated POST request with a JSON `body` to `path`, for endpoints this crate does not
+    /// support yet. See `raw_get`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response is not JSON.
+    pub async fn raw_post(&self, path: &str, body: &Value) -> Result<Value> {
+        let request = self.http.post(self.raw_url(path)).json(body);
+        self.send_raw("raw_post", path, request).await
+    }
+
+    fn raw_url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    async fn send_raw(&self, operation: &'static str, path: &str, request: reqwest::RequestBuilder) -> Result<Value> {
+        let context = || format!("while requesting {}", path);
+        let body = self.dispatch(operation, None, request)
+            .await
+            .with_context(context)?
+            .bytes().await
+            .with_context(context)?;
+        let res = if body.is_empty() { Value::Null } else { serde_json::from_slice(&body).with_context(context)? };
+
+        self.log_body(operation, None, &res);
+
+        Ok(res)
+    }
 }
@@ -0,0 +1,144 @@
+//! Experimental login to claude.ai by email, without copying cookies out of a browser.
+//!
+//! claude.ai signs users in by emailing them a magic link along with a short code. `EmailLogin` requests that
+//! email and exchanges either the code or the link for a fresh `sessionKey` cookie, ready for `Client::new`.
+//!
+//! The endpoints are undocumented and claude.ai may require a captcha for some accounts or networks, in which
+//! case the login fails with an API error. Enable the `login` feature to use this module.
+
+use reqwest::header::SET_COOKIE;
+use serde_json::{ json, Value };
+use tracing::info;
+
+use crate::{ builder::{ HttpOptions, DEFAULT_BASE_URL }, Context, Error, Result };
+
+/// Name of the cookie holding the claude.ai session.
+const SESSION_COOKIE: &str = "sessionKey";
+
+/// A login by email in progress.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::{ login::EmailLogin, Client };
+/// # async fn example() -> claude::Result<()> {
+/// let login = EmailLogin::new("jane@example.com")?;
+/// login.send().await?;
+///
+/// let mut code = String::new();
+/// std::io::stdin().read_line(&mut code)?;
+/// let cookies = login.verify_code(code.trim()).await?;
+/// let client = Client::builder(cookies).build().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct EmailLogin {
+    email: String,
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl EmailLogin {
+    /// Prepares the login of the account registered with `email`. Nothing is sent until `send`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the HTTP client cannot be built.
+    pub fn new(email: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            email: email.into(),
+            http: crate::build_client(crate::default_headers().clone(), &HttpOptions {
+                https_only: true,
+                ..HttpOptions::default()
+            })?,
+            base_url: DEFAULT_BASE_URL.to_string(),
+        })
+    }
+
+    /// Logs in through `url` instead of `https://claude.ai`, for example a mock server in tests.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the HTTP client cannot be rebuilt.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Result<Self> {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        let https_only = !self.base_url.starts_with("http://");
+        self.http = crate::build_client(crate::default_headers().clone(), &HttpOptions {
+            https_only,
+            ..HttpOptions::default()
+        })?;
+        Ok(self)
+    }
+
+    /// Asks claude.ai to email the magic link and login code to the account.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, for example because claude.ai requires a
+    /// captcha.
+    pub async fn send(&self) -> Result<()> {
+        let url = format!("{}/api/auth/send_magic_link", self.base_url);
+        let payload = json!({ "email_address": self.email, "source": "claude" });
+        let request = self.http.post(url).json(&payload);
+        let context = || "while requesting the login email";
+        let res = crate::execute("send_magic_link", None, request, None).await.with_context(context)?;
+        crate::error_for_status(res).await.with_context(context)?;
+        info!(target: "claude::audit", event = "login.sent");
+        Ok(())
+    }
+
+    /// Exchanges the code from the login email for a session, returning the cookies to build a client with.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::Unauthorized` if the code is wrong or expired, or
+    /// `Error::MissingSessionCookie` if claude.ai accepted it without starting a session.
+    pub async fn verify_code(&self, code: &str) -> Result<String> {
+        let credentials = json!({ "method": "code", "email_address": self.email, "code": code });
+        self.verify(credentials).await
+    }
+
+    /// Exchanges the magic link from the login email for a session, returning the cookies to build a client
+    /// with.
+    ///
+    /// The link has the form `https://claude.ai/magic-link#<nonce>:<encoded email>`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::InvalidMagicLink` if `link` does not have that form, `Error::Unauthorized`
+    /// if it is expired or was already used, or `Error::MissingSessionCookie` if claude.ai accepted it without
+    /// starting a session.
+    pub async fn verify_link(&self, link: &str) -> Result<String> {
+        let (nonce, encoded_email) = parse_magic_link(link).ok_or_else(|| Error::InvalidMagicLink(link.to_string()))?;
+        let credentials = json!({ "method": "nonce", "nonce": nonce, "encoded_email_address": encoded_email });
+        self.verify(credentials).await
+    }
+
+    async fn verify(&self, credentials: Value) -> Result<String> {
+        let url = format!("{}/api/auth/verify_magic_link", self.base_url);
+        let payload = json!({ "credentials": credentials, "source": "claude" });
+        let request = self.http.post(url).json(&payload);
+        let context = || "while verifying the login";
+        let res = crate::execute("verify_magic_link", None, request, None).await.with_context(context)?;
+        let res = crate::error_for_status(res).await.with_context(context)?;
+
+        let session = res.headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|cookie| cookie.split(';').next())
+            .find(|pair| pair.trim_start().starts_with(&format!("{}=", SESSION_COOKIE)))
+            .map(|pair| pair.trim().to_string())
+            .ok_or(Error::MissingSessionCookie)?;
+        info!(target: "claude::audit", event = "login.verified");
+        Ok(session)
+    }
+}
+
+/// Splits a magic link into its nonce and encoded email address.
+fn parse_magic_link(link: &str) -> Option<(&str, &str)> {
+    let (_, fragment) = link.split_once("/magic-link#")?;
+    let (nonce, encoded_email) = fragment.split_once(':')?;
+    (!nonce.is_empty() && !encoded_email.is_empty()).then_some((nonce, encoded_email))
+}
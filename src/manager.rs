@@ -0,0 +1,172 @@
+use std::{ collections::HashMap, fmt, sync::{ Arc, Mutex }, time::{ Duration, Instant } };
+use tracing::debug;
+
+use crate::{ builder::ClientBuilder, rate_limit::RateLimiter, Client, Error, Result };
+
+/// Default time after which the client of an inactive user is dropped.
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Holds the claude.ai sessions of many users, such as the members of a team sharing a bridge service.
+///
+/// Sessions are registered by user ID with their cookies. The client of a user is built on first use and
+/// reused afterwards, until it stays unused longer than the idle timeout; it is then dropped, closing its
+/// connections, and built again when the user comes back. Each user has their own rate limit, so one busy user
+/// cannot exhaust the limits of the others; it is shared by every client built for the session, including
+/// clones the user still holds after their client was dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::manager::ClientManager;
+/// use std::time::Duration;
+/// # async fn example(alice_cookies: String) -> claude::Result<()> {
+/// let manager = ClientManager::new().idle_timeout(Duration::from_secs(600)).requests_per_minute(20);
+/// manager.insert_session("alice", alice_cookies);
+///
+/// let client = manager.client("alice").await?;
+/// let chat = client.create_new_chat().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ClientManager {
+    tenants: Mutex<HashMap<String, Tenant>>,
+    idle_timeout: Duration,
+    requests_per_minute: Option<u32>,
+    configure: Option<Arc<dyn Fn(ClientBuilder) -> ClientBuilder + Send + Sync>>,
+}
+
+/// The session of a user, and its client while it is in use.
+struct Tenant {
+    cookies: String,
+    client: Option<Client>,
+    /// The rate limiter of the first client of the session, kept when the client is dropped so that clones still
+    /// held by the user and the clients built later share a single limit.
+    rate_limiter: Option<Arc<RateLimiter>>,
+    last_used: Instant,
+}
+
+impl fmt::Debug for ClientManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientManager")
+            .field("sessions", &self.tenants.lock().unwrap().len())
+            .field("idle_timeout", &self.idle_timeout)
+            .field("requests_per_minute", &self.requests_per_minute)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ClientManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientManager {
+    /// Returns a manager without sessions, dropping clients unused for 30 minutes and without rate limits.
+    pub fn new() -> Self {
+        Self {
+            tenants: Mutex::new(HashMap::new()),
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            requests_per_minute: None,
+            configure: None,
+        }
+    }
+
+    /// Sets the time after which the client of an inactive user is dropped.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Limits each user to `requests` requests per minute (see `ClientBuilder::requests_per_minute`).
+    pub fn requests_per_minute(mut self, requests: u32) -> Self {
+        self.requests_per_minute = Some(requests);
+        self
+    }
+
+    /// Sets a function configuring the builder of every client, such as to set a base URL or a retry policy.
+    pub fn configure(mut self, configure: impl Fn(ClientBuilder) -> ClientBuilder + Send + Sync + 'static) -> Self {
+        self.configure = Some(Arc::new(configure));
+        self
+    }
+
+    /// Registers the session of `user_id`, replacing any previous one and its client.
+    pub fn insert_session(&self, user_id: impl Into<String>, cookies: impl Into<String>) {
+        let tenant = Tenant { cookies: cookies.into(), client: None, rate_limiter: None, last_used: Instant::now() };
+        self.tenants.lock().unwrap().insert(user_id.into(), tenant);
+    }
+
+    /// Forgets the session of `user_id`, returning whether there was one.
+    pub fn remove_session(&self, user_id: &str) -> bool {
+        self.tenants.lock().unwrap().remove(user_id).is_some()
+    }
+
+    /// Returns whether a session is registered for `user_id`.
+    pub fn contains(&self, user_id: &str) -> bool {
+        self.tenants.lock().unwrap().contains_key(user_id)
+    }
+
+    /// Returns the client of `user_id`, building it if it is not in use yet.
+    ///
+    /// Idle clients of other users are dropped along the way.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::UnknownSession` if no session is registered for `user_id`, or an error
+    /// if the client cannot be built, such as when the cookies have expired.
+    pub async fn client(&self, user_id: &str) -> Result<Client> {
+        let (cookies, rate_limiter) = {
+            let mut tenants = self.tenants.lock().unwrap();
+            self.evict(&mut tenants, Some(user_id));
+            let tenant = tenants.get_mut(user_id).ok_or_else(|| Error::UnknownSession(user_id.to_string()))?;
+            tenant.last_used = Instant::now();
+            if let Some(client) = &tenant.client {
+                return Ok(client.clone());
+            }
+            (tenant.cookies.clone(), tenant.rate_limiter.clone())
+        };
+
+        debug!(operation = "client_manager", user_id, "building client");
+        let mut builder = Client::builder(cookies.clone());
+        if let Some(requests) = self.requests_per_minute {
+            builder = builder.requests_per_minute(requests);
+        }
+        if let Some(configure) = &self.configure {
+            builder = configure(builder);
+        }
+        let client = builder.shared_rate_limiter(rate_limiter).build().await?;
+
+        let mut tenants = self.tenants.lock().unwrap();
+        match tenants.get_mut(user_id) {
+            // Another call may have built a client meanwhile, which is kept so the user has a single rate limit.
+            Some(tenant) if tenant.cookies == cookies => {
+                if tenant.rate_limiter.is_none() {
+                    tenant.rate_limiter = client.rate_limiter.clone();
+                }
+                Ok(tenant.client.get_or_insert(client).clone())
+            }
+            // The session was replaced or removed while the client was built for the old one.
+            _ => Ok(client),
+        }
+    }
+
+    /// Drops the clients unused for longer than the idle timeout, returning how many were dropped.
+    ///
+    /// Sessions are kept, so the clients are built again when needed. `client` also does this on every call.
+    pub fn evict_idle(&self) -> usize {
+        self.evict(&mut self.tenants.lock().unwrap(), None)
+    }
+
+    fn evict(&self, tenants: &mut HashMap<String, Tenant>, keep: Option<&str>) -> usize {
+        let mut evicted = 0;
+        for (user_id, tenant) in tenants.iter_mut() {
+            let idle = tenant.last_used.elapsed() >= self.idle_timeout;
+            if idle && tenant.client.is_some() && Some(user_id.as_str()) != keep {
+                debug!(operation = "client_manager", user_id, "dropping idle client");
+                tenant.client = None;
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+}
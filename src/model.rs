@@ -0,0 +1,104 @@
+use serde::{ Deserialize, Deserializer, Serialize, Serializer };
+use std::{ convert::Infallible, fmt, str::FromStr };
+
+/// A model messages can be sent to.
+///
+/// Known models map to the identifiers claude.ai expects; `Custom` covers any other identifier, such as a
+/// model released after this version of the crate.
+///
+/// # Examples
+///
+/// ```
+/// use claude::Model;
+///
+/// assert_eq!(Model::Claude35Sonnet.id(), "claude-3-5-sonnet-20240620");
+/// assert_eq!("claude-3-opus-20240229".parse::<Model>().unwrap(), Model::Claude3Opus);
+/// assert_eq!(Model::from("claude-next"), Model::Custom("claude-next".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Model {
+    #[default]
+    Claude2,
+    Claude3Haiku,
+    Claude3Sonnet,
+    Claude3Opus,
+    Claude35Sonnet,
+    /// Any other model, by identifier.
+    Custom(String),
+}
+
+impl Model {
+    /// Returns the identifier sent to claude.ai for this model.
+    pub fn id(&self) -> &str {
+        match self {
+            Model::Claude2 => "claude-2",
+            Model::Claude3Haiku => "claude-3-haiku-20240307",
+            Model::Claude3Sonnet => "claude-3-sonnet-20240229",
+            Model::Claude3Opus => "claude-3-opus-20240229",
+            Model::Claude35Sonnet => "claude-3-5-sonnet-20240620",
+            Model::Custom(id) => id,
+        }
+    }
+
+    /// Returns the maximum number of tokens the model accepts in a conversation, if known.
+    pub fn context_window(&self) -> Option<usize> {
+        match self {
+            Model::Claude2 => Some(100_000),
+            Model::Claude3Haiku | Model::Claude3Sonnet | Model::Claude3Opus | Model::Claude35Sonnet => Some(200_000),
+            Model::Custom(_) => None,
+        }
+    }
+
+    /// Returns whether the model accepts images. Custom models are assumed to, leaving the check to claude.ai.
+    pub fn supports_vision(&self) -> bool {
+        !matches!(self, Model::Claude2)
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for Model {
+    type Err = Infallible;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Ok(match id {
+            "claude-2" => Model::Claude2,
+            "claude-3-haiku-20240307" => Model::Claude3Haiku,
+            "claude-3-sonnet-20240229" => Model::Claude3Sonnet,
+            "claude-3-opus-20240229" => Model::Claude3Opus,
+            "claude-3-5-sonnet-20240620" => Model::Claude35Sonnet,
+            id => Model::Custom(id.to_string()),
+        })
+    }
+}
+
+impl From<&str> for Model {
+    fn from(id: &str) -> Self {
+        match id.parse() {
+            Ok(model) => model,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl From<String> for Model {
+    fn from(id: String) -> Self {
+        Model::from(id.as_str())
+    }
+}
+
+impl Serialize for Model {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Model {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Model::from(String::deserialize(deserializer)?))
+    }
+}
@@ -0,0 +1,28 @@
+use opentelemetry::propagation::Injector;
+use reqwest::header::{ HeaderMap, HeaderName, HeaderValue };
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if
+            let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(&value),
+            )
+        {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// Injects the OpenTelemetry context of `span` into outgoing request headers using the globally
+/// configured propagator, so the request shows up inline in the caller's distributed trace.
+pub(crate) fn inject_context(span: &Span, headers: &mut HeaderMap) {
+    let context = span.context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers));
+    });
+}
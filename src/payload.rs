@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+use crate::UploadedAttachment;
+
+/// The body of the `append_message` and `retry_message` requests, which send a message and stream the answer.
+///
+/// # Examples
+///
+/// ```
+/// use claude::payload::{ AppendMessageRequest, CompletionParams };
+///
+/// let request = AppendMessageRequest {
+///     completion: CompletionParams { prompt: "Hello", timezone: "Europe/Paris", model: "claude-2" },
+///     organization_uuid: "org_uuid",
+///     conversation_uuid: "chat_uuid",
+///     parent_message_uuid: None,
+///     text: "Hello",
+///     attachments: &[],
+///     files: vec![],
+/// };
+/// let json = serde_json::to_value(&request).unwrap();
+/// assert_eq!(json["completion"]["model"], "claude-2");
+/// assert!(json.get("parent_message_uuid").is_none());
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct AppendMessageRequest<'a> {
+    pub completion: CompletionParams<'a>,
+    pub organization_uuid: &'a str,
+    pub conversation_uuid: &'a str,
+    /// The message the new one follows, to start a new branch from it. The message follows the current leaf
+    /// when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_message_uuid: Option<&'a str>,
+    /// The message, empty when regenerating the last answer.
+    pub text: &'a str,
+    /// The converted documents attached to the message.
+    pub attachments: &'a [UploadedAttachment],
+    /// The UUIDs of the images attached to the message.
+    pub files: Vec<&'a str>,
+}
+
+/// How the answer of an `AppendMessageRequest` is generated.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionParams<'a> {
+    /// The message, repeated from `AppendMessageRequest::text`.
+    pub prompt: &'a str,
+    /// The IANA time zone of the user, such as `Asia/Saigon`, which the model uses to tell the local time.
+    pub timezone: &'a str,
+    /// The identifier of the model answering.
+    pub model: &'a str,
+}
@@ -0,0 +1,258 @@
+use serde::{ Deserialize, Serialize };
+use serde_json::{ Map, Value };
+use tracing::{ debug, info };
+
+use crate::{ Client, Context, Conversation, NewChatOptions, Result };
+
+/// A project of the account, as listed by `Client::list_projects`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectInfo {
+    pub uuid: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Whether only the creator can see the project, as opposed to the whole organization.
+    #[serde(default)]
+    pub is_private: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Any other field of the project, kept so that fields added by claude.ai are not lost.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A document of the knowledge base of a project, added with `Client::add_project_doc`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectDoc {
+    pub uuid: String,
+    pub file_name: String,
+    /// The text of the document, which every conversation of the project sees.
+    #[serde(default)]
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Any other field of the document, kept so that fields added by claude.ai are not lost.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A handle to a project, the unit claude.ai groups knowledge-base documents and conversations in.
+///
+/// Obtained with `Client::project`. Conversations created through the handle belong to the project, so they
+/// see its documents and custom instructions.
+#[derive(Debug, Clone, Copy)]
+pub struct Project<'a> {
+    client: &'a Client,
+    uuid: &'a str,
+}
+
+impl Client {
+    /// Returns a handle to the project `uuid`.
+    ///
+    /// No request is sent: an unknown project only fails once the handle is used.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let project = client.project("project_uuid");
+    /// let chat = project.create_chat().await?;
+    /// client.send_message(&chat.uuid, "Summarize the project documents", None, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn project<'a>(&'a self, uuid: &'a str) -> Project<'a> {
+        Project { client: self, uuid }
+    }
+
+    /// Creates a private project.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ProjectInfo>` - The created project, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let info = client.create_project("Thesis", "Drafts and reviews of my thesis").await?;
+    /// client.add_project_doc(&info.uuid, "outline.md", "# Outline\n...").await?;
+    /// let chat = client.project(&info.uuid).create_chat().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_project(&self, name: &str, description: &str) -> Result<ProjectInfo> {
+        let url = format!("{}/api/organizations/{}/projects", self.base_url, self.org_uuid);
+
+        let payload = serde_json::json!({
+            "name": name,
+            "description": description,
+            "is_private": true,
+        });
+
+        let request = self.http.post(url).json(&payload);
+        let context = || format!("while creating project {}", name);
+        let res: ProjectInfo = self.dispatch("create_project", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("create_project", None, &res);
+        info!(target: "claude::audit", event = "project.created", project_uuid = %res.uuid);
+
+        Ok(res)
+    }
+
+    /// Lists the projects of the account.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ProjectInfo>>` - The projects, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    pub async fn list_projects(&self) -> Result<Vec<ProjectInfo>> {
+        let url = format!("{}/api/organizations/{}/projects", self.base_url, self.org_uuid);
+
+        let request = self.http.get(url);
+        let context = || "while listing projects";
+        let res: Vec<ProjectInfo> = self.dispatch("list_projects", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("list_projects", None, &res);
+
+        Ok(res)
+    }
+
+    /// Deletes a project, along with its documents.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails.
+    pub async fn delete_project(&self, project_uuid: &str) -> Result<()> {
+        let url = format!("{}/api/organizations/{}/projects/{}", self.base_url, self.org_uuid, project_uuid);
+
+        let request = self.http.delete(url);
+        self.dispatch("delete_project", None, request)
+            .await
+            .with_context(|| format!("while deleting project {}", project_uuid))?;
+        info!(target: "claude::audit", event = "project.deleted", project_uuid);
+
+        Ok(())
+    }
+
+    /// Adds a text document to the knowledge base of a project.
+    ///
+    /// # Arguments
+    ///
+    /// * `project_uuid` - The UUID of the project.
+    /// * `file_name` - The name the document is shown under.
+    /// * `content` - The text of the document.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ProjectDoc>` - The added document, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    pub async fn add_project_doc(&self, project_uuid: &str, file_name: &str, content: &str) -> Result<ProjectDoc> {
+        let url = format!("{}/api/organizations/{}/projects/{}/docs", self.base_url, self.org_uuid, project_uuid);
+
+        let payload = serde_json::json!({
+            "file_name": file_name,
+            "content": content,
+        });
+
+        let request = self.http.post(url).json(&payload);
+        let context = || format!("while adding {} to project {}", file_name, project_uuid);
+        let res: ProjectDoc = self.dispatch("add_project_doc", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("add_project_doc", None, &res);
+        info!(target: "claude::audit", event = "project.doc_added", project_uuid, doc_uuid = %res.uuid);
+
+        Ok(res)
+    }
+
+    /// Lists the documents of the knowledge base of a project.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    pub async fn list_project_docs(&self, project_uuid: &str) -> Result<Vec<ProjectDoc>> {
+        let url = format!("{}/api/organizations/{}/projects/{}/docs", self.base_url, self.org_uuid, project_uuid);
+
+        let request = self.http.get(url);
+        let context = || format!("while listing the documents of project {}", project_uuid);
+        let res: Vec<ProjectDoc> = self.dispatch("list_project_docs", None, request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("list_project_docs", None, &res);
+
+        Ok(res)
+    }
+}
+
+impl<'a> Project<'a> {
+    /// Returns the UUID of the project.
+    pub fn uuid(&self) -> &'a str {
+        self.uuid
+    }
+
+    /// Creates a new chat conversation inside the project.
+    ///
+    /// The conversation is named from `Client::title_template`, like those created by `Client::create_new_chat`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Conversation>` - The created chat conversation, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    pub async fn create_chat(&self) -> Result<Conversation> {
+        self.client
+            .create_new_chat_with(NewChatOptions { project_uuid: Some(self.uuid.to_string()), ..NewChatOptions::default() })
+            .await
+            .with_context(|| format!("while creating a conversation in project {}", self.uuid))
+    }
+
+    /// Lists the conversations of the project.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Conversation>>` - The conversations whose `project_uuid` is this project, if the request is
+    ///   successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    pub async fn list_conversations(&self) -> Result<Vec<Conversation>> {
+        let mut conversations = self.client
+            .list_all_conversations().await
+            .with_context(|| format!("while listing conversations of project {}", self.uuid))?;
+        conversations.retain(|c| c.project_uuid.as_deref() == Some(self.uuid));
+        debug!(operation = "list_project_conversations", project_uuid = self.uuid, count = conversations.len());
+        Ok(conversations)
+    }
+}
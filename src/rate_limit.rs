@@ -0,0 +1,33 @@
+use std::{ sync::Mutex, time::{ Duration, Instant } };
+
+use crate::{ deadline, Result };
+
+/// Spaces requests evenly so that no more than a given number are sent per minute.
+///
+/// Each request reserves the next free slot, so concurrent callers queue up in order instead of all waking up
+/// at once. Shared between the clones of a client.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits for the next slot, failing with `Error::DeadlineExceeded` if the current deadline passes first.
+    pub async fn acquire(&self) -> Result<()> {
+        let slot = {
+            let mut next = self.next.lock().unwrap();
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        deadline::bound(tokio::time::sleep_until(tokio::time::Instant::from_std(slot))).await
+    }
+}
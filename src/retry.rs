@@ -0,0 +1,101 @@
+use std::{ collections::hash_map::RandomState, fmt, hash::{ BuildHasher, Hasher }, time::Duration };
+
+use crate::Error;
+
+/// Decides whether, and after how long, a failed request is retried.
+///
+/// The client calls `should_retry` after every failed attempt of a request, with the error the attempt
+/// failed with: a transport error, or an error built from the status and headers of an error response,
+/// whose body is left for the caller. Requests with streaming bodies cannot be replayed and are never
+/// retried. `RetryPolicy` is the usual strategy; implement this trait for others, and set it with
+/// `ClientBuilder::retry_strategy`.
+///
+/// # Examples
+///
+/// A budget of retries shared by all requests, so that an outage does not multiply the load:
+///
+/// ```
+/// use claude::{ retry::RetryStrategy, Error, RetryPolicy };
+/// use std::{ sync::atomic::{ AtomicU32, Ordering }, time::Duration };
+///
+/// #[derive(Debug)]
+/// struct RetryBudget {
+///     remaining: AtomicU32,
+///     policy: RetryPolicy,
+/// }
+///
+/// impl RetryStrategy for RetryBudget {
+///     fn should_retry(&self, attempt: u32, error: &Error) -> Option<Duration> {
+///         let delay = self.policy.should_retry(attempt, error)?;
+///         self.remaining.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).ok()?;
+///         Some(delay)
+///     }
+/// }
+///
+/// let budget = RetryBudget { remaining: AtomicU32::new(1), policy: RetryPolicy::default() };
+/// assert!(budget.should_retry(1, &Error::EmptyResponse).is_some());
+/// assert!(budget.should_retry(1, &Error::EmptyResponse).is_none());
+/// ```
+pub trait RetryStrategy: fmt::Debug + Send + Sync {
+    /// Returns the pause before retry number `attempt` (starting at 1) of a request that failed with `error`,
+    /// or `None` to give up and return the failure to the caller.
+    fn should_retry(&self, attempt: u32, error: &Error) -> Option<Duration>;
+}
+
+/// How the client retries requests that failed transiently.
+///
+/// Rate limited (429) and server error (5xx) responses, as well as connection failures and timeouts, are
+/// retried with an exponentially growing, jittered backoff. When the server says when to retry, with a
+/// `Retry-After` header or a reset time in the body, that delay is used instead; a delay longer than
+/// `max_backoff` is not waited for and the response is returned to the caller.
+///
+/// Retries are disabled unless a policy is set with `ClientBuilder::retry_policy`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt.
+    pub max_retries: u32,
+    /// Backoff before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for the pause between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the longest backoff before retry number `attempt` (starting at 1), doubling each time up to
+    /// `max_backoff`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    /// Returns a random pause between half of `backoff(attempt)` and all of it, so that clients failing at the
+    /// same time do not retry in lockstep.
+    fn jittered_backoff(&self, attempt: u32) -> Duration {
+        let backoff = self.backoff(attempt);
+        let random = RandomState::new().build_hasher().finish();
+        backoff / 2 + backoff.mul_f64(((random % 1000) as f64) / 2000.0)
+    }
+}
+
+impl RetryStrategy for RetryPolicy {
+    fn should_retry(&self, attempt: u32, error: &Error) -> Option<Duration> {
+        if attempt > self.max_retries || !error.is_retryable() {
+            return None;
+        }
+        match error.retry_after() {
+            Some(delay) if delay > self.max_backoff => None,
+            Some(delay) => Some(delay),
+            None => Some(self.jittered_backoff(attempt)),
+        }
+    }
+}
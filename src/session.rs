@@ -0,0 +1,270 @@
+use std::sync::atomic::{ AtomicBool, Ordering };
+use tracing::debug;
+#[cfg(feature = "language")]
+use tracing::warn;
+#[cfg(feature = "language")]
+pub use whatlang::Lang;
+
+use crate::{ Client, Context, Error, Model, Result };
+
+/// A conversation bound to a client, carrying per-session options applied to every message.
+#[derive(Debug)]
+pub struct ChatSession<'a> {
+    client: &'a Client,
+    chat_uuid: String,
+    model: Option<Model>,
+    #[cfg(feature = "language")]
+    language: Option<Lang>,
+    system: Option<String>,
+    needs_framing: AtomicBool,
+}
+
+impl<'a> ChatSession<'a> {
+    /// Creates a session for an existing chat conversation.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client used to send messages.
+    /// * `chat_uuid` - The UUID of the chat conversation.
+    pub fn new(client: &'a Client, chat_uuid: impl Into<String>) -> Self {
+        Self {
+            client,
+            chat_uuid: chat_uuid.into(),
+            model: None,
+            #[cfg(feature = "language")]
+            language: None,
+            system: None,
+            needs_framing: AtomicBool::new(false),
+        }
+    }
+
+    /// Creates a new chat conversation and returns a session bound to it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversation cannot be created.
+    pub async fn create(client: &'a Client) -> Result<Self> {
+        let chat = client.create_new_chat().await?;
+        let session = Self::new(client, chat.uuid);
+        session.needs_framing.store(true, Ordering::SeqCst);
+        Ok(session)
+    }
+
+    /// Creates a session for an existing chat conversation, pinned to `model`.
+    ///
+    /// Every message of the session is answered by `model`, without falling back to other models. The model is
+    /// checked against `Client::list_models` up front, so a typo or a model missing from the plan fails here
+    /// rather than on the first send.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client used to send messages.
+    /// * `chat_uuid` - The UUID of the chat conversation.
+    /// * `model` - The model, or its identifier such as `claude-2`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::UnknownModel` if the account cannot use `model`, or an error if the
+    /// models cannot be listed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ ChatSession, Client, Model };
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let session = ChatSession::with_model(&client, "chat_uuid", Model::Claude35Sonnet).await?;
+    /// let answer = session.send("What is Rust?", None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_model(client: &'a Client, chat_uuid: impl Into<String>, model: impl Into<Model>) -> Result<Self> {
+        let model = model.into();
+        validate_model(client, &model).await?;
+        let mut session = Self::new(client, chat_uuid);
+        session.model = Some(model);
+        Ok(session)
+    }
+
+    /// Creates a new chat conversation and returns a session bound to it, pinned to `model`.
+    ///
+    /// The model is validated before the conversation is created, as in `with_model`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::UnknownModel` if the account cannot use `model`, or an error if the
+    /// models cannot be listed or the conversation cannot be created.
+    pub async fn create_with_model(client: &'a Client, model: impl Into<Model>) -> Result<Self> {
+        let model = model.into();
+        validate_model(client, &model).await?;
+        let mut session = Self::create(client).await?;
+        session.model = Some(model);
+        Ok(session)
+    }
+
+    /// Returns the model the session is pinned to, if any.
+    pub fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    /// Moves the session to a brand new chat conversation, for example once the current one has grown too long.
+    ///
+    /// The system framing message, if any, is injected again as the first turn of the new conversation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversation cannot be created.
+    pub async fn start_new_conversation(&mut self) -> Result<()> {
+        let chat = self.client.create_new_chat().await?;
+        debug!(
+            operation = "start_new_conversation",
+            conversation_id = %chat.uuid,
+            previous_conversation_id = %self.chat_uuid,
+            "session moved to a new conversation"
+        );
+        self.chat_uuid = chat.uuid;
+        self.needs_framing.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Sets a framing message that emulates a system prompt.
+    ///
+    /// The web backend has no system prompt, so the text is sent as the first turn of every conversation the
+    /// session creates, before the first user prompt. Sessions opened with `new` on an existing conversation
+    /// are assumed to be framed already.
+    pub fn set_system(&mut self, text: impl Into<String>) {
+        self.system = Some(text.into());
+    }
+
+    /// Returns the UUID of the chat conversation this session sends to.
+    pub fn chat_uuid(&self) -> &str {
+        &self.chat_uuid
+    }
+
+    /// Requires answers to be written in `language`.
+    ///
+    /// Every prompt gets an instruction to answer in that language appended, and the language of each answer
+    /// is detected. If it drifts, the question is asked again once with a stronger instruction. Requires the
+    /// `language` feature.
+    #[cfg(feature = "language")]
+    pub fn set_language(&mut self, language: Lang) {
+        self.language = Some(language);
+    }
+
+    /// Sends a message in this session.
+    ///
+    /// # Arguments
+    ///
+    /// * `prompt` - A string representing the message to be sent.
+    /// * `attachments` - An optional vector of strings representing the paths to the files to be uploaded as attachments.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The answer, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if sending the message fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ ChatSession, Client };
+    /// use std::env::var;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     dotenv::dotenv().ok();
+    ///     let cookies = format!(
+    ///         "activitySessionId={}; sessionKey={}",
+    ///         var("SESSION_ID").unwrap(),
+    ///         var("SESSION_KEY").unwrap()
+    ///     );
+    ///     let client = Client::new(cookies).await;
+    ///     let mut session = ChatSession::create(&client).await.unwrap();
+    ///     session.set_system("You are a concise assistant.");
+    ///     let answer = session.send("What is Rust?", None).await.unwrap();
+    ///     println!("{}", answer);
+    /// }
+    /// ```
+    pub async fn send(&self, prompt: &str, attachments: Option<Vec<&str>>) -> Result<String> {
+        if let Some(system) = &self.system {
+            if self.needs_framing.swap(false, Ordering::SeqCst) {
+                debug!(operation = "set_system", conversation_id = %self.chat_uuid, "injecting system framing");
+                if let Err(e) = self.send_raw(system, None).await {
+                    self.needs_framing.store(true, Ordering::SeqCst);
+                    return Err(e);
+                }
+            }
+        }
+
+        #[cfg(feature = "language")]
+        if let Some(language) = self.language {
+            return self.send_in_language(prompt, attachments, language).await;
+        }
+        self.send_raw(prompt, attachments).await
+    }
+
+    /// Sends a message with an instruction to answer in `language`, asking again once if the answer drifted.
+    #[cfg(feature = "language")]
+    async fn send_in_language(&self, prompt: &str, attachments: Option<Vec<&str>>, language: Lang) -> Result<String> {
+        let prompt = format!("{}\n\nPlease answer in {}.", prompt, language.eng_name());
+        let answer = self.send_raw(&prompt, attachments).await?;
+        if matches_language(&answer, language) {
+            return Ok(answer);
+        }
+
+        debug!(
+            operation = "send",
+            conversation_id = %self.chat_uuid,
+            language = language.code(),
+            "answer drifted away from the session language, asking again"
+        );
+        let retry = format!(
+            "Your previous answer was not written in {0}. Please give the same answer again, written only in {0}.",
+            language.eng_name()
+        );
+        let answer = self.send_raw(&retry, None).await?;
+        if !matches_language(&answer, language) {
+            warn!(
+                operation = "send",
+                conversation_id = %self.chat_uuid,
+                language = language.code(),
+                "answer is still not in the session language after retrying"
+            );
+        }
+        Ok(answer)
+    }
+
+    /// Sends a message to the session's conversation, with the pinned model if any.
+    async fn send_raw(&self, prompt: &str, attachments: Option<Vec<&str>>) -> Result<String> {
+        match &self.model {
+            Some(model) => {
+                let completion = self.client
+                    .send_message_with_model(&self.chat_uuid, prompt, attachments, None, model.clone()).await?;
+                Ok(completion.text)
+            }
+            None => self.client.send_message(&self.chat_uuid, prompt, attachments, None).await,
+        }
+    }
+}
+
+/// Checks that `model` is one of the models available to the account.
+async fn validate_model(client: &Client, model: &Model) -> Result<()> {
+    let context = || format!("while validating model {}", model);
+    let models = client.list_models().await.with_context(context)?;
+    if models.iter().any(|m| m.id == model.id()) {
+        return Ok(());
+    }
+    Err(Error::UnknownModel {
+        model: model.to_string(),
+        available: models.into_iter().map(|m| m.id).collect(),
+    })
+}
+
+/// Returns whether `text` is written in `language`, giving the benefit of the doubt when detection is unreliable.
+#[cfg(feature = "language")]
+fn matches_language(text: &str, language: Lang) -> bool {
+    match whatlang::detect(text) {
+        Some(info) if info.is_reliable() => info.lang() == language,
+        _ => true,
+    }
+}
@@ -0,0 +1,99 @@
+//! Comparison of answers, to tell whether a new model or a retry changed an answer materially.
+//!
+//! Everything is computed locally from the text of the answers, without embeddings or external services.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+/// How close two answers are, from the strictest comparison to the loosest.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Similarity {
+    /// Whether the answers are identical.
+    pub exact: bool,
+    /// Whether the answers are identical once whitespace is normalized (see `normalize_whitespace`).
+    pub normalized: bool,
+    /// The share of words the answers have in common (see `token_overlap`), from 0 to 1.
+    pub overlap: f64,
+}
+
+impl Similarity {
+    /// Compares two answers in every way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use claude::similarity::Similarity;
+    ///
+    /// let similarity = Similarity::of("The answer is 42.", "The answer is  42.\n");
+    /// assert!(!similarity.exact);
+    /// assert!(similarity.normalized);
+    /// assert_eq!(similarity.overlap, 1.0);
+    /// assert!(!similarity.changed_materially(0.8));
+    /// ```
+    pub fn of(old: &str, new: &str) -> Self {
+        Self {
+            exact: old == new,
+            normalized: normalize_whitespace(old) == normalize_whitespace(new),
+            overlap: token_overlap(old, new),
+        }
+    }
+
+    /// Returns whether the answers differ by more than their layout, with less than `threshold` of their
+    /// words in common.
+    pub fn changed_materially(&self, threshold: f64) -> bool {
+        !self.normalized && self.overlap < threshold
+    }
+}
+
+/// Collapses every run of whitespace into a single space and trims both ends, so that answers differing only
+/// by line breaks or indentation compare equal.
+///
+/// # Examples
+///
+/// ```
+/// use claude::similarity::normalize_whitespace;
+///
+/// assert_eq!(normalize_whitespace("  Hello,\n\n\tworld!  "), "Hello, world!");
+/// ```
+pub fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Scores how many words two texts share, from 0 for no common word to 1 for the same words.
+///
+/// Words are compared case-insensitively, without punctuation, and counted with their repetitions. The score
+/// is the F1 score of the words of `new` against those of `old`: twice the number of common words divided by
+/// the number of words of both texts. Two texts without any word score 1.
+///
+/// # Examples
+///
+/// ```
+/// use claude::similarity::token_overlap;
+///
+/// assert_eq!(token_overlap("Paris is the capital.", "the capital is PARIS"), 1.0);
+/// assert_eq!(token_overlap("red green", "red blue"), 0.5);
+/// assert_eq!(token_overlap("yes", "no"), 0.0);
+/// ```
+pub fn token_overlap(old: &str, new: &str) -> f64 {
+    let old = word_counts(old);
+    let new = word_counts(new);
+    let total: usize = old.values().chain(new.values()).sum();
+    if total == 0 {
+        return 1.0;
+    }
+    let common: usize = old
+        .iter()
+        .map(|(word, count)| (*count).min(new.get(word).copied().unwrap_or_default()))
+        .sum();
+    (2 * common) as f64 / total as f64
+}
+
+/// Counts the lowercase words of a text, splitting it on anything that is not a letter or a digit.
+pub(crate) fn word_counts(text: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()) {
+        *counts.entry(word.to_lowercase()).or_default() += 1;
+    }
+    counts
+}
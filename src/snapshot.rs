@@ -0,0 +1,162 @@
+use chrono::{ DateTime, Utc };
+use serde::{ Deserialize, Serialize };
+use serde_json::Value;
+
+use crate::{ utils, ChatMessage, Client, Context, Conversation, Result };
+
+/// A conversation and its messages as they were at a given time, fetched with `Client::conversation_snapshot`.
+///
+/// A snapshot cannot be modified, so it can be shared between the code exporting, comparing and caching
+/// conversations without any of them seeing the others' changes. It serializes with serde, for example to
+/// keep it on disk.
+///
+/// # Examples
+///
+/// ```
+/// use claude::{ snapshot::ConversationSnapshot, ChatMessage, Conversation };
+///
+/// let conversation: Conversation = serde_json::from_value(serde_json::json!({
+///     "uuid": "chat_uuid", "name": "Greetings", "summary": "",
+/// })).unwrap();
+/// let message = |uuid: &str, index: usize| -> ChatMessage {
+///     serde_json::from_value(serde_json::json!({
+///         "uuid": uuid, "index": index, "sender": "human", "text": "Hello", "attachments": [],
+///     })).unwrap()
+/// };
+/// let before = ConversationSnapshot::new(conversation.clone(), vec![message("a", 0)]);
+/// let after = ConversationSnapshot::new(conversation, vec![message("a", 0), message("b", 1)]);
+///
+/// assert!(after.changed_since(&before));
+/// let added: Vec<_> = after.added_since(&before).iter().map(|message| message.uuid.as_str()).collect();
+/// assert_eq!(added, ["b"]);
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConversationSnapshot {
+    conversation: Conversation,
+    messages: Vec<ChatMessage>,
+    fetched_at: DateTime<Utc>,
+}
+
+impl ConversationSnapshot {
+    /// Returns a snapshot of `conversation` and its `messages`, taken now.
+    pub fn new(conversation: Conversation, messages: Vec<ChatMessage>) -> Self {
+        Self::at(conversation, messages, Utc::now())
+    }
+
+    /// Returns a snapshot of `conversation` and its `messages`, taken at `fetched_at`.
+    pub fn at(conversation: Conversation, messages: Vec<ChatMessage>, fetched_at: DateTime<Utc>) -> Self {
+        Self { conversation, messages, fetched_at }
+    }
+
+    /// Returns the name, summary and other metadata of the conversation.
+    pub fn conversation(&self) -> &Conversation {
+        &self.conversation
+    }
+
+    /// Returns the messages of the conversation, in order.
+    pub fn messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    /// Returns when the snapshot was taken.
+    pub fn fetched_at(&self) -> DateTime<Utc> {
+        self.fetched_at
+    }
+
+    /// Returns how long ago the snapshot was taken, for caches to tell whether it is still fresh.
+    pub fn age(&self) -> chrono::Duration {
+        Utc::now() - self.fetched_at
+    }
+
+    /// Returns the checksum of the messages, as computed by `utils::conversation_checksum`.
+    pub fn checksum(&self) -> String {
+        utils::conversation_checksum(&self.messages)
+    }
+
+    /// Returns whether any message was added, removed or edited between `older` and this snapshot.
+    pub fn changed_since(&self, older: &ConversationSnapshot) -> bool {
+        self.checksum() != older.checksum()
+    }
+
+    /// Returns the messages of this snapshot that `older` does not have.
+    pub fn added_since(&self, older: &ConversationSnapshot) -> Vec<&ChatMessage> {
+        self.messages
+            .iter()
+            .filter(|message| older.messages.iter().all(|known| known.uuid != message.uuid))
+            .collect()
+    }
+
+    /// Feeds the conversation and its messages to an exporter.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the exporter fails to write.
+    #[cfg(feature = "export")]
+    pub fn write(&self, exporter: &mut dyn crate::export::Exporter) -> Result<()> {
+        crate::export::write_conversation(exporter, &self.conversation, &self.messages)
+    }
+
+    /// Returns the conversation and its messages, dropping the time the snapshot was taken.
+    pub fn into_parts(self) -> (Conversation, Vec<ChatMessage>) {
+        (self.conversation, self.messages)
+    }
+}
+
+#[cfg(feature = "export")]
+impl From<ConversationSnapshot> for crate::export::Transcript {
+    fn from(snapshot: ConversationSnapshot) -> Self {
+        let (conversation, messages) = snapshot.into_parts();
+        Self { conversation, messages }
+    }
+}
+
+impl Client {
+    /// Retrieves a chat conversation together with its messages, in a single request.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let snapshot = client.conversation_snapshot("chat_uuid").await?;
+    /// println!("{} had {} messages at {}", snapshot.conversation().name, snapshot.messages().len(), snapshot.fetched_at());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn conversation_snapshot(&self, chat_uuid: &str) -> Result<ConversationSnapshot> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+
+        let request = self.http.get(url);
+        let context = || format!("while fetching a snapshot of conversation {}", chat_uuid);
+        let mut res: Value = self.dispatch("conversation_snapshot", Some(chat_uuid), request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+        let fetched_at = Utc::now();
+
+        let messages = res
+            .as_object_mut()
+            .and_then(|conversation| conversation.remove("chat_messages"))
+            .unwrap_or_else(|| Value::Array(vec![]));
+        let messages: Vec<ChatMessage> = serde_json::from_value(messages).with_context(context)?;
+        let conversation: Conversation = serde_json::from_value(res).with_context(context)?;
+
+        self.log_body("conversation_snapshot", Some(chat_uuid), &conversation);
+        self.check_fields("conversation_snapshot", chat_uuid, conversation.missing_fields())?;
+        for message in &messages {
+            self.check_fields("conversation_snapshot", &message.uuid, message.missing_fields())?;
+        }
+
+        Ok(ConversationSnapshot::at(conversation, messages, fetched_at))
+    }
+}
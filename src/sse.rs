@@ -0,0 +1,319 @@
+use futures_util::{ stream, Stream, StreamExt };
+use serde::Serialize;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{ Error, ErrorKind, Result };
+
+/// Extracts the completion fragments from a raw `append_message` event stream.
+///
+/// The parser is deliberately tolerant: blank lines, comments, `event:` lines, truncated or otherwise
+/// malformed `data:` payloads and events without a string `completion` are skipped rather than failing the
+/// whole answer, since transport hiccups regularly produce garbage lines. It never panics, whatever the input.
+///
+/// # Examples
+///
+/// ```
+/// let body = "data: {\"completion\":\"Hello\"}\n\ndata: {\"completion\":\" world\"}\n\n";
+/// assert_eq!(claude::sse::parse_completions(body), vec!["Hello", " world"]);
+/// ```
+pub fn parse_completions(body: &str) -> Vec<String> {
+    let mut decoder = EventDecoder::default();
+    let mut events = decoder.push(body.as_bytes());
+    events.extend(decoder.finish());
+    events
+        .into_iter()
+        .filter_map(|event| {
+            match event {
+                StreamEvent::Completion(completion) => Some(completion),
+                StreamEvent::Stop(_) => None,
+            }
+        })
+        .collect()
+}
+
+/// A raw server-sent event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    /// The type given by the `event:` field, if any.
+    pub event: Option<String>,
+    /// The `data:` fields of the event, joined with line breaks.
+    pub data: String,
+    /// The `id:` field of the event, if any.
+    pub id: Option<String>,
+}
+
+/// Incrementally parses a `text/event-stream` into `Event`s as chunks arrive.
+///
+/// Follows the [event stream format](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation):
+/// lines end with `\n`, `\r\n` or `\r`, comments start with `:`, consecutive `data:` fields are joined with
+/// line breaks and a blank line dispatches the event. Chunks can end anywhere, including in the middle of a
+/// line or of a UTF-8 sequence. Unknown fields are ignored, and an event left without a closing blank line when
+/// the stream ends is still returned by `finish`, since truncated streams are common.
+///
+/// # Examples
+///
+/// ```
+/// use claude::sse::{ Event, EventStreamParser };
+///
+/// let mut parser = EventStreamParser::default();
+/// assert_eq!(parser.push(b": keep-alive\nevent: completion\ndata: {\"a\":"), vec![]);
+/// assert_eq!(parser.push(b"\ndata: 1}\n\n"), vec![Event {
+///     event: Some("completion".to_string()),
+///     data: "{\"a\":\n1}".to_string(),
+///     id: None,
+/// }]);
+/// ```
+#[derive(Debug, Default)]
+pub struct EventStreamParser {
+    pending: Vec<u8>,
+    /// Whether the last chunk ended with `\r`, so a leading `\n` in the next one ends no other line.
+    after_cr: bool,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl EventStreamParser {
+    /// Feeds the next chunk of the stream, returning the events it completes.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Event> {
+        let mut chunk = chunk;
+        if self.after_cr && chunk.first() == Some(&b'\n') {
+            chunk = &chunk[1..];
+            self.after_cr = false;
+        }
+        if let Some(&last) = chunk.last() {
+            self.after_cr = last == b'\r';
+        }
+        self.pending.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        let mut start = 0;
+        let mut index = 0;
+        while index < self.pending.len() {
+            match self.pending[index] {
+                b'\n' | b'\r' => {
+                    let end = index;
+                    if self.pending[index] == b'\r' && self.pending.get(index + 1) == Some(&b'\n') {
+                        index += 1;
+                    }
+                    let line = String::from_utf8_lossy(&self.pending[start..end]).into_owned();
+                    events.extend(self.process_line(&line));
+                    start = index + 1;
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+        self.pending.drain(..start);
+        events
+    }
+
+    /// Parses whatever is left once the stream has ended, such as a last event without a closing blank line.
+    pub fn finish(mut self) -> Vec<Event> {
+        let line = String::from_utf8_lossy(&std::mem::take(&mut self.pending)).into_owned();
+        let mut events: Vec<Event> = self.process_line(&line).into_iter().collect();
+        events.extend(self.dispatch());
+        events
+    }
+
+    /// Applies a complete line to the event being built, returning the event if the line dispatches it.
+    fn process_line(&mut self, line: &str) -> Option<Event> {
+        // Stray whitespace around fields is tolerated, so whitespace-only lines dispatch events too.
+        let line = line.trim_start();
+        if line.trim_end().is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => {
+                self.event = Some(value.to_string());
+            }
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => {
+                self.id = Some(value.to_string());
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Returns the event built so far, if it carries data, and starts a new one.
+    fn dispatch(&mut self) -> Option<Event> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(Event { event, data: std::mem::take(&mut self.data).join("\n"), id: self.id.clone() })
+    }
+}
+
+/// An event of a streamed answer, as yielded by `Client::send_message_stream`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// The next fragment of the answer.
+    Completion(String),
+    /// The model finished answering, with the reason it stopped, such as `stop_sequence` or `max_tokens`.
+    Stop(String),
+}
+
+/// An event of a streamed answer rendered for machine consumers, one JSON object per line, by `json_lines`.
+///
+/// Every line has a `type` field: `delta` for each fragment of the answer, then a single `done` or `error` line
+/// that ends the answer.
+///
+/// # Examples
+///
+/// ```
+/// use claude::sse::JsonLine;
+///
+/// let line = JsonLine::Delta { text: "Hello".to_string() };
+/// assert_eq!(line.to_line(), "{\"type\":\"delta\",\"text\":\"Hello\"}\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonLine {
+    /// The next fragment of the answer.
+    Delta {
+        text: String,
+    },
+    /// The answer is complete, with the reason the model stopped if claude.ai gave one.
+    Done {
+        stop_reason: Option<String>,
+    },
+    /// The answer failed. Nothing follows.
+    Error {
+        kind: ErrorKind,
+        message: String,
+        retryable: bool,
+    },
+}
+
+impl JsonLine {
+    /// Returns the `error` line reporting `error`, such as a failure to open the answer.
+    pub fn error(error: &Error) -> Self {
+        JsonLine::Error { kind: error.kind(), message: error.to_string(), retryable: error.is_retryable() }
+    }
+
+    /// Returns the line as compact JSON, ending with a line break.
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("JSON lines always serialize");
+        line.push('\n');
+        line
+    }
+}
+
+/// Renders the events of a streamed answer, such as the stream of `Client::send_message_stream`, as JSON lines.
+///
+/// The stream always ends with exactly one `done` or `error` line, even when the answer ends without a stop
+/// reason, so a consumer reading the lines from a subprocess knows when the answer is over.
+///
+/// # Examples
+///
+/// ```
+/// use claude::sse::{ json_lines, JsonLine, StreamEvent };
+/// use futures_util::{ stream, StreamExt };
+/// # futures_util::FutureExt::now_or_never(async {
+/// let events = stream::iter(vec![Ok(StreamEvent::Completion("Hi".to_string()))]);
+/// let lines: Vec<JsonLine> = json_lines(events).collect().await;
+/// assert_eq!(lines, [JsonLine::Delta { text: "Hi".to_string() }, JsonLine::Done { stop_reason: None }]);
+/// # });
+/// ```
+pub fn json_lines(events: impl Stream<Item = Result<StreamEvent>>) -> impl Stream<Item = JsonLine> {
+    let events = Box::pin(events);
+    stream::unfold(Some(events), |events| async move {
+        let mut events = events?;
+        let line = match events.next().await {
+            Some(Ok(StreamEvent::Completion(text))) => JsonLine::Delta { text },
+            Some(Ok(StreamEvent::Stop(stop_reason))) => {
+                return Some((JsonLine::Done { stop_reason: Some(stop_reason) }, None));
+            }
+            Some(Err(e)) => {
+                return Some((JsonLine::error(&e), None));
+            }
+            None => {
+                return Some((JsonLine::Done { stop_reason: None }, None));
+            }
+        };
+        Some((line, Some(events)))
+    })
+}
+
+/// Incrementally decodes an `append_message` event stream into `StreamEvent`s as chunks arrive.
+///
+/// Events are parsed with `EventStreamParser`, then their JSON payloads as tolerantly as by
+/// `parse_completions`: when the joined `data:` fields of an event are not valid JSON, each field is tried on
+/// its own, since truncated payloads are usually followed by intact ones.
+///
+/// # Examples
+///
+/// ```
+/// use claude::sse::{ EventDecoder, StreamEvent };
+///
+/// let mut decoder = EventDecoder::default();
+/// assert_eq!(decoder.push(b"data: {\"completion\":\"Hel"), vec![]);
+/// assert_eq!(
+///     decoder.push(b"lo\"}\n\ndata: {\"completion\":\"\",\"stop_reason\":\"stop_sequence\"}\n"),
+///     vec![StreamEvent::Completion("Hello".to_string())]
+/// );
+/// assert_eq!(
+///     decoder.finish(),
+///     vec![StreamEvent::Completion("".to_string()), StreamEvent::Stop("stop_sequence".to_string())]
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct EventDecoder {
+    parser: EventStreamParser,
+}
+
+impl EventDecoder {
+    /// Feeds the next chunk of the stream, returning the answer events of every event it completes.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<StreamEvent> {
+        self.parser.push(chunk).iter().flat_map(answer_events).collect()
+    }
+
+    /// Parses whatever is left once the stream has ended, such as a last event without a closing blank line.
+    pub fn finish(self) -> Vec<StreamEvent> {
+        self.parser.finish().iter().flat_map(answer_events).collect()
+    }
+}
+
+/// Extracts the answer events carried by a server-sent event.
+fn answer_events(event: &Event) -> Vec<StreamEvent> {
+    let payloads = match parse_payload(&event.data) {
+        Some(data) => vec![data],
+        None if event.data.contains('\n') => event.data.lines().filter_map(parse_payload).collect(),
+        None => vec![],
+    };
+
+    let mut events = Vec::new();
+    for data in payloads {
+        if let Some(completion) = data.get("completion").and_then(Value::as_str) {
+            events.push(StreamEvent::Completion(completion.to_string()));
+        }
+        if let Some(stop_reason) = data.get("stop_reason").and_then(Value::as_str) {
+            events.push(StreamEvent::Stop(stop_reason.to_string()));
+        }
+    }
+    events
+}
+
+/// Parses a JSON payload, skipping empty or malformed ones.
+fn parse_payload(payload: &str) -> Option<Value> {
+    let payload = payload.trim();
+    if payload.is_empty() {
+        return None;
+    }
+
+    match serde_json::from_str::<Value>(payload) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            debug!(operation = "send_message", error = %e, "skipping malformed event");
+            None
+        }
+    }
+}
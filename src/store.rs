@@ -0,0 +1,152 @@
+use serde::{ Deserialize, Serialize };
+use std::{ collections::HashMap, fs, path::{ Path, PathBuf } };
+use tracing::debug;
+
+use crate::{ utils, Attachment, ChatMessage, Client, Context, ConversationSnapshot, Result };
+
+/// A local, content-addressed store of attachment contents.
+///
+/// Contents are written once under `blobs/<sha256>` however many messages and conversations share them, and
+/// `index.json` maps each attachment ID to its blob, so downloaded conversations can be resolved back to local
+/// files.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::{ store::AttachmentStore, Client };
+/// # async fn example(client: Client) -> claude::Result<()> {
+/// let mut store = AttachmentStore::open("attachments")?;
+/// let snapshot = client.download_attachments("chat_uuid", &mut store).await?;
+/// for message in snapshot.messages() {
+///     for path in store.resolve(message).into_iter().flatten() {
+///         println!("{}", path.display());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AttachmentStore {
+    root: PathBuf,
+    index: HashMap<String, StoredAttachment>,
+}
+
+/// An entry of the store index.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StoredAttachment {
+    /// Hex-encoded SHA-256 digest of the contents, naming the blob.
+    pub sha256: String,
+    pub file_name: String,
+    pub file_type: String,
+}
+
+impl AttachmentStore {
+    /// Opens the store in `root`, creating the directory if needed and loading its index if it exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directory cannot be created or the index cannot be read.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(root.join("blobs")).with_context(|| format!("while opening store {}", root.display()))?;
+        let index = match fs::read(root.join("index.json")) {
+            Ok(index) => serde_json::from_slice(&index)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("while reading the index of store {}", root.display()));
+            }
+        };
+        Ok(Self { root, index })
+    }
+
+    /// Returns the directory of the store.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Stores the contents of `attachment`, unless identical contents are already stored, and returns the path
+    /// of its blob.
+    ///
+    /// The index is only updated in memory; call `save` to persist it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the blob cannot be written.
+    pub fn store(&mut self, attachment: &Attachment) -> Result<PathBuf> {
+        let sha256 = utils::sha256_hex(attachment.extracted_content.as_bytes());
+        let path = self.blob_path(&sha256);
+        if !path.exists() {
+            let partial = path.with_extension("partial");
+            fs::write(&partial, &attachment.extracted_content)?;
+            fs::rename(&partial, &path)?;
+        } else {
+            debug!(operation = "store_attachment", sha256, "contents already stored");
+        }
+        self.index.insert(attachment.id.clone(), StoredAttachment {
+            sha256,
+            file_name: attachment.file_name.clone(),
+            file_type: attachment.file_type.clone(),
+        });
+        Ok(path)
+    }
+
+    /// Returns the index entry of the attachment `id`, if it is stored.
+    pub fn get(&self, id: &str) -> Option<&StoredAttachment> {
+        self.index.get(id)
+    }
+
+    /// Resolves the attachments of `message` to local paths, in order, with `None` for those not stored.
+    pub fn resolve(&self, message: &ChatMessage) -> Vec<Option<PathBuf>> {
+        message.attachments
+            .iter()
+            .map(|attachment| self.get(&attachment.id).map(|stored| self.blob_path(&stored.sha256)))
+            .collect()
+    }
+
+    /// Writes the index to `index.json`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the index cannot be written.
+    pub fn save(&self) -> Result<()> {
+        let path = self.root.join("index.json");
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_vec_pretty(&self.index)?)?;
+        fs::rename(&partial, &path).with_context(|| format!("while saving the index of store {}", self.root.display()))
+    }
+
+    fn blob_path(&self, sha256: &str) -> PathBuf {
+        self.root.join("blobs").join(sha256)
+    }
+}
+
+impl Client {
+    /// Downloads a snapshot of a conversation and stores the contents of its attachments in `store`.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - The UUID of the chat conversation.
+    /// * `store` - The store receiving the attachments. Its index is saved once every attachment is stored.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ConversationSnapshot>` - The conversation and its messages, whose attachments `store.resolve`
+    ///   resolves to local paths, if the request is successful. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversation cannot be retrieved or the store cannot be written.
+    pub async fn download_attachments(
+        &self,
+        chat_uuid: &str,
+        store: &mut AttachmentStore
+    ) -> Result<ConversationSnapshot> {
+        let snapshot = self.conversation_snapshot(chat_uuid).await?;
+        let context = || format!("while storing the attachments of conversation {}", chat_uuid);
+        for attachment in snapshot.messages().iter().flat_map(|message| &message.attachments) {
+            store.store(attachment).with_context(context)?;
+        }
+        store.save().with_context(context)?;
+        Ok(snapshot)
+    }
+}
@@ -0,0 +1,167 @@
+use tracing::{ debug, warn };
+
+use crate::{ utils::{ estimate_tokens, split_text }, ChatMessage, Client, Context, Result };
+
+/// Estimated number of tokens of the transcript sent with each summarization prompt.
+const CHUNK_TOKENS: usize = 20_000;
+/// Separates the partial summaries combined into one.
+const SUMMARY_SEPARATOR: &str = "\n\n---\n\n";
+
+/// The kind of summary `Client::summarize_conversation` writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SummaryStyle {
+    /// A single paragraph.
+    #[default]
+    Brief,
+    /// Several paragraphs covering every topic, decision and open question.
+    Detailed,
+    /// A bulleted list of the key points.
+    Bullets,
+}
+
+impl SummaryStyle {
+    /// Returns the instruction describing the summary to the model.
+    fn instruction(&self) -> &'static str {
+        match self {
+            SummaryStyle::Brief => "Write the summary as a single short paragraph.",
+            SummaryStyle::Detailed =>
+                "Write a detailed summary of several paragraphs, covering every topic discussed, the decisions made and the questions left open.",
+            SummaryStyle::Bullets => "Write the summary as a bulleted list of the key points, one line each.",
+        }
+    }
+}
+
+impl Client {
+    /// Summarizes a whole chat conversation, however long it is.
+    ///
+    /// The transcript is cut into parts small enough for a single prompt, each part is summarized, then the
+    /// partial summaries are combined until a single one is left. Every prompt is sent in a helper conversation
+    /// that is deleted once answered, as with `generate_summary`, so the summarized conversation is left as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `chat_uuid` - A string representing the UUID of the chat conversation to summarize.
+    /// * `style` - The kind of summary to write.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The summary, if every prompt succeeds. Otherwise, an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the conversation cannot be retrieved, or if a helper conversation
+    /// cannot be created or answered. A helper conversation that cannot be deleted is only logged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::{ summarize::SummaryStyle, Client };
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let summary = client.summarize_conversation("chat_uuid", SummaryStyle::Bullets).await?;
+    /// println!("{}", summary);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn summarize_conversation(&self, chat_uuid: &str, style: SummaryStyle) -> Result<String> {
+        let context = || format!("while summarizing conversation {}", chat_uuid);
+        let snapshot = self.conversation_snapshot(chat_uuid).await.with_context(context)?;
+        let chunks = chunk_transcript(snapshot.messages(), CHUNK_TOKENS);
+
+        let mut summaries = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            let prompt = if chunks.len() == 1 {
+                format!(
+                    "Summarize the following conversation. {} Reply with the summary only.\n\n{}",
+                    style.instruction(),
+                    chunk
+                )
+            } else {
+                format!(
+                    "Summarize the following part {} of {} of a conversation. {} Reply with the summary only.\n\n{}",
+                    i + 1,
+                    chunks.len(),
+                    style.instruction(),
+                    chunk
+                )
+            };
+            debug!(operation = "summarize_conversation", conversation_id = chat_uuid, part = i + 1, parts = chunks.len());
+            summaries.push(self.ask_helper(&prompt).await.with_context(context)?);
+        }
+
+        while summaries.len() > 1 {
+            let groups = group_summaries(&summaries, CHUNK_TOKENS);
+            debug!(operation = "summarize_conversation", conversation_id = chat_uuid, summaries = summaries.len(), groups = groups.len());
+            let mut combined = Vec::with_capacity(groups.len());
+            for group in groups {
+                let prompt = format!(
+                    "The following are summaries of consecutive parts of a conversation, separated by `---`. Combine them into a single summary of the whole conversation. {} Reply with the summary only.\n\n{}",
+                    style.instruction(),
+                    group
+                );
+                combined.push(self.ask_helper(&prompt).await.with_context(context)?);
+            }
+            summaries = combined;
+        }
+
+        Ok(summaries.pop().unwrap_or_default())
+    }
+
+    /// Sends `prompt` in a new helper conversation and deletes the conversation, returning the answer.
+    ///
+    /// The answer is returned even if the helper conversation cannot be deleted, which is logged as a warning.
+    pub(crate) async fn ask_helper(&self, prompt: &str) -> Result<String> {
+        let helper = self.create_new_chat().await?;
+        let answer = self.send_message(&helper.uuid, prompt, None, None).await;
+        if let Err(e) = self.delete_conversation(&helper.uuid).await {
+            warn!(operation = "ask_helper", conversation_id = helper.uuid, error = %e, "cannot delete the helper conversation");
+        }
+        Ok(answer?.trim().to_string())
+    }
+}
+
+/// Renders the messages as a transcript cut into parts of about `max_tokens` tokens, cutting between messages
+/// whenever possible.
+fn chunk_transcript(messages: &[ChatMessage], max_tokens: usize) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut chunk = String::new();
+    for message in messages {
+        // A character is at least two bytes per estimated token, so parts of this size always fit.
+        for part in split_text(&format!("{}: {}\n\n", message.sender, message.text), 2 * max_tokens, false) {
+            if !chunk.is_empty() && estimate_tokens(&chunk) + estimate_tokens(&part) > max_tokens {
+                chunks.push(std::mem::take(&mut chunk));
+            }
+            chunk.push_str(&part);
+        }
+    }
+    if !chunk.is_empty() || chunks.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Joins consecutive summaries into groups of about `max_tokens` tokens, at least two summaries each so that
+/// every round of combining makes progress.
+fn group_summaries(summaries: &[String], max_tokens: usize) -> Vec<String> {
+    let mut groups = vec![];
+    let mut group: Vec<&str> = vec![];
+    let mut tokens = 0;
+    for summary in summaries {
+        let summary_tokens = estimate_tokens(summary);
+        if group.len() >= 2 && tokens + summary_tokens > max_tokens {
+            groups.push(group.join(SUMMARY_SEPARATOR));
+            group.clear();
+            tokens = 0;
+        }
+        group.push(summary);
+        tokens += summary_tokens;
+    }
+    match groups.last_mut() {
+        // A lone summary left over is combined with the previous group rather than summarized again on its own.
+        Some(last) if group.len() == 1 => {
+            last.push_str(SUMMARY_SEPARATOR);
+            last.push_str(group[0]);
+        }
+        _ => groups.push(group.join(SUMMARY_SEPARATOR)),
+    }
+    groups
+}
@@ -0,0 +1,160 @@
+//! Adapters pacing a streamed answer for display, so every frontend does not have to write the same glue.
+//!
+//! Both take the stream returned by `Client::send_message_stream`.
+
+use futures_util::{ stream, Stream, StreamExt };
+use std::{ collections::VecDeque, pin::Pin, time::Duration };
+use tokio::time::{ sleep_until, timeout_at, Instant };
+
+use crate::{ Result, StreamEvent };
+
+/// Splits the answer into single characters yielded at most once per `interval`, for a typewriter effect.
+///
+/// Other events and errors are passed through once the characters received before them are yielded. The
+/// pace only ever slows the answer down: characters are never held back longer than needed to keep `interval`
+/// between them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::{ throttle, Client, StreamEvent };
+/// use futures_util::StreamExt;
+/// use std::time::Duration;
+/// # async fn example(client: Client) -> claude::Result<()> {
+/// let answer = client.send_message_stream("chat_uuid", "Tell me a story", None, None).await?;
+/// let mut answer = Box::pin(throttle::typewriter(answer, Duration::from_millis(15)));
+/// while let Some(event) = answer.next().await {
+///     if let StreamEvent::Completion(c) = event? {
+///         print!("{}", c);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn typewriter<S>(events: S, interval: Duration) -> impl Stream<Item = Result<StreamEvent>>
+    where S: Stream<Item = Result<StreamEvent>>
+{
+    let state = Typewriter {
+        events: Box::pin(events),
+        chars: VecDeque::new(),
+        held: None,
+        next_at: Instant::now(),
+        interval,
+    };
+    stream::unfold(state, Typewriter::next)
+}
+
+struct Typewriter<S> {
+    events: Pin<Box<S>>,
+    chars: VecDeque<char>,
+    /// An event received while characters were still queued, yielded after them.
+    held: Option<Result<StreamEvent>>,
+    next_at: Instant,
+    interval: Duration,
+}
+
+impl<S: Stream<Item = Result<StreamEvent>>> Typewriter<S> {
+    async fn next(mut self) -> Option<(Result<StreamEvent>, Self)> {
+        loop {
+            if let Some(c) = self.chars.pop_front() {
+                sleep_until(self.next_at).await;
+                self.next_at = Instant::now().max(self.next_at) + self.interval;
+                return Some((Ok(StreamEvent::Completion(c.to_string())), self));
+            }
+            if let Some(event) = self.held.take() {
+                return Some((event, self));
+            }
+            match self.events.next().await? {
+                Ok(StreamEvent::Completion(text)) => self.chars.extend(text.chars()),
+                event => self.held = Some(event),
+            }
+        }
+    }
+}
+
+/// Turns the answer into snapshots of the whole text received so far, at most one per `interval`.
+///
+/// Each snapshot is meant to replace the previous one on screen, so a UI re-renders at a bounded rate however
+/// small the fragments are. The complete answer is always yielded last. `Stop` events are dropped; errors are
+/// passed through.
+///
+/// # Examples
+///
+/// ```no_run
+/// use claude::{ throttle, Client };
+/// use futures_util::StreamExt;
+/// use std::time::Duration;
+/// # async fn example(client: Client) -> claude::Result<()> {
+/// let answer = client.send_message_stream("chat_uuid", "Tell me a story", None, None).await?;
+/// let mut renders = Box::pin(throttle::debounce(answer, Duration::from_millis(100)));
+/// while let Some(text) = renders.next().await {
+///     println!("{}", text?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn debounce<S>(events: S, interval: Duration) -> impl Stream<Item = Result<String>>
+    where S: Stream<Item = Result<StreamEvent>>
+{
+    let state = Debounce {
+        events: Some(Box::pin(events)),
+        text: String::new(),
+        dirty: false,
+        next_at: Instant::now(),
+        interval,
+    };
+    stream::unfold(state, Debounce::next)
+}
+
+struct Debounce<S> {
+    /// The answer, until it ends.
+    events: Option<Pin<Box<S>>>,
+    text: String,
+    /// Whether text was received since the last snapshot.
+    dirty: bool,
+    next_at: Instant,
+    interval: Duration,
+}
+
+impl<S: Stream<Item = Result<StreamEvent>>> Debounce<S> {
+    async fn next(mut self) -> Option<(Result<String>, Self)> {
+        loop {
+            let Some(events) = self.events.as_mut() else {
+                return self.snapshot();
+            };
+            let event = if self.dirty {
+                match timeout_at(self.next_at, events.next()).await {
+                    Ok(event) => event,
+                    Err(_) => {
+                        return self.snapshot();
+                    }
+                }
+            } else {
+                events.next().await
+            };
+            match event {
+                Some(Ok(StreamEvent::Completion(text))) => {
+                    self.text.push_str(&text);
+                    self.dirty = true;
+                }
+                Some(Ok(StreamEvent::Stop(_))) => {}
+                Some(Err(e)) => {
+                    return Some((Err(e), self));
+                }
+                None => {
+                    self.events = None;
+                }
+            }
+        }
+    }
+
+    /// Yields the text received so far, if it changed since the last snapshot.
+    fn snapshot(mut self) -> Option<(Result<String>, Self)> {
+        if !self.dirty {
+            return None;
+        }
+        self.dirty = false;
+        self.next_at = Instant::now() + self.interval;
+        Some((Ok(self.text.clone()), self))
+    }
+}
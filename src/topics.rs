@@ -0,0 +1,186 @@
+//! Offline analysis of what conversations are about, grouping them by the words they use.
+//!
+//! `TopicReport::build` clusters archived conversations without sending anything: each conversation is
+//! described by the distinctive words of its name and messages, and conversations sharing enough of them form
+//! a topic.
+
+use chrono::{ DateTime, Utc };
+use serde::Serialize;
+use std::{ cmp::Reverse, collections::HashMap, fmt };
+
+use crate::{ similarity::word_counts, snapshot::ConversationSnapshot };
+
+/// Number of words listed for each topic.
+const TOP_TERMS: usize = 5;
+/// Common English words that say nothing about the topic of a conversation.
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "also", "and", "any", "are", "because", "been", "but", "can", "could", "did", "does", "for",
+    "from", "get", "had", "has", "have", "help", "here", "how", "into", "its", "just", "like", "make", "may", "more",
+    "most", "need", "not", "now", "only", "other", "our", "out", "please", "should", "some", "such", "than", "thank",
+    "thanks", "that", "the", "their", "them", "then", "there", "these", "they", "this", "those", "use", "using",
+    "very", "want", "was", "were", "what", "when", "where", "which", "while", "who", "why", "will", "with", "would",
+    "you", "your",
+];
+
+/// Conversations grouped by topic, largest topic first.
+///
+/// Displays as a plain text report, one paragraph per topic.
+///
+/// # Examples
+///
+/// ```
+/// use claude::{ snapshot::ConversationSnapshot, topics::TopicReport, ChatMessage, Conversation };
+///
+/// let snapshot = |uuid: &str, text: &str| {
+///     let conversation: Conversation = serde_json::from_value(serde_json::json!({
+///         "uuid": uuid, "name": "", "summary": "",
+///     })).unwrap();
+///     let message: ChatMessage = serde_json::from_value(serde_json::json!({
+///         "uuid": format!("{}-0", uuid), "index": 0, "sender": "human", "text": text, "attachments": [],
+///     })).unwrap();
+///     ConversationSnapshot::new(conversation, vec![message])
+/// };
+/// let snapshots = [
+///     snapshot("a", "Why does the borrow checker reject this Rust closure?"),
+///     snapshot("b", "Explain Rust lifetimes and the borrow checker"),
+///     snapshot("c", "A sourdough bread recipe with a long fermentation"),
+/// ];
+/// let report = TopicReport::build(&snapshots, 0.2);
+///
+/// assert_eq!(report.topics.len(), 2);
+/// assert_eq!(report.topics[0].conversations, ["a", "b"]);
+/// assert!(report.topics[0].top_terms.contains(&"borrow".to_string()));
+/// println!("{}", report);
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TopicReport {
+    pub topics: Vec<Topic>,
+}
+
+/// Conversations about the same topic.
+#[derive(Debug, Clone, Serialize)]
+pub struct Topic {
+    /// The most distinctive words of the topic, most distinctive first.
+    pub top_terms: Vec<String>,
+    /// The UUIDs of the conversations, oldest first.
+    pub conversations: Vec<String>,
+    /// When the oldest conversation was created, if the API said so.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first: Option<DateTime<Utc>>,
+    /// When the most recent conversation was last updated, if the API said so.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last: Option<DateTime<Utc>>,
+}
+
+impl TopicReport {
+    /// Groups conversations by topic.
+    ///
+    /// Each conversation is described by the words of its name and messages, weighted by how rare they are
+    /// across `snapshots` (TF-IDF). Conversations are taken oldest first and join the topic they are most
+    /// similar to, if the cosine similarity is at least `threshold`, or start a topic of their own. Thresholds
+    /// around 0.2 give broad topics, and higher ones narrower topics.
+    pub fn build<'a>(snapshots: impl IntoIterator<Item = &'a ConversationSnapshot>, threshold: f64) -> Self {
+        let mut snapshots: Vec<_> = snapshots.into_iter().collect();
+        snapshots.sort_by_key(|snapshot| created_at(snapshot));
+
+        let counts: Vec<HashMap<String, usize>> = snapshots.iter().map(|snapshot| terms(snapshot)).collect();
+        let mut containing: HashMap<&str, usize> = HashMap::new();
+        for term in counts.iter().flat_map(|counts| counts.keys()) {
+            *containing.entry(term.as_str()).or_default() += 1;
+        }
+        let total = counts.len() as f64;
+        let vectors: Vec<HashMap<&str, f64>> = counts
+            .iter()
+            .map(|counts| {
+                counts
+                    .iter()
+                    .map(|(term, count)| {
+                        let idf = (1.0 + total / containing[term.as_str()] as f64).ln();
+                        (term.as_str(), (1.0 + *count as f64).ln() * idf)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Each cluster keeps the sum of the vectors of its conversations, whose direction is their centroid.
+        let mut clusters: Vec<(HashMap<&str, f64>, Vec<usize>)> = vec![];
+        for (i, vector) in vectors.iter().enumerate() {
+            let best = clusters
+                .iter()
+                .enumerate()
+                .map(|(c, (centroid, _))| (c, cosine(vector, centroid)))
+                .filter(|(_, similarity)| *similarity >= threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            match best {
+                Some((c, _)) => {
+                    let (centroid, members) = &mut clusters[c];
+                    for (term, weight) in vector {
+                        *centroid.entry(term).or_default() += weight;
+                    }
+                    members.push(i);
+                }
+                None => clusters.push((vector.clone(), vec![i])),
+            }
+        }
+
+        let mut topics: Vec<Topic> = clusters
+            .into_iter()
+            .map(|(centroid, members)| {
+                let mut terms: Vec<_> = centroid.into_iter().collect();
+                terms.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+                let members: Vec<_> = members.into_iter().map(|i| snapshots[i]).collect();
+                Topic {
+                    top_terms: terms.into_iter().take(TOP_TERMS).map(|(term, _)| term.to_string()).collect(),
+                    conversations: members.iter().map(|snapshot| snapshot.conversation().uuid.clone()).collect(),
+                    first: members.iter().filter_map(|snapshot| created_at(snapshot)).min(),
+                    last: members.iter().filter_map(|snapshot| updated_at(snapshot)).max(),
+                }
+            })
+            .collect();
+        topics.sort_by_key(|topic| Reverse(topic.conversations.len()));
+        Self { topics }
+    }
+}
+
+impl fmt::Display for TopicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let date = |time: Option<DateTime<Utc>>| time.map_or("?".to_string(), |time| time.format("%Y-%m-%d").to_string());
+        for (i, topic) in self.topics.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}. {}", i + 1, topic.top_terms.join(", "))?;
+            writeln!(f, "   {} conversations, {} to {}", topic.conversations.len(), date(topic.first), date(topic.last))?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts the words of the name and messages of a conversation that may tell its topic.
+fn terms(snapshot: &ConversationSnapshot) -> HashMap<String, usize> {
+    let mut counts = word_counts(&snapshot.conversation().name);
+    for message in snapshot.messages() {
+        for (word, count) in word_counts(&message.text) {
+            *counts.entry(word).or_default() += count;
+        }
+    }
+    counts.retain(|word, _| {
+        word.chars().count() > 2 && !word.chars().all(|c| c.is_numeric()) && !STOP_WORDS.contains(&word.as_str())
+    });
+    counts
+}
+
+fn cosine(a: &HashMap<&str, f64>, b: &HashMap<&str, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(term, weight)| Some(weight * b.get(term)?)).sum();
+    let norm = |v: &HashMap<&str, f64>| v.values().map(|weight| weight * weight).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+fn created_at(snapshot: &ConversationSnapshot) -> Option<DateTime<Utc>> {
+    snapshot.conversation().created_at.or_else(|| snapshot.messages().first()?.created_at)
+}
+
+fn updated_at(snapshot: &ConversationSnapshot) -> Option<DateTime<Utc>> {
+    snapshot.conversation().updated_at.or_else(|| snapshot.messages().last()?.created_at).or(created_at(snapshot))
+}
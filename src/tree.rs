@@ -0,0 +1,211 @@
+use serde::Deserialize;
+use tracing::info;
+
+use crate::{ ChatMessage, Client, Context, Result };
+
+/// All the messages of a conversation, including the branches left behind by edits and retries.
+///
+/// Each message links to the one it follows with `parent_message_uuid`, so the messages form a tree. The
+/// branch shown in the web UI is the path from the root to the current leaf.
+///
+/// # Examples
+///
+/// ```
+/// use claude::{ tree::ConversationTree, ChatMessage };
+///
+/// let message = |uuid: &str, parent: &str, index: usize| -> ChatMessage {
+///     let sender = if index % 2 == 0 { "human" } else { "assistant" };
+///     serde_json::from_value(serde_json::json!({
+///         "uuid": uuid, "parent_message_uuid": parent, "index": index,
+///         "sender": sender, "text": "", "attachments": [],
+///     })).unwrap()
+/// };
+/// let root = "00000000-0000-4000-8000-000000000000";
+/// // The question was asked once and answered twice, the second answer being a retry.
+/// let messages = vec![message("q", root, 0), message("a1", "q", 1), message("a2", "q", 1)];
+/// let tree = ConversationTree::new(messages, Some("a2".to_string()));
+///
+/// assert_eq!(tree.children("q").len(), 2);
+/// assert_eq!(tree.leaves().len(), 2);
+/// let current: Vec<_> = tree.current_branch().iter().map(|message| message.uuid.as_str()).collect();
+/// assert_eq!(current, ["q", "a2"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConversationTree {
+    /// The messages of every branch, in index order.
+    pub messages: Vec<ChatMessage>,
+    /// The last message of the branch shown in the web UI, if the API says so.
+    pub current_leaf: Option<String>,
+}
+
+impl ConversationTree {
+    /// Returns the tree of `messages`, whose current branch ends at `current_leaf`.
+    pub fn new(mut messages: Vec<ChatMessage>, current_leaf: Option<String>) -> Self {
+        messages.sort_by_key(|message| message.index);
+        Self { messages, current_leaf }
+    }
+
+    /// Returns the message with the UUID `uuid`.
+    pub fn get(&self, uuid: &str) -> Option<&ChatMessage> {
+        self.messages.iter().find(|message| message.uuid == uuid)
+    }
+
+    /// Returns the message `message` follows, or `None` for the first message of the conversation.
+    pub fn parent(&self, message: &ChatMessage) -> Option<&ChatMessage> {
+        self.get(message.parent_message_uuid.as_deref()?)
+    }
+
+    /// Returns the messages following the message with the UUID `uuid`, one per branch.
+    pub fn children(&self, uuid: &str) -> Vec<&ChatMessage> {
+        self.messages
+            .iter()
+            .filter(|message| message.parent_message_uuid.as_deref() == Some(uuid))
+            .collect()
+    }
+
+    /// Returns the first messages of the conversation, several when the first message was edited.
+    pub fn roots(&self) -> Vec<&ChatMessage> {
+        self.messages
+            .iter()
+            .filter(|message| self.parent(message).is_none())
+            .collect()
+    }
+
+    /// Returns the alternatives to the message with the UUID `uuid`, itself included: the messages following
+    /// the same parent.
+    pub fn siblings(&self, uuid: &str) -> Vec<&ChatMessage> {
+        match self.get(uuid) {
+            Some(message) if self.parent(message).is_some() => {
+                self.children(message.parent_message_uuid.as_deref().unwrap_or_default())
+            }
+            Some(_) => self.roots(),
+            None => vec![],
+        }
+    }
+
+    /// Returns the last message of every branch.
+    pub fn leaves(&self) -> Vec<&ChatMessage> {
+        self.messages
+            .iter()
+            .filter(|message| self.children(&message.uuid).is_empty())
+            .collect()
+    }
+
+    /// Returns the messages from the first one to the message with the UUID `leaf`, or nothing if there is no
+    /// such message.
+    pub fn branch(&self, leaf: &str) -> Vec<&ChatMessage> {
+        let mut branch: Vec<_> = std::iter
+            ::successors(self.get(leaf), |message| self.parent(message))
+            .take(self.messages.len())
+            .collect();
+        branch.reverse();
+        branch
+    }
+
+    /// Returns the branch shown in the web UI, or the branch of the most recent message when the current leaf
+    /// is unknown.
+    pub fn current_branch(&self) -> Vec<&ChatMessage> {
+        let leaf = match &self.current_leaf {
+            Some(leaf) => leaf.as_str(),
+            None => {
+                match self.messages.iter().max_by_key(|message| (message.index, message.created_at)) {
+                    Some(message) => &message.uuid,
+                    None => {
+                        return vec![];
+                    }
+                }
+            }
+        };
+        self.branch(leaf)
+    }
+
+    /// Returns the last message of the most recent branch going through the message with the UUID `uuid`, the
+    /// leaf to pass to `Client::set_current_leaf` to show that message.
+    pub fn latest_leaf(&self, uuid: &str) -> Option<&ChatMessage> {
+        std::iter
+            ::successors(self.get(uuid), |message| {
+                self.children(&message.uuid).into_iter().max_by_key(|child| child.created_at)
+            })
+            .take(self.messages.len())
+            .last()
+    }
+}
+
+impl Client {
+    /// Retrieves every message of a chat conversation, including the branches left behind by edits and retries.
+    ///
+    /// `chat_conversation_history` only returns the messages of the current branch.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or if the response cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) -> claude::Result<()> {
+    /// let tree = client.conversation_tree("chat_uuid").await?;
+    /// for leaf in tree.leaves() {
+    ///     println!("branch of {} messages ending with: {}", tree.branch(&leaf.uuid).len(), leaf.text);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn conversation_tree(&self, chat_uuid: &str) -> Result<ConversationTree> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}?tree=True&rendering_mode=messages",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+
+        #[derive(Deserialize, Debug)]
+        struct Tree {
+            chat_messages: Vec<ChatMessage>,
+            #[serde(default)]
+            current_leaf_message_uuid: Option<String>,
+        }
+
+        let request = self.http.get(url);
+        let context = || format!("while fetching the message tree of conversation {}", chat_uuid);
+        let res: Tree = self.dispatch("conversation_tree", Some(chat_uuid), request)
+            .await
+            .with_context(context)?
+            .json().await
+            .with_context(context)?;
+
+        self.log_body("conversation_tree", Some(chat_uuid), &res);
+
+        Ok(ConversationTree::new(res.chat_messages, res.current_leaf_message_uuid))
+    }
+
+    /// Switches the branch of a chat conversation shown in the web UI and continued by new messages, to the
+    /// branch ending with the message `leaf_uuid`.
+    ///
+    /// Use `ConversationTree::latest_leaf` to find the leaf of the branch going through a given message.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails.
+    pub async fn set_current_leaf(&self, chat_uuid: &str, leaf_uuid: &str) -> Result<()> {
+        let url = format!(
+            "{}/api/organizations/{}/chat_conversations/{}/current_leaf_message_uuid",
+            self.base_url,
+            self.org_uuid,
+            chat_uuid
+        );
+
+        let payload = serde_json::json!({
+            "current_leaf_message_uuid": leaf_uuid,
+        });
+
+        let request = self.http.put(url).json(&payload);
+        self.dispatch("set_current_leaf", Some(chat_uuid), request)
+            .await
+            .with_context(|| format!("while switching the branch of conversation {}", chat_uuid))?;
+        info!(target: "claude::audit", event = "conversation.branch_switched", conversation_id = chat_uuid);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use serde::Serialize;
+use std::{ collections::VecDeque, iter, ops::Range };
+
+#[cfg(feature = "attachments")]
+use crate::{ Error, Result, UploadedAttachment };
+
+/// Marker inserted where `TruncationPolicy::Sample` dropped sections.
+const OMISSION_MARKER: &str = "\n\n[...]\n\n";
+
+/// What to do with attachments whose extracted text exceeds `Client::attachment_budget`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncationPolicy {
+    /// Refuses to send the message with `Error::AttachmentTooLarge`.
+    #[default]
+    Error,
+    /// Drops the beginning of the text, keeping its end.
+    TruncateHead,
+    /// Drops the end of the text, keeping its beginning.
+    TruncateTail,
+    /// Keeps the first and last sections (separated by blank lines) and sections sampled evenly in between,
+    /// marking each gap with `[...]`.
+    Sample,
+}
+
+/// What was dropped from an attachment to fit the budget.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TruncationReport {
+    pub file_name: String,
+    /// Length of the extracted text before truncation, in characters.
+    pub original_chars: usize,
+    /// Number of characters of the original text that were kept.
+    pub kept_chars: usize,
+    /// Character ranges of the original text that were dropped, in order.
+    pub dropped: Vec<Range<usize>>,
+}
+
+/// Truncates `text` to at most `budget` characters according to `policy`.
+///
+/// Returns the truncated text and the character ranges of `text` that were dropped, which are empty when the
+/// text already fits. `TruncationPolicy::Error` only applies to attachments and is treated as `TruncateTail`.
+///
+/// # Examples
+///
+/// ```
+/// use claude::truncation::{ truncate, TruncationPolicy };
+/// let (kept, dropped) = truncate("abcdef", 4, TruncationPolicy::TruncateHead);
+/// assert_eq!(kept, "cdef");
+/// assert_eq!(dropped, vec![0..2]);
+/// ```
+pub fn truncate(text: &str, budget: usize, policy: TruncationPolicy) -> (String, Vec<Range<usize>>) {
+    let len = text.chars().count();
+    if len <= budget {
+        return (text.to_string(), vec![]);
+    }
+    // A budget too small for a single marker leaves nothing to sample with.
+    let policy = match policy {
+        TruncationPolicy::Sample if budget < OMISSION_MARKER.chars().count() => TruncationPolicy::TruncateTail,
+        policy => policy,
+    };
+    let kept = match policy {
+        TruncationPolicy::TruncateHead => iter::once(len - budget..len).collect(),
+        TruncationPolicy::Error | TruncationPolicy::TruncateTail => iter::once(0..budget).collect(),
+        TruncationPolicy::Sample => sample(text, budget),
+    };
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut truncated = String::new();
+    let mut dropped = vec![];
+    let mut end = 0;
+    for range in &kept {
+        if range.start > end {
+            dropped.push(end..range.start);
+            if policy == TruncationPolicy::Sample {
+                truncated.push_str(OMISSION_MARKER);
+            }
+        }
+        truncated.extend(&chars[range.clone()]);
+        end = range.end;
+    }
+    if end < len {
+        dropped.push(end..len);
+        if policy == TruncationPolicy::Sample {
+            truncated.push_str(OMISSION_MARKER);
+        }
+    }
+    (truncated, dropped)
+}
+
+/// Returns the character ranges of the sections kept by `TruncationPolicy::Sample`, in order.
+fn sample(text: &str, budget: usize) -> Vec<Range<usize>> {
+    let sections = sections(text);
+    let marker = OMISSION_MARKER.chars().count();
+    let first = sections[0].clone();
+    if first.len() + marker > budget {
+        return iter::once(0..budget - marker).collect();
+    }
+
+    // The first and last sections come first, then the middle ones in an order spreading them evenly.
+    let mut order = vec![0, sections.len() - 1];
+    let mut intervals = VecDeque::from([(1, sections.len() - 1)]);
+    while let Some((low, high)) = intervals.pop_front() {
+        if low < high {
+            let middle = (low + high) / 2;
+            order.push(middle);
+            intervals.push_back((low, middle));
+            intervals.push_back((middle + 1, high));
+        }
+    }
+
+    let mut kept = vec![false; sections.len()];
+    let mut used = 0;
+    for index in order {
+        // Each kept section may open a new gap, whose marker must fit too.
+        let cost = sections[index].len() + marker;
+        if !kept[index] && used + cost <= budget {
+            kept[index] = true;
+            used += cost;
+        }
+    }
+    sections
+        .into_iter()
+        .zip(kept)
+        .filter_map(|(section, kept)| kept.then_some(section))
+        .collect()
+}
+
+/// Splits `text` into sections ending after each blank line, as character ranges.
+fn sections(text: &str) -> Vec<Range<usize>> {
+    let mut sections = vec![];
+    let mut start = 0;
+    let mut previous = None;
+    for (index, c) in text.chars().enumerate() {
+        if c == '\n' && previous == Some('\n') {
+            sections.push(start..index + 1);
+            start = index + 1;
+        }
+        previous = Some(c);
+    }
+    let len = text.chars().count();
+    if start < len || sections.is_empty() {
+        sections.push(start..len);
+    }
+    sections
+}
+
+/// Splits `budget` between texts of the given lengths, giving short texts all they need and sharing the rest
+/// equally between the longer ones.
+#[cfg(feature = "attachments")]
+fn allocate(lengths: &[usize], budget: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..lengths.len()).collect();
+    order.sort_by_key(|&index| lengths[index]);
+    let mut shares = vec![0; lengths.len()];
+    let mut remaining = budget;
+    for (position, index) in order.into_iter().enumerate() {
+        let share = lengths[index].min(remaining / (lengths.len() - position));
+        shares[index] = share;
+        remaining -= share;
+    }
+    shares
+}
+
+/// Fits the extracted text of converted documents into `budget` characters in total, truncating them in place
+/// according to `policy`.
+#[cfg(feature = "attachments")]
+pub(crate) fn fit_documents(
+    documents: &mut [UploadedAttachment],
+    budget: usize,
+    policy: TruncationPolicy
+) -> Result<Vec<TruncationReport>> {
+    let lengths: Vec<usize> = documents
+        .iter()
+        .map(|document| document.extracted_content.chars().count())
+        .collect();
+    if lengths.iter().sum::<usize>() <= budget {
+        return Ok(vec![]);
+    }
+
+    let mut reports = vec![];
+    for ((document, len), share) in documents.iter_mut().zip(&lengths).zip(allocate(&lengths, budget)) {
+        if *len <= share {
+            continue;
+        }
+        let file_name = document.file_name.clone();
+        if policy == TruncationPolicy::Error {
+            return Err(Error::AttachmentTooLarge { file_name, chars: *len, budget: share });
+        }
+        let (truncated, dropped) = truncate(&document.extracted_content, share, policy);
+        let dropped_chars: usize = dropped.iter().map(|range| range.len()).sum();
+        document.extracted_content = truncated;
+        reports.push(TruncationReport { file_name, original_chars: *len, kept_chars: len - dropped_chars, dropped });
+    }
+    Ok(reports)
+}
@@ -0,0 +1,413 @@
+use sha2::{ Digest, Sha256 };
+use tokio::{ fs::File, io::AsyncReadExt };
+use chrono::{ DateTime, TimeZone };
+use std::{ borrow::Cow, path::Path };
+
+use crate::{ ChatMessage, Result };
+
+/// Computes the hex-encoded SHA-256 digest of a byte slice.
+///
+/// # Examples
+///
+/// ```
+/// let hash = claude::utils::sha256_hex(b"hello");
+/// assert_eq!(hash, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+/// ```
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Computes the hex-encoded SHA-256 digest of a file, reading it in chunks so large documents
+/// are never held in memory at once.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be opened or read.
+pub async fn hash_file(file_path: impl AsRef<Path>) -> Result<String> {
+    let mut file = File::open(file_path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Computes a checksum of the contents of a conversation, to tell whether two copies of it have diverged.
+///
+/// The checksum covers, for every message in index order, its UUID, index, sender and text, and the ID, name,
+/// type and contents of its attachments. Feedback and timestamps are left out, since they change without the
+/// conversation itself changing. Every field is length-prefixed, so moving text from one field or message to
+/// the next changes the checksum.
+///
+/// # Examples
+///
+/// ```
+/// use claude::{ utils::conversation_checksum, ChatMessage };
+///
+/// let messages: Vec<ChatMessage> = serde_json::from_str(r#"[
+///     { "uuid": "m-2", "attachments": [], "sender": "assistant", "index": 1, "text": "Hi!" },
+///     { "uuid": "m-1", "attachments": [], "sender": "human", "index": 0, "text": "Hello" }
+/// ]"#).unwrap();
+/// let mut reversed = messages.clone();
+/// reversed.reverse();
+/// assert_eq!(conversation_checksum(&messages), conversation_checksum(&reversed));
+/// ```
+pub fn conversation_checksum(messages: &[ChatMessage]) -> String {
+    fn field(hasher: &mut Sha256, bytes: &[u8]) {
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(bytes);
+    }
+
+    let mut messages: Vec<&ChatMessage> = messages.iter().collect();
+    messages.sort_by(|a, b| a.index.cmp(&b.index).then_with(|| a.uuid.cmp(&b.uuid)));
+    let mut hasher = Sha256::new();
+    hasher.update((messages.len() as u64).to_le_bytes());
+    for message in messages {
+        field(&mut hasher, message.uuid.as_bytes());
+        hasher.update((message.index as u64).to_le_bytes());
+        field(&mut hasher, message.sender.as_str().as_bytes());
+        field(&mut hasher, message.text.as_bytes());
+        hasher.update((message.attachments.len() as u64).to_le_bytes());
+        for attachment in &message.attachments {
+            field(&mut hasher, attachment.id.as_bytes());
+            field(&mut hasher, attachment.file_name.as_bytes());
+            field(&mut hasher, attachment.file_type.as_bytes());
+            field(&mut hasher, attachment.extracted_content.as_bytes());
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Returns the size of a file, in bytes.
+///
+/// # Errors
+///
+/// This function will return an error if the metadata of the file cannot be read.
+pub async fn get_file_size(file_path: impl AsRef<Path>) -> Result<u64> {
+    Ok(tokio::fs::metadata(file_path).await?.len())
+}
+
+/// Splits a text into parts of at most `max_bytes` bytes each, cutting between lines whenever possible.
+///
+/// When `repeat_header` is set, the first line, such as the header row of a CSV file, starts every part, so
+/// each part can be read on its own. Lines longer than a part are cut between characters.
+///
+/// # Examples
+///
+/// ```
+/// use claude::utils::split_text;
+///
+/// let csv = "name,age\nAda,36\nAlan,41\nGrace,85\n";
+/// assert_eq!(split_text(csv, 20, true), ["name,age\nAda,36\n", "name,age\nAlan,41\n", "name,age\nGrace,85\n"]);
+/// assert_eq!(split_text("abcdef", 4, false), ["abcd", "ef"]);
+/// ```
+pub fn split_text(text: &str, max_bytes: usize, repeat_header: bool) -> Vec<String> {
+    let max_bytes = max_bytes.max(1);
+    if text.len() <= max_bytes {
+        return vec![text.to_string()];
+    }
+    let mut lines = text.split_inclusive('\n');
+    // A header leaving no room for the rows is not repeated.
+    let header = match lines.clone().next() {
+        Some(header) if repeat_header && header.len() < max_bytes / 2 => {
+            lines.next();
+            header
+        }
+        _ => "",
+    };
+
+    let mut parts = vec![];
+    let mut part = String::from(header);
+    for mut line in lines {
+        while !line.is_empty() {
+            let room = max_bytes - part.len();
+            if line.len() <= room {
+                part.push_str(line);
+                break;
+            }
+            if part.len() > header.len() && line.len() <= max_bytes - header.len() {
+                parts.push(std::mem::replace(&mut part, header.to_string()));
+                continue;
+            }
+            let mut end = room;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            if end == 0 {
+                if part.len() > header.len() {
+                    parts.push(std::mem::replace(&mut part, header.to_string()));
+                    continue;
+                }
+                // A character longer than a whole part is kept whole.
+                end = line.chars().next().map_or(0, char::len_utf8);
+            }
+            part.push_str(&line[..end]);
+            line = &line[end..];
+            parts.push(std::mem::replace(&mut part, header.to_string()));
+        }
+    }
+    if part.len() > header.len() {
+        parts.push(part);
+    }
+    parts
+}
+
+/// Estimates the number of tokens of a text.
+///
+/// Claude's tokenizer is not public, so this counts about four ASCII characters per token and one token per
+/// other character, which tends to overestimate less than it underestimates. It is meant to catch prompts that
+/// are far too long before sending them, not to measure them exactly.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(claude::utils::estimate_tokens("Hello, world"), 3);
+/// assert_eq!(claude::utils::estimate_tokens("こんにちは"), 5);
+/// ```
+pub fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.bytes().filter(u8::is_ascii).count();
+    let other = text.chars().count() - ascii;
+    ascii.div_ceil(4) + other
+}
+
+/// Fills a conversation title template.
+///
+/// `{date}` is replaced with the date of `now` as `YYYY-MM-DD`, `{time}` with its time as `HH:MM:SS`, and
+/// `{n}` with the sequence number `n`. Other text is kept as is.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{ TimeZone, Utc };
+/// use claude::utils::render_title;
+///
+/// let now = Utc.with_ymd_and_hms(2023, 11, 2, 9, 30, 0).unwrap();
+/// assert_eq!(render_title("batch {date} #{n}", 7, &now), "batch 2023-11-02 #7");
+/// assert_eq!(render_title("{date} {time}", 1, &now), "2023-11-02 09:30:00");
+/// ```
+pub fn render_title<Tz>(template: &str, n: u64, now: &DateTime<Tz>) -> String
+    where Tz: TimeZone, Tz::Offset: std::fmt::Display
+{
+    template
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H:%M:%S").to_string())
+        .replace("{n}", &n.to_string())
+}
+
+/// Returns the MIME type of a file from its extension, matched case-insensitively, if it is a known one.
+///
+/// Source code and configuration files are sent as `text/plain`, which is how claude.ai reads them.
+///
+/// # Examples
+///
+/// ```
+/// use claude::utils::get_content_type;
+/// use std::path::Path;
+///
+/// assert_eq!(get_content_type(Path::new("report.DOCX")), Some(
+///     "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+/// ));
+/// assert_eq!(get_content_type(Path::new("main.rs")), Some("text/plain"));
+/// assert_eq!(get_content_type(Path::new("Makefile")), None);
+/// ```
+pub fn get_content_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let content_type = match extension.as_str() {
+        "pdf" => "application/pdf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "rtf" => "application/rtf",
+        "epub" => "application/epub+zip",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "html" | "htm" => "text/html",
+        "md" | "markdown" => "text/markdown",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        | "txt"
+        | "log"
+        | "rs"
+        | "py"
+        | "js"
+        | "ts"
+        | "jsx"
+        | "tsx"
+        | "java"
+        | "kt"
+        | "c"
+        | "h"
+        | "cpp"
+        | "hpp"
+        | "cs"
+        | "go"
+        | "rb"
+        | "php"
+        | "swift"
+        | "sh"
+        | "sql"
+        | "css"
+        | "toml"
+        | "yaml"
+        | "yml"
+        | "ini" => "text/plain",
+        _ => {
+            return None;
+        }
+    };
+    Some(content_type)
+}
+
+/// Guesses the MIME type of a file from its first bytes, for files without a known extension.
+///
+/// Recognizes PDF, ZIP-based office documents and EPUB, legacy Office documents, RTF and common images, and
+/// falls back to `text/plain` for valid UTF-8 without control bytes. A prefix of a few kilobytes is enough.
+///
+/// # Examples
+///
+/// ```
+/// use claude::utils::sniff_content_type;
+///
+/// assert_eq!(sniff_content_type(b"%PDF-1.7\n"), Some("application/pdf"));
+/// assert_eq!(sniff_content_type(b"Plain notes\n"), Some("text/plain"));
+/// assert_eq!(sniff_content_type(&[0, 159, 146, 150]), None);
+/// ```
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|window| window == needle);
+    let content_type = match bytes {
+        [b'%', b'P', b'D', b'F', ..] => "application/pdf",
+        [b'{', b'\\', b'r', b't', b'f', ..] => "application/rtf",
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        [0xd0, 0xcf, 0x11, 0xe0, 0xa1, 0xb1, 0x1a, 0xe1, ..] => "application/msword",
+        [b'P', b'K', 3, 4, ..] if contains(b"mimetypeapplication/epub+zip") => "application/epub+zip",
+        [b'P', b'K', 3, 4, ..] if contains(b"word/") => {
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        }
+        [b'P', b'K', 3, 4, ..] if contains(b"xl/") => {
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        }
+        [b'P', b'K', 3, 4, ..] if contains(b"ppt/") => {
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+        }
+        [b'P', b'K', 3, 4, ..] => "application/zip",
+        _ => {
+            // The prefix may end in the middle of a character.
+            let text = match std::str::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(e) if e.error_len().is_none() => std::str::from_utf8(&bytes[..e.valid_up_to()]).ok()?,
+                Err(_) => {
+                    return None;
+                }
+            };
+            if text.chars().any(|c| c.is_control() && !c.is_whitespace()) {
+                return None;
+            }
+            "text/plain"
+        }
+    };
+    Some(content_type)
+}
+
+/// Maximum length, in bytes, of a file name sent to the API. Most file systems share this limit.
+const MAX_FILE_NAME_LEN: usize = 255;
+
+/// Normalizes a file name or path into the name sent to the backend with an attachment.
+///
+/// Both `/` and `\` are treated as separators, so Windows paths never leak their directories even when
+/// handled on another platform, and verbatim/UNC prefixes such as `\\?\` are ignored. Characters that
+/// Windows rejects in file names are replaced by `_`, trailing dots and spaces are dropped, and names longer
+/// than 255 bytes are shortened while keeping their extension.
+///
+/// # Examples
+///
+/// ```
+/// use claude::utils::normalize_file_name;
+///
+/// assert_eq!(normalize_file_name(r"C:\Users\me\Documents\cv.pdf"), "cv.pdf");
+/// assert_eq!(normalize_file_name(r"\\?\UNC\server\share\report.docx"), "report.docx");
+/// assert_eq!(normalize_file_name("notes: draft?.txt"), "notes_ draft_.txt");
+/// assert_eq!(normalize_file_name(&format!("{}.md", "a".repeat(300))).len(), 255);
+/// assert_eq!(normalize_file_name(r"C:\"), "attachment");
+/// ```
+pub fn normalize_file_name(path: &str) -> String {
+    let name = path
+        .rsplit(['/', '\\'])
+        .find(|component| !component.is_empty())
+        .unwrap_or_default();
+
+    // A bare root leaves only a drive letter ("C:") or a verbatim prefix ("?", ".") behind.
+    let is_drive = name.len() == 2 && name.ends_with(':');
+    if is_drive || name == "?" || name == "." {
+        return "attachment".to_string();
+    }
+
+    let name: String = name
+        .chars()
+        .map(|c| {
+            match c {
+                '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            }
+        })
+        .collect();
+    let name = name.trim_end_matches(['.', ' ']).trim_start();
+
+    if name.is_empty() {
+        return "attachment".to_string();
+    }
+
+    truncate_file_name(name, MAX_FILE_NAME_LEN)
+}
+
+/// Shortens a file name to at most `max` bytes, preserving its extension when it is reasonably short.
+fn truncate_file_name(name: &str, max: usize) -> String {
+    if name.len() <= max {
+        return name.to_string();
+    }
+
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot > 0 && name.len() - dot <= 16 => name.split_at(dot),
+        _ => (name, ""),
+    };
+    let mut end = max - extension.len();
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &stem[..end], extension)
+}
+
+/// Returns a form of `path` that can be opened even when it exceeds the legacy `MAX_PATH` limit.
+///
+/// On Windows, long absolute paths are given the `\\?\` verbatim prefix. Elsewhere the path is returned as is.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    #[cfg(windows)]
+    {
+        const MAX_PATH: usize = 260;
+        let raw = path.as_os_str().to_string_lossy();
+        if path.is_absolute() && raw.len() >= MAX_PATH && !raw.starts_with(r"\\?\") {
+            let verbatim = match raw.strip_prefix(r"\\") {
+                Some(unc) => format!(r"\\?\UNC\{}", unc),
+                None => format!(r"\\?\{}", raw),
+            };
+            return Cow::Owned(verbatim.into());
+        }
+    }
+    Cow::Borrowed(path)
+}
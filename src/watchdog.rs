@@ -0,0 +1,293 @@
+//! Detection of changes to the undocumented claude.ai API, before they surface as deserialization errors.
+//!
+//! `Client::check_schema` fetches each endpoint the crate reads as raw JSON and compares its fields with the
+//! ones the crate knows. Fields are named by their path, such as `chat_messages[].sender`, where `[]` stands
+//! for every element of an array.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::{ collections::BTreeSet, fmt };
+use tracing::{ debug, warn };
+
+use crate::Client;
+
+/// The fields the crate reads from the response of an endpoint.
+#[derive(Debug, Clone, Copy)]
+pub struct Schema {
+    /// Fields the crate cannot do without: their absence makes requests to the endpoint fail.
+    pub required: &'static [&'static str],
+    /// Fields the crate reads when they are present.
+    pub optional: &'static [&'static str],
+}
+
+const ORGANIZATIONS: Schema = Schema { required: &["[].uuid", "[].name"], optional: &[] };
+
+const CONVERSATIONS: Schema = Schema {
+    required: &["[].uuid", "[].name"],
+    optional: &[
+        "[].summary",
+        "[].is_starred",
+        "[].project_uuid",
+        "[].created_at",
+        "[].updated_at",
+        "[].model",
+        "[].settings",
+    ],
+};
+
+const CONVERSATION: Schema = Schema {
+    required: &[
+        "uuid",
+        "name",
+        "chat_messages[].uuid",
+        "chat_messages[].attachments",
+        "chat_messages[].sender",
+        "chat_messages[].index",
+        "chat_messages[].text",
+        "chat_messages[].attachments[].id",
+        "chat_messages[].attachments[].extracted_content",
+        "chat_messages[].attachments[].file_name",
+        "chat_messages[].attachments[].file_size",
+        "chat_messages[].attachments[].file_type",
+    ],
+    optional: &[
+        "summary",
+        "is_starred",
+        "project_uuid",
+        "created_at",
+        "updated_at",
+        "model",
+        "settings",
+        "current_leaf_message_uuid",
+        "chat_messages",
+        "chat_messages[].chat_feedback",
+        "chat_messages[].created_at",
+        "chat_messages[].updated_at",
+        "chat_messages[].parent_message_uuid",
+    ],
+};
+
+const ACCOUNT: Schema = Schema {
+    required: &["uuid", "email_address", "memberships[].organization", "memberships[].organization.uuid"],
+    optional: &[
+        "full_name",
+        "display_name",
+        "memberships",
+        "memberships[].organization.capabilities",
+        "memberships[].organization.rate_limit_tier",
+    ],
+};
+
+const USAGE: Schema = Schema {
+    required: &[],
+    optional: &[
+        "five_hour",
+        "five_hour.utilization",
+        "five_hour.resets_at",
+        "seven_day",
+        "seven_day.utilization",
+        "seven_day.resets_at",
+        "seven_day_opus",
+        "seven_day_opus.utilization",
+        "seven_day_opus.resets_at",
+    ],
+};
+
+/// The differences between the responses of the endpoints and the fields the crate knows.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaReport {
+    pub endpoints: Vec<EndpointReport>,
+}
+
+/// The differences between the response of one endpoint and the fields the crate knows.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EndpointReport {
+    /// The name of the endpoint, such as `conversation`.
+    pub endpoint: String,
+    /// Fields of the response the crate does not read, possibly added by claude.ai.
+    pub unknown: Vec<String>,
+    /// Fields the crate requires that the response lacks, which break the endpoint.
+    pub missing: Vec<String>,
+    /// Why the endpoint could not be fetched, if it could not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl EndpointReport {
+    /// Compares a response of the endpoint `endpoint` with the fields of `schema`.
+    ///
+    /// Only the fields of objects the crate reads are compared, so the contents of an unknown object are not
+    /// listed field by field. Required fields are only missing from objects that are present: an empty array
+    /// of messages lacks nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use claude::watchdog::{ EndpointReport, Schema };
+    ///
+    /// let schema = Schema { required: &["uuid", "items[].id"], optional: &["items", "name"] };
+    /// let response = serde_json::json!({ "uuid": "1", "title": "renamed", "items": [{ "key": 1 }] });
+    /// let report = EndpointReport::compare("example", &response, &schema);
+    /// assert_eq!(report.unknown, ["items[].key", "title"]);
+    /// assert_eq!(report.missing, ["items[].id"]);
+    /// ```
+    pub fn compare(endpoint: &str, response: &Value, schema: &Schema) -> Self {
+        let known: BTreeSet<&str> = schema.required.iter().chain(schema.optional).copied().collect();
+        let mut present = BTreeSet::new();
+        collect_paths(response, "", &known, &mut present);
+
+        let unknown = present
+            .iter()
+            .filter(|path| !path.ends_with("[]") && !known.contains(path.as_str()))
+            .cloned()
+            .collect();
+        let missing = schema.required
+            .iter()
+            .filter(|path| !present.contains(**path) && (parent(path).is_empty() || present.contains(parent(path))))
+            .map(|path| path.to_string())
+            .collect();
+        Self { endpoint: endpoint.to_string(), unknown, missing, error: None }
+    }
+
+    /// Returns whether the response has no field the crate does not know and lacks none it requires.
+    pub fn is_clean(&self) -> bool {
+        self.unknown.is_empty() && self.missing.is_empty() && self.error.is_none()
+    }
+}
+
+impl SchemaReport {
+    /// Returns whether no endpoint has changed.
+    pub fn is_clean(&self) -> bool {
+        self.endpoints.iter().all(EndpointReport::is_clean)
+    }
+
+    /// Returns whether an endpoint lacks a required field or failed, so that the crate cannot use it.
+    pub fn is_breaking(&self) -> bool {
+        self.endpoints.iter().any(|endpoint| !endpoint.missing.is_empty() || endpoint.error.is_some())
+    }
+}
+
+impl fmt::Display for SchemaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for endpoint in &self.endpoints {
+            if endpoint.is_clean() {
+                writeln!(f, "{}: ok", endpoint.endpoint)?;
+                continue;
+            }
+            writeln!(f, "{}:", endpoint.endpoint)?;
+            if let Some(error) = &endpoint.error {
+                writeln!(f, "  error: {}", error)?;
+            }
+            for path in &endpoint.missing {
+                writeln!(f, "  missing: {}", path)?;
+            }
+            for path in &endpoint.unknown {
+                writeln!(f, "  unknown: {}", path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds the path of every field of `value` to `present`, descending only into the fields leading to `known`
+/// paths. The elements of arrays are marked with `[]`.
+fn collect_paths(value: &Value, prefix: &str, known: &BTreeSet<&str>, present: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                let leads_to_known = known
+                    .iter()
+                    .any(|known| known.starts_with(&format!("{}.", path)) || known.starts_with(&format!("{}[]", path)));
+                if leads_to_known {
+                    collect_paths(value, &path, known, present);
+                }
+                present.insert(path);
+            }
+        }
+        Value::Array(items) => {
+            let path = format!("{}[]", prefix);
+            for item in items {
+                collect_paths(item, &path, known, present);
+            }
+            if !items.is_empty() {
+                present.insert(path);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the path of the object or array holding the field at `path`, empty for top-level fields.
+fn parent(path: &str) -> &str {
+    match path.strip_suffix("[]") {
+        Some(array) => array,
+        None => path.rsplit_once('.').map_or("", |(parent, _)| parent),
+    }
+}
+
+impl Client {
+    /// Compares the responses of the endpoints the crate reads with the fields it knows.
+    ///
+    /// The organizations, the conversations, the account, the usage limits and one conversation with its
+    /// messages are fetched, the conversation being `chat_uuid` or else the most recent one. An endpoint that
+    /// cannot be fetched is reported with its error rather than failing the whole check. Changes are logged as
+    /// warnings.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use claude::Client;
+    /// # async fn example(client: Client) {
+    /// let report = client.check_schema(None).await;
+    /// print!("{}", report);
+    /// if report.is_breaking() {
+    ///     std::process::exit(1);
+    /// }
+    /// # }
+    /// ```
+    pub async fn check_schema(&self, chat_uuid: Option<&str>) -> SchemaReport {
+        let conversations_path = format!("/api/organizations/{}/chat_conversations", self.org_uuid);
+        let conversations = self.raw_get(&conversations_path).await;
+        let chat_uuid = match (chat_uuid, &conversations) {
+            (Some(chat_uuid), _) => Some(chat_uuid.to_string()),
+            (None, Ok(list)) => list.pointer("/0/uuid").and_then(Value::as_str).map(str::to_string),
+            (None, Err(_)) => None,
+        };
+
+        let mut endpoints = vec![
+            check("organizations", self.raw_get("/api/organizations").await, &ORGANIZATIONS),
+            check("conversations", conversations, &CONVERSATIONS),
+        ];
+        if let Some(chat_uuid) = chat_uuid {
+            let path = format!("{}/{}", conversations_path, chat_uuid);
+            endpoints.push(check("conversation", self.raw_get(&path).await, &CONVERSATION));
+        }
+        endpoints.push(check("account", self.raw_get("/api/account").await, &ACCOUNT));
+        let usage_path = format!("/api/organizations/{}/usage", self.org_uuid);
+        endpoints.push(check("usage", self.raw_get(&usage_path).await, &USAGE));
+
+        for endpoint in &endpoints {
+            if endpoint.is_clean() {
+                debug!(operation = "check_schema", endpoint = endpoint.endpoint, "schema unchanged");
+            } else {
+                warn!(
+                    operation = "check_schema",
+                    endpoint = endpoint.endpoint,
+                    unknown = ?endpoint.unknown,
+                    missing = ?endpoint.missing,
+                    error = endpoint.error,
+                    "schema changed"
+                );
+            }
+        }
+        SchemaReport { endpoints }
+    }
+}
+
+fn check(endpoint: &str, response: crate::Result<Value>, schema: &Schema) -> EndpointReport {
+    match response {
+        Ok(response) => EndpointReport::compare(endpoint, &response, schema),
+        Err(e) => EndpointReport { endpoint: endpoint.to_string(), error: Some(e.to_string()), ..EndpointReport::default() },
+    }
+}
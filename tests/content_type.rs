@@ -0,0 +1,26 @@
+use claude::utils::{ get_content_type, sniff_content_type };
+use std::path::Path;
+
+/// Returns the start of a ZIP archive whose first entry is named `entry`.
+fn zip_with(entry: &str) -> Vec<u8> {
+    let mut bytes = b"PK\x03\x04".to_vec();
+    bytes.extend_from_slice(&[0; 26]);
+    bytes.extend_from_slice(entry.as_bytes());
+    bytes
+}
+
+#[test]
+fn zip_containers_are_told_apart() {
+    assert_eq!(sniff_content_type(&zip_with("word/document.xml")), get_content_type(Path::new("a.docx")));
+    assert_eq!(sniff_content_type(&zip_with("xl/workbook.xml")), get_content_type(Path::new("a.xlsx")));
+    assert_eq!(sniff_content_type(&zip_with("ppt/presentation.xml")), get_content_type(Path::new("a.pptx")));
+    assert_eq!(sniff_content_type(&zip_with("mimetypeapplication/epub+zip")), Some("application/epub+zip"));
+    assert_eq!(sniff_content_type(&zip_with("notes.txt")), Some("application/zip"));
+}
+
+#[test]
+fn text_cut_mid_character_is_still_text() {
+    let text = "Größe".as_bytes();
+    assert_eq!(sniff_content_type(&text[..3]), Some("text/plain"));
+    assert_eq!(sniff_content_type(b"\x00\x01binary"), None);
+}
@@ -0,0 +1,75 @@
+use claude::{ ApiError, Context, Error, ErrorKind, RetryPolicy, RetryStrategy };
+use reqwest::StatusCode;
+
+fn status(status: StatusCode) -> Error {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::Unauthorized(ApiError::from_body(status, "")),
+        StatusCode::TOO_MANY_REQUESTS => Error::RateLimited { resets_at: None, message: String::new() },
+        _ => Error::Api(ApiError::from_body(status, "")),
+    }
+}
+
+#[test]
+fn api_error_bodies_are_parsed() {
+    let body = r#"{"type":"error","error":{"type":"not_found_error","message":"Conversation not found"}}"#;
+    let e = ApiError::from_body(StatusCode::NOT_FOUND, body);
+    assert_eq!(e.error_type.as_deref(), Some("not_found_error"));
+    assert_eq!(e.message, "Conversation not found");
+    assert_eq!(e.to_string(), "404 Not Found (not_found_error): Conversation not found");
+
+    let e = ApiError::from_body(StatusCode::BAD_GATEWAY, "<html>Bad gateway</html>\n");
+    assert_eq!(e.error_type, None);
+    assert_eq!(e.message, "<html>Bad gateway</html>");
+}
+
+#[test]
+fn statuses_map_to_kinds() {
+    assert!(status(StatusCode::FORBIDDEN).is_auth());
+    assert!(status(StatusCode::TOO_MANY_REQUESTS).is_rate_limit());
+    assert_eq!(status(StatusCode::BAD_REQUEST).kind(), ErrorKind::Api);
+}
+
+#[test]
+fn retryability() {
+    assert!(status(StatusCode::TOO_MANY_REQUESTS).is_retryable());
+    assert!(status(StatusCode::BAD_GATEWAY).is_retryable());
+    assert!(!status(StatusCode::UNAUTHORIZED).is_retryable());
+    assert!(Error::EmptyResponse.is_retryable());
+    assert!(!Error::DeadlineExceeded.is_retryable());
+    assert!(!Error::AttachmentsDisabled.is_retryable());
+}
+
+#[test]
+fn retry_policy_decisions() {
+    let policy = RetryPolicy { max_retries: 2, ..RetryPolicy::default() };
+    let backoff = policy.should_retry(1, &status(StatusCode::BAD_GATEWAY)).unwrap();
+    assert!(backoff <= policy.backoff(1) && backoff >= policy.backoff(1) / 2);
+    assert_eq!(policy.should_retry(3, &status(StatusCode::BAD_GATEWAY)), None);
+    assert_eq!(policy.should_retry(1, &status(StatusCode::UNAUTHORIZED)), None);
+
+    let soon = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+    let limited = Error::RateLimited { resets_at: Some(soon), message: String::new() };
+    assert!(policy.should_retry(1, &limited).unwrap() > policy.backoff(1));
+    let later = Error::RateLimited { resets_at: Some(soon + policy.max_backoff), message: String::new() };
+    assert_eq!(policy.should_retry(1, &later), None);
+}
+
+#[test]
+fn context_layers_are_transparent() {
+    let e = Err::<(), _>(status(StatusCode::UNAUTHORIZED)).context("while listing conversations").unwrap_err();
+    assert!(e.is_auth());
+    assert_eq!(e.kind(), ErrorKind::Auth);
+}
+
+#[test]
+fn retry_after_is_read_from_nested_error_bodies() {
+    let resets_at = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() + 120;
+    let message = serde_json::json!({ "type": "exceeded_limit", "resetsAt": resets_at }).to_string();
+    let body = serde_json::json!({ "error": { "type": "rate_limit_error", "message": message } }).to_string();
+
+    let e = Error::ModelUnavailable { model: "claude-2".to_string(), message: body };
+    assert!(e.retry_after().is_some());
+    assert!(e.is_retryable());
+
+    assert_eq!(status(StatusCode::TOO_MANY_REQUESTS).retry_after(), None);
+}
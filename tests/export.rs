@@ -0,0 +1,141 @@
+#![cfg(feature = "export")]
+
+use claude::{
+    export::{ anonymize::Anonymizer, write_conversation, ExportFormat, JsonExporter, Transcript },
+    ChatMessage,
+    Conversation,
+};
+
+fn fixture() -> (Conversation, Vec<ChatMessage>) {
+    let conversation = serde_json::from_value(
+        serde_json::json!({ "uuid": "c-1", "name": "Grace's taxes", "summary": "" })
+    ).unwrap();
+    let messages = serde_json::from_value(
+        serde_json::json!([
+            {
+                "uuid": "m-1",
+                "attachments": [{
+                    "id": "a-1",
+                    "extracted_content": "Grace Hopper, 42 Main Street",
+                    "file_name": "return.txt",
+                    "file_size": 28,
+                    "file_type": "text/plain",
+                }],
+                "sender": "human",
+                "index": 0,
+                "text": "I am Grace. Reply to grace.hopper@navy.example.org or GRACE.HOPPER@navy.example.org.",
+                "sent_from": "Grace's laptop",
+            },
+            {
+                "uuid": "m-2",
+                "attachments": [],
+                "sender": "assistant",
+                "index": 1,
+                "text": "Sure, Grace.",
+                "parent_message_uuid": "m-1",
+            },
+        ])
+    ).unwrap();
+    (conversation, messages)
+}
+
+#[test]
+fn anonymizer_scrubs_identifiers_emails_and_attachments() {
+    let (conversation, messages) = fixture();
+    let anonymizer = Anonymizer { identifiers: vec!["Grace".to_string()], ..Anonymizer::default() };
+
+    let conversation = anonymizer.anonymize_conversation(&conversation);
+    assert!(!conversation.name.contains("Grace") && conversation.uuid != "c-1");
+
+    let message = anonymizer.anonymize_message(&messages[0]);
+    assert!(!message.text.contains("Grace") && !message.text.contains("navy"));
+    assert!(message.text.ends_with("."));
+    // Addresses differing only by case share a pseudonym.
+    let pseudonyms: Vec<&str> = message.text.matches("[email:").collect();
+    assert_eq!(pseudonyms.len(), 2);
+    let emails: Vec<&str> = message.text.split(' ').filter(|word| word.starts_with("[email:")).collect();
+    assert_eq!(emails[0], emails[1].trim_end_matches('.'));
+
+    assert_eq!(message.attachments[0].extracted_content, "");
+    assert_eq!(message.attachments[0].file_name, "return.txt");
+    assert_ne!(message.attachments[0].id, "a-1");
+    assert!(message.extra.is_empty());
+
+    // Branches still link up once UUIDs are hashed.
+    let reply = anonymizer.anonymize_message(&messages[1]);
+    assert_eq!(reply.parent_message_uuid.as_deref(), Some(message.uuid.as_str()));
+}
+
+#[test]
+fn anthropic_jsonl_uses_content_blocks() {
+    let (conversation, messages) = fixture();
+    let mut output = Vec::new();
+    write_conversation(&mut *ExportFormat::AnthropicJsonl.exporter(&mut output), &conversation, &messages).unwrap();
+    write_conversation(&mut *ExportFormat::AnthropicJsonl.exporter(&mut output), &conversation, &messages).unwrap();
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    let first = &lines[0]["messages"][0];
+    assert_eq!(first["role"], "user");
+    assert_eq!(first["content"].as_array().unwrap().len(), 2);
+    assert_eq!(lines[0]["messages"][1]["content"][0]["text"], "Sure, Grace.");
+}
+
+#[test]
+fn jsonl_escapes_attachment_names() {
+    let (conversation, mut messages) = fixture();
+    messages[0].attachments[0].file_name = "a\">b.txt".to_string();
+    for format in [ExportFormat::OpenAiJsonl, ExportFormat::AnthropicJsonl] {
+        let mut output = Vec::new();
+        write_conversation(&mut *format.exporter(&mut output), &conversation, &messages).unwrap();
+        let line: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let content = line["messages"][0]["content"].to_string();
+        assert!(content.contains("<document name=\\\"a&quot;&gt;b.txt\\\">"), "{}", content);
+    }
+}
+
+fn transcript(name: &str, messages: &[(&str, &str)]) -> Transcript {
+    let messages = messages
+        .iter()
+        .enumerate()
+        .map(|(index, (sent_at, text))| {
+            serde_json::from_value(serde_json::json!({
+                "uuid": format!("{}-{}", name, index),
+                "attachments": [],
+                "sender": if index % 2 == 0 { "human" } else { "assistant" },
+                "index": index,
+                "text": text,
+                "created_at": sent_at,
+            })).unwrap()
+        })
+        .collect();
+    let conversation = serde_json::from_value(serde_json::json!({ "uuid": name, "name": name, "summary": "" })).unwrap();
+    Transcript { conversation, messages }
+}
+
+#[test]
+fn transcripts_merge_chronologically_with_provenance() {
+    let first = transcript("Part 1", &[("2023-11-05T10:00:00Z", "a"), ("2023-11-05T10:01:00Z", "b")]);
+    let second = transcript("Part 2", &[("2023-11-05T10:00:30Z", "c"), ("2023-11-05T10:02:00Z", "d")]);
+
+    // Merging works on transcripts read back from JSON exports.
+    let mut exporter = JsonExporter::new(Vec::new());
+    first.write(&mut exporter).unwrap();
+    let first = Transcript::read(exporter.into_inner().as_slice()).unwrap();
+
+    let merged = first.merge(&second);
+    assert_eq!(merged.conversation.name, "Part 1 + Part 2");
+    let texts: Vec<&str> = merged.messages.iter().map(|m| m.text.as_str()).collect();
+    assert_eq!(texts, [
+        "[from \"Part 1\", message 0]\na",
+        "[from \"Part 2\", message 0]\nc",
+        "[from \"Part 1\", message 1]\nb",
+        "[from \"Part 2\", message 1]\nd",
+    ]);
+    let indices: Vec<usize> = merged.messages.iter().map(|m| m.index).collect();
+    assert_eq!(indices, [0, 1, 2, 3]);
+}
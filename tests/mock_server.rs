@@ -0,0 +1,1338 @@
+//! End-to-end tests against a local mock of claude.ai, reached through `ClientBuilder::base_url`.
+
+use claude::{
+    account::Plan,
+    api::ApiClient,
+    artifacts::ArtifactParser,
+    auth::{ AuthFuture, AuthHook },
+    backend::ClaudeBackend,
+    manager::ClientManager,
+    sse::json_lines,
+    summarize::SummaryStyle,
+    ApiError,
+    Client,
+    ConversationSnapshot,
+    Error,
+    ErrorKind,
+    ListParams,
+    NewChatOptions,
+    RetryPolicy,
+    SortOrder,
+    StreamEvent,
+};
+#[cfg(feature = "store")]
+use claude::archive::ChatArchive;
+#[cfg(feature = "login")]
+use claude::login::EmailLogin;
+use futures_util::StreamExt;
+use std::{ sync::atomic::{ AtomicUsize, Ordering }, time::Duration };
+use tokio::{ io::{ AsyncReadExt, AsyncWriteExt }, net::TcpListener };
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path, e))
+}
+
+/// Serves each request with the `(status, body)` returned by `route` for its method and path, and returns the
+/// base URL of the server.
+async fn serve(route: fn(&str, &str) -> (u16, String)) -> String {
+    serve_with_headers(move |method, path| {
+        let (status, body) = route(method, path);
+        (status, vec![], body)
+    }).await
+}
+
+/// Like `serve`, with the headers returned by `route` added to each response.
+async fn serve_with_headers(
+    route: impl Fn(&str, &str) -> (u16, Vec<String>, String) + Copy + Send + 'static
+) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let head = String::from_utf8_lossy(&request).into_owned();
+                // Drain bodies such as uploads, so the client is done writing before the response.
+                let lowercase = head.to_lowercase();
+                let header_end = request.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+                let content_length = lowercase
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|length| length.trim().parse::<usize>().ok());
+                loop {
+                    let done = match content_length {
+                        Some(length) => request.len() >= header_end + length,
+                        None => !lowercase.contains("transfer-encoding: chunked") || request.ends_with(b"0\r\n\r\n"),
+                    };
+                    if done {
+                        break;
+                    }
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let mut parts = head.split_whitespace();
+                let (method, path) = (parts.next().unwrap(), parts.next().unwrap());
+                let (status, headers, body) = route(method, path);
+                let headers: String = headers.iter().map(|header| format!("{}\r\n", header)).collect();
+                let response = format!(
+                    "HTTP/1.1 {} OK\r\ncontent-length: {}\r\nconnection: close\r\n{}\r\n{}",
+                    status,
+                    body.len(),
+                    headers,
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            });
+        }
+    });
+    format!("http://{}", addr)
+}
+
+fn claude_ai(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", "/api/organizations") => (200, fixture("organizations.json")),
+        ("GET", p) if p.ends_with("/chat_conversations") => (200, fixture("chat_conversations.json")),
+        _ => (404, String::new()),
+    }
+}
+
+#[tokio::test]
+async fn builder_targets_the_base_url() {
+    let base_url = serve(claude_ai).await;
+    let client = Client::builder("sessionKey=test").base_url(base_url).build().await.unwrap();
+    assert_eq!(client.org_uuid, "0f6b7a34-9c7e-4c4f-9a1e-2f4a1e0d5b11");
+
+    let conversations = client.list_all_conversations().await.unwrap();
+    assert_eq!(conversations.len(), 2);
+}
+
+#[tokio::test]
+async fn cookies_are_left_out_of_debug_output() {
+    let builder = Client::builder("sessionKey=sk-secret").base_url(serve(claude_ai).await);
+    assert!(!format!("{:?}", builder).contains("sk-secret"));
+    let client = builder.build().await.unwrap();
+    assert_eq!(client.cookies(), "sessionKey=sk-secret");
+    assert!(!format!("{:?}", client).contains("sk-secret"));
+}
+
+fn starred(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.ends_with("/chat_conversations") => (200, fixture("synthetic/chat_conversations.json")),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn starred_conversations_are_listed() {
+    let client = Client::builder("sessionKey=test").base_url(serve(starred).await).build().await.unwrap();
+    let conversations = client.list_starred_conversations().await.unwrap();
+    let names: Vec<_> = conversations.iter().map(|conversation| conversation.name.as_str()).collect();
+    assert_eq!(names, ["CV review"]);
+}
+
+#[tokio::test]
+async fn timezone_defaults_to_the_local_one() {
+    let base_url = serve(claude_ai).await;
+    let client = Client::builder("sessionKey=test").base_url(&base_url).build().await.unwrap();
+    assert!(!client.timezone.is_empty());
+
+    let client = Client::builder("sessionKey=test").base_url(&base_url).timezone("Europe/Paris").build().await.unwrap();
+    assert_eq!(client.timezone, "Europe/Paris");
+}
+
+fn without_organizations(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", "/api/organizations") => (200, "[]".to_string()),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn accounts_without_organizations_are_errors() {
+    let e = Client::builder("sessionKey=test").base_url(serve(without_organizations).await).build().await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::NoOrganization));
+    assert_eq!(e.kind(), ErrorKind::Auth);
+}
+
+#[test]
+fn invalid_header_names_are_errors() {
+    assert!(Client::builder("sessionKey=test").header("X-Request-Source", "batch").is_ok());
+    let e = Client::builder("sessionKey=test").header("x request source", "batch").unwrap_err();
+    assert!(matches!(e, Error::InvalidHttpHeaderName(_)));
+    assert_eq!(e.kind(), ErrorKind::Validation);
+}
+
+fn signed_in(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", "/api/account") => (200, fixture("account.json")),
+        ("GET", p) if p.ends_with("/usage") => (200, fixture("usage.json")),
+        ("GET", p) if p.ends_with("/chat_conversations/chat") => (200, fixture("chat_conversation.json")),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn account_info_reports_the_plan_of_the_organization() {
+    let client = Client::builder("sessionKey=test").base_url(serve(signed_in).await).build().await.unwrap();
+    let account = client.account_info().await.unwrap();
+    assert_eq!(account.email_address, "user@example.com");
+    assert_eq!(account.display_name.as_deref(), Some("Jane"));
+    assert_eq!(account.plan, Plan::Pro);
+    assert_eq!(account.rate_limit_tier.as_deref(), Some("default_claude_ai"));
+}
+
+#[tokio::test]
+async fn usage_reports_exhausted_limits() {
+    let client = Client::builder("sessionKey=test").base_url(serve(signed_in).await).build().await.unwrap();
+    let usage = client.usage().await.unwrap();
+    assert!(usage.is_exhausted());
+    assert_eq!(usage.seven_day.as_ref().unwrap().utilization, 37.0);
+    assert!(usage.seven_day_opus.is_none());
+    assert!(usage.extra.contains_key("seven_day_oauth_apps"));
+    // The fixture's limit reset long ago.
+    assert_eq!(usage.wait_time(), Some(Duration::ZERO));
+}
+
+#[tokio::test]
+async fn schema_changes_are_reported_by_endpoint() {
+    let client = Client::builder("sessionKey=test").base_url(serve(signed_in).await).build().await.unwrap();
+    let report = client.check_schema(Some("chat")).await;
+    let endpoints: Vec<_> = report.endpoints.iter().map(|endpoint| endpoint.endpoint.as_str()).collect();
+    assert_eq!(endpoints, ["organizations", "conversations", "conversation", "account", "usage"]);
+
+    let organizations = &report.endpoints[0];
+    assert!(organizations.unknown.contains(&"[].capabilities".to_string()));
+    assert!(organizations.missing.is_empty());
+    assert!(report.endpoints[1].is_clean());
+    assert!(report.endpoints[4].unknown.contains(&"seven_day_oauth_apps".to_string()));
+    assert!(!report.is_breaking());
+}
+
+#[tokio::test]
+async fn raw_requests_reuse_the_client() {
+    let client = Client::builder("sessionKey=test").base_url(serve(claude_ai).await).build().await.unwrap();
+    let path = format!("/api/organizations/{}/chat_conversations", client.org_uuid);
+    let conversations = client.raw_get(&path).await.unwrap();
+    assert_eq!(conversations[0]["name"], "CV review");
+
+    let missing = client.raw_post("api/unknown", &serde_json::json!({ "name": "x" })).await.unwrap_err();
+    assert!(matches!(missing.root_cause(), Error::Api(e) if e.status == reqwest::StatusCode::NOT_FOUND));
+}
+
+fn without_summaries(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.ends_with("/chat_conversations") => (200, r#"[{"uuid": "c-1", "name": "Taxes"}]"#.to_string()),
+        _ => claude_ai(method, path),
+    }
+}
+
+fn with_every_field(method: &str, path: &str) -> (u16, String) {
+    let conversation = serde_json::json!({
+        "uuid": "c-1",
+        "name": "Taxes",
+        "summary": "",
+        "settings": {},
+        "created_at": "2024-04-01T09:00:00Z",
+        "updated_at": "2024-04-01T09:05:00Z",
+    });
+    match (method, path) {
+        ("GET", p) if p.ends_with("/chat_conversations") => (200, serde_json::json!([conversation]).to_string()),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn strict_clients_accept_complete_responses() {
+    let mut client = Client::builder("sessionKey=test").base_url(serve(with_every_field).await).build().await.unwrap();
+    client.strict_responses = true;
+    let conversations = client.list_all_conversations().await.unwrap();
+    assert!(conversations[0].missing_fields().is_empty());
+}
+
+#[tokio::test]
+async fn vanished_fields_fail_only_strict_clients() {
+    let mut client = Client::builder("sessionKey=test").base_url(serve(without_summaries).await).build().await.unwrap();
+    let conversations = client.list_all_conversations().await.unwrap();
+    assert_eq!(conversations[0].summary, None);
+    assert_eq!(conversations[0].missing_fields(), ["summary", "settings", "created_at", "updated_at"]);
+
+    client.strict_responses = true;
+    let e = client.list_all_conversations().await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::MissingFields { uuid, fields } if uuid == "c-1" && fields.len() == 4));
+    assert_eq!(e.kind(), ErrorKind::Parse);
+}
+
+fn partly_deletable(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("DELETE", p) if p.ends_with("/e56a5ab3-0eca-4a04-9c63-3fadaf14cd17") => (204, String::new()),
+        ("DELETE", _) => (403, r#"{"error":{"type":"permission_error","message":"Forbidden"}}"#.to_string()),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn reset_all_reports_failed_deletions() {
+    let client = Client::builder("sessionKey=test").base_url(serve(partly_deletable).await).build().await.unwrap();
+    let summary = client.reset_all().await.unwrap();
+    assert_eq!(summary.deleted, ["e56a5ab3-0eca-4a04-9c63-3fadaf14cd17"]);
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(summary.failed[0].0, "7d1d3c9a-51a6-4f5b-8b56-1f0ac0ad62e4");
+    assert!(!summary.is_complete());
+}
+
+fn branching(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.contains("/chat_conversations/chat?tree=True") => (200, fixture("chat_conversation_tree.json")),
+        ("PUT", p) if p.ends_with("/chat_conversations/chat/current_leaf_message_uuid") => (202, String::new()),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn conversation_trees_keep_every_branch() {
+    let client = Client::builder("sessionKey=test").base_url(serve(branching).await).build().await.unwrap();
+    let tree = client.conversation_tree("chat").await.unwrap();
+    let texts = |messages: Vec<&claude::ChatMessage>| -> Vec<String> {
+        messages.iter().map(|message| message.text.trim().to_string()).collect()
+    };
+
+    assert_eq!(tree.messages.len(), 5);
+    assert_eq!(texts(tree.roots()), ["Suggest a name for my cat"]);
+    assert_eq!(
+        texts(tree.current_branch()),
+        ["Suggest a name for my cat", "How about Miso?", "Something shorter", "Bo."]
+    );
+    let first_answer = "a8e4d2c7-3f1b-4e9a-8c5d-2b7f0e1a9c02";
+    assert_eq!(texts(tree.siblings(first_answer)), ["How about Whiskers?", "How about Miso?"]);
+    assert_eq!(texts(tree.leaves()), ["How about Whiskers?", "Bo."]);
+    assert_eq!(tree.latest_leaf(&tree.roots()[0].uuid).unwrap().text, " Bo.");
+
+    client.set_current_leaf("chat", first_answer).await.unwrap();
+}
+
+#[test]
+fn conversation_trees_stop_at_cycles() {
+    let message = |uuid: &str, parent: &str, index: usize| -> claude::ChatMessage {
+        serde_json::from_value(serde_json::json!({
+            "uuid": uuid, "parent_message_uuid": parent, "index": index,
+            "sender": "human", "text": "", "attachments": [],
+        })).unwrap()
+    };
+    // Corrupt links making each message the parent of the other.
+    let tree = claude::tree::ConversationTree::new(vec![message("a", "b", 0), message("b", "a", 1)], None);
+    assert_eq!(tree.branch("a").len(), 2);
+    assert!(tree.latest_leaf("a").is_some());
+}
+
+fn snapshotting(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.ends_with("/chat_conversations/chat") => (200, fixture("chat_conversation.json")),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn snapshots_hold_the_conversation_and_its_messages() {
+    let client = Client::builder("sessionKey=test").base_url(serve(snapshotting).await).build().await.unwrap();
+    let snapshot = client.conversation_snapshot("chat").await.unwrap();
+
+    assert_eq!(snapshot.conversation().name, "CV review");
+    assert!(!snapshot.conversation().extra.contains_key("chat_messages"));
+    assert_eq!(snapshot.messages().len(), 2);
+    assert_eq!(snapshot.checksum(), client.conversation_checksum("chat").await.unwrap());
+
+    let restored: ConversationSnapshot = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+    assert_eq!(restored.fetched_at(), snapshot.fetched_at());
+    assert!(!restored.changed_since(&snapshot));
+    assert!(restored.added_since(&snapshot).is_empty());
+}
+
+static FRAMINGS: AtomicUsize = AtomicUsize::new(0);
+
+fn creating(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", p) if p.ends_with("/chat_conversations") => (201, fixture("create_chat_conversation.json")),
+        ("POST", "/api/append_message") => {
+            FRAMINGS.fetch_add(1, Ordering::SeqCst);
+            (200, fixture("append_message.txt"))
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn custom_instructions_are_sent_first() {
+    let client = Client::builder("sessionKey=test").base_url(serve(creating).await).build().await.unwrap();
+
+    client.create_new_chat().await.unwrap();
+    assert_eq!(FRAMINGS.load(Ordering::SeqCst), 0);
+
+    let options = NewChatOptions {
+        name: Some("Pirate talk".to_string()),
+        system_prompt: Some("Answer like a pirate.".to_string()),
+        ..NewChatOptions::default()
+    };
+    client.create_new_chat_with(options).await.unwrap();
+    assert_eq!(FRAMINGS.load(Ordering::SeqCst), 1);
+}
+
+static HELPERS_DELETED: AtomicUsize = AtomicUsize::new(0);
+
+fn summarizing(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.ends_with("/chat_conversations/chat") => (200, fixture("chat_conversation.json")),
+        ("POST", p) if p.ends_with("/chat_conversations") => (201, fixture("create_chat_conversation.json")),
+        ("POST", "/api/append_message") => (200, fixture("append_message.txt")),
+        ("DELETE", _) => {
+            HELPERS_DELETED.fetch_add(1, Ordering::SeqCst);
+            (204, String::new())
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn conversations_are_summarized_in_helper_conversations() {
+    let client = Client::builder("sessionKey=test").base_url(serve(summarizing).await).build().await.unwrap();
+    let summary = client.summarize_conversation("chat", SummaryStyle::Brief).await.unwrap();
+    assert_eq!(summary, "Hello! How can I help you today?");
+    assert_eq!(HELPERS_DELETED.load(Ordering::SeqCst), 1);
+}
+
+fn undeletable_helpers(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("DELETE", _) => (403, String::new()),
+        ("GET", p) if p.contains("/chat_conversations/") => (200, fixture("chat_conversation.json")),
+        _ => summarizing(method, path),
+    }
+}
+
+#[tokio::test]
+async fn summaries_survive_undeletable_helpers() {
+    let client = Client::builder("sessionKey=test").base_url(serve(undeletable_helpers).await).build().await.unwrap();
+    let summary = client.summarize_conversation("chat", SummaryStyle::Brief).await.unwrap();
+    assert_eq!(summary, "Hello! How can I help you today?");
+
+    let mut conversation = client.list_all_conversations().await.unwrap().remove(0);
+    client.generate_summary(&mut conversation).await.unwrap();
+    assert_eq!(conversation.summary.as_deref(), Some("Hello! How can I help you today?"));
+}
+
+#[cfg(feature = "store")]
+fn archiving(method: &str, path: &str) -> (u16, String) {
+    let (first, second) = ("e56a5ab3-0eca-4a04-9c63-3fadaf14cd17", "7d1d3c9a-51a6-4f5b-8b56-1f0ac0ad62e4");
+    match (method, path) {
+        ("GET", p) if p.ends_with(first) => (200, fixture("chat_conversation.json")),
+        ("GET", p) if p.ends_with(second) => (200, fixture("chat_conversation.json").replace(first, second)),
+        ("POST", p) if p.ends_with("/chat_conversations") => (201, fixture("create_chat_conversation.json")),
+        ("POST", "/api/append_message") => (200, fixture("append_message.txt")),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[cfg(feature = "store")]
+#[tokio::test]
+async fn archived_conversations_answer_questions() {
+    let base_url = serve(archiving).await;
+    let client = Client::builder("sessionKey=test").base_url(&base_url).build().await.unwrap();
+    let dir = std::env::temp_dir().join(format!("claude-archive-{}", std::process::id()));
+    let mut archive = ChatArchive::open(&dir).unwrap();
+
+    assert_eq!(client.sync_archive(&mut archive).await.unwrap(), 2);
+    assert_eq!(client.sync_archive(&mut archive).await.unwrap(), 0);
+    assert_eq!(ChatArchive::open(&dir).unwrap().len(), 2);
+
+    let hits = archive.search("improve my cv", 10);
+    assert_eq!(hits.len(), 4);
+    assert_eq!(hits[0].message.text, "Help me improve this CV");
+
+    let answer = client.ask_archive(&archive, "How can I improve my CV?").await.unwrap();
+    assert_eq!(answer.text, "Hello! How can I help you today?");
+    assert_eq!(answer.sources.len(), 2);
+    assert!(answer.sources[0].url.starts_with(&format!("{}/chat/", base_url)));
+
+    let report = archive.topic_report(0.2);
+    assert_eq!(report.topics.len(), 1);
+    assert_eq!(report.topics[0].conversations.len(), 2);
+
+    let e = client.ask_archive(&archive, "quantum chromodynamics").await.unwrap_err();
+    assert!(matches!(e, Error::NoArchiveMatch(_)));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+fn projects(method: &str, path: &str) -> (u16, String) {
+    let project = r#"{"uuid":"p-1","name":"Thesis","description":"Drafts","is_private":true,"is_starred":false}"#;
+    let doc = r#"{"uuid":"d-1","file_name":"outline.md","content":"Outline"}"#;
+    match (method, path) {
+        ("POST", p) if p.ends_with("/projects") => (201, project.to_string()),
+        ("GET", p) if p.ends_with("/projects") => (200, format!("[{}]", project)),
+        ("POST", p) if p.ends_with("/projects/p-1/docs") => (201, doc.to_string()),
+        ("GET", p) if p.ends_with("/projects/p-1/docs") => (200, format!("[{}]", doc)),
+        ("DELETE", p) if p.ends_with("/projects/p-1") => (204, String::new()),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn projects_can_be_managed() {
+    let client = Client::builder("sessionKey=test").base_url(serve(projects).await).build().await.unwrap();
+    let project = client.create_project("Thesis", "Drafts").await.unwrap();
+    assert_eq!((project.uuid.as_str(), project.is_private), ("p-1", true));
+    assert_eq!(project.extra["is_starred"], false);
+    assert_eq!(client.list_projects().await.unwrap()[0].name, "Thesis");
+
+    let doc = client.add_project_doc("p-1", "outline.md", "Outline").await.unwrap();
+    assert_eq!(doc.file_name, "outline.md");
+    assert_eq!(client.list_project_docs("p-1").await.unwrap()[0].content, "Outline");
+    client.delete_project("p-1").await.unwrap();
+}
+
+static PAGES: AtomicUsize = AtomicUsize::new(0);
+
+/// Serves 53 conversations in pages.
+fn paginated(method: &str, path: &str) -> (u16, String) {
+    let Some((_, offset)) = path.split_once("offset=") else {
+        return claude_ai(method, path);
+    };
+    let offset: usize = offset.parse().unwrap();
+    PAGES.fetch_add(1, Ordering::SeqCst);
+    let conversations: Vec<_> = (offset..53.min(offset + 50))
+        .map(|i| serde_json::json!({ "uuid": format!("c-{}", i), "name": "", "summary": "" }))
+        .collect();
+    (200, serde_json::Value::from(conversations).to_string())
+}
+
+#[tokio::test]
+async fn conversations_are_streamed_page_by_page() {
+    let client = Client::builder("sessionKey=test").base_url(serve(paginated).await).build().await.unwrap();
+    let first: Vec<_> = client.conversations_stream().take(3).collect().await;
+    assert_eq!(first.len(), 3);
+    assert_eq!(PAGES.load(Ordering::SeqCst), 1);
+
+    let all: Vec<_> = client.conversations_stream().map(Result::unwrap).collect().await;
+    assert_eq!(all.len(), 53);
+    assert_eq!(all[52].uuid, "c-52");
+    assert_eq!(PAGES.load(Ordering::SeqCst), 3);
+
+    let page = client.list_conversations(ListParams { limit: Some(10), offset: 50, ..ListParams::default() }).await;
+    assert_eq!(page.unwrap().len(), 3);
+    let rest = client.list_conversations(ListParams { offset: 30, ..ListParams::default() }).await.unwrap();
+    assert_eq!((rest.len(), rest[0].uuid.as_str()), (23, "c-30"));
+    // One request each, the remaining 23 conversations fitting in a single page.
+    assert_eq!(PAGES.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn conversations_can_be_listed_oldest_first() {
+    let client = Client::builder("sessionKey=test").base_url(serve(claude_ai).await).build().await.unwrap();
+    let params = ListParams { limit: Some(1), order: SortOrder::OldestFirst, ..ListParams::default() };
+    let oldest = client.list_conversations(params).await.unwrap();
+    assert_eq!(oldest.len(), 1);
+    assert_eq!(oldest[0].uuid, "7d1d3c9a-51a6-4f5b-8b56-1f0ac0ad62e4");
+}
+
+/// Lists three conversations, the last two updated at the same time.
+fn tied_conversations(method: &str, path: &str) -> (u16, String) {
+    if method != "GET" || !path.ends_with("/chat_conversations") {
+        return claude_ai(method, path);
+    }
+    let conversations: Vec<_> = [("c-0", "2024-05-02T00:00:00Z"), ("c-1", "2024-05-01T00:00:00Z"), ("c-2", "2024-05-01T00:00:00Z")]
+        .iter()
+        .map(|(uuid, updated_at)| serde_json::json!({ "uuid": uuid, "name": "", "summary": "", "updated_at": updated_at }))
+        .collect();
+    (200, serde_json::Value::from(conversations).to_string())
+}
+
+#[tokio::test]
+async fn oldest_first_is_the_reverse_of_the_listing() {
+    let client = Client::builder("sessionKey=test").base_url(serve(tied_conversations).await).build().await.unwrap();
+    let params = ListParams { order: SortOrder::OldestFirst, ..ListParams::default() };
+    let oldest = client.list_conversations(params).await.unwrap();
+    let uuids: Vec<_> = oldest.iter().map(|conversation| conversation.uuid.as_str()).collect();
+    assert_eq!(uuids, ["c-2", "c-1", "c-0"]);
+}
+
+/// Counts the requests it signs, failing once `limit` is reached.
+#[derive(Debug)]
+struct CountingHook {
+    signed: std::sync::Arc<AtomicUsize>,
+    limit: usize,
+}
+
+impl AuthHook for CountingHook {
+    fn authenticate<'a>(&'a self, request: &'a mut reqwest::Request) -> AuthFuture<'a> {
+        Box::pin(async move {
+            if self.signed.fetch_add(1, Ordering::SeqCst) >= self.limit {
+                return Err(Error::Unauthorized(ApiError::from_body(reqwest::StatusCode::UNAUTHORIZED, "")));
+            }
+            request.headers_mut().insert("x-proxy-signature", "signed".parse()?);
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn auth_hooks_run_before_every_request() {
+    let signed = std::sync::Arc::new(AtomicUsize::new(0));
+    let client = Client::builder("sessionKey=test")
+        .base_url(serve(claude_ai).await)
+        .auth_hook(CountingHook { signed: signed.clone(), limit: 2 })
+        .build().await
+        .unwrap();
+    client.list_all_conversations().await.unwrap();
+    assert_eq!(signed.load(Ordering::SeqCst), 2);
+    assert!(client.list_all_conversations().await.unwrap_err().is_auth());
+}
+
+#[tokio::test]
+async fn builder_reports_invalid_cookies() {
+    let base_url = serve(|_, _| (403, String::new())).await;
+    assert!(Client::builder("sessionKey=expired").base_url(base_url).build().await.is_err());
+}
+
+static LOGINS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts the clients built, which look up their organization once.
+fn counting_logins(method: &str, path: &str) -> (u16, String) {
+    if path == "/api/organizations" {
+        LOGINS.fetch_add(1, Ordering::SeqCst);
+    }
+    claude_ai(method, path)
+}
+
+#[tokio::test]
+async fn managed_clients_are_cached_until_idle() {
+    let base_url = serve(counting_logins).await;
+    let manager = ClientManager::new().configure(move |builder| builder.base_url(base_url.clone()));
+    manager.insert_session("alice", "sessionKey=alice");
+    manager.insert_session("bob", "sessionKey=bob");
+    assert_eq!(LOGINS.load(Ordering::SeqCst), 0);
+
+    manager.client("alice").await.unwrap();
+    manager.client("alice").await.unwrap().list_all_conversations().await.unwrap();
+    manager.client("bob").await.unwrap();
+    assert_eq!(LOGINS.load(Ordering::SeqCst), 2);
+    assert_eq!(manager.evict_idle(), 0);
+
+    let manager = manager.idle_timeout(Duration::ZERO);
+    assert_eq!(manager.evict_idle(), 2);
+    manager.client("alice").await.unwrap();
+    assert_eq!(LOGINS.load(Ordering::SeqCst), 3);
+
+    assert!(manager.remove_session("bob"));
+    assert!(matches!(manager.client("bob").await, Err(Error::UnknownSession(user)) if user == "bob"));
+}
+
+#[tokio::test]
+async fn rebuilt_clients_share_the_rate_limit_of_the_user() {
+    let base_url = serve(claude_ai).await;
+    let manager = ClientManager::new()
+        .idle_timeout(Duration::ZERO)
+        .requests_per_minute(60)
+        .configure(move |builder| builder.base_url(base_url.clone()));
+    manager.insert_session("alice", "sessionKey=alice");
+
+    let held = manager.client("alice").await.unwrap();
+    held.list_all_conversations().await.unwrap();
+    assert_eq!(manager.evict_idle(), 1);
+
+    // The new client waits for the slot after the one taken by the client still held.
+    let start = std::time::Instant::now();
+    manager.client("alice").await.unwrap().list_all_conversations().await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(900), "{:?}", start.elapsed());
+}
+
+#[cfg(feature = "login")]
+fn magic_link(method: &str, path: &str) -> (u16, Vec<String>, String) {
+    match (method, path) {
+        ("POST", "/api/auth/send_magic_link") => (200, vec![], r#"{"sent":true}"#.to_string()),
+        ("POST", "/api/auth/verify_magic_link") => {
+            let cookies = vec![
+                "set-cookie: activitySessionId=a1; Path=/".to_string(),
+                "set-cookie: sessionKey=sk-ant-sid01-test; Path=/; Secure; HttpOnly".to_string()
+            ];
+            (200, cookies, r#"{"success":true}"#.to_string())
+        }
+        _ => (404, vec![], String::new()),
+    }
+}
+
+#[cfg(feature = "login")]
+#[tokio::test]
+async fn email_logins_return_the_session_cookie() {
+    let base_url = serve_with_headers(magic_link).await;
+    let login = EmailLogin::new("jane@example.com").unwrap().with_base_url(base_url).unwrap();
+    login.send().await.unwrap();
+    assert_eq!(login.verify_code("123456").await.unwrap(), "sessionKey=sk-ant-sid01-test");
+    let cookies = login.verify_link("https://claude.ai/magic-link#n0nce:amFuZUBleGFtcGxlLmNvbQ").await.unwrap();
+    assert_eq!(cookies, "sessionKey=sk-ant-sid01-test");
+    assert!(matches!(login.verify_link("https://claude.ai/login").await, Err(Error::InvalidMagicLink(_))));
+}
+
+fn limited(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", "/api/organizations") => (200, fixture("organizations.json")),
+        ("GET", p) if p.ends_with("/chat_conversations") => {
+            let resets_at = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() + 120;
+            let message = serde_json::json!({ "type": "exceeded_limit", "resetsAt": resets_at }).to_string();
+            (429, serde_json::json!({ "error": { "type": "rate_limit_error", "message": message } }).to_string())
+        }
+        _ => (401, r#"{"error":{"type":"permission_error","message":"Invalid authorization"}}"#.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn error_statuses_become_typed_errors() {
+    let client = Client::builder("sessionKey=test").base_url(serve(limited).await).build().await.unwrap();
+
+    let e = client.list_all_conversations().await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::RateLimited { resets_at: Some(_), .. }), "{:?}", e);
+    let wait = e.retry_after().unwrap();
+    assert!(wait > Duration::from_secs(100) && wait <= Duration::from_secs(120));
+
+    let e = client.get_conversation("missing").await.unwrap_err();
+    match e.root_cause() {
+        Error::Unauthorized(e) => assert_eq!(e.error_type.as_deref(), Some("permission_error")),
+        e => panic!("expected Unauthorized, got {:?}", e),
+    }
+}
+
+static FLAKY_FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+/// Fails listing conversations with a 503 twice before answering.
+fn flaky(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.ends_with("/chat_conversations") && FLAKY_FAILURES.fetch_add(1, Ordering::SeqCst) < 2 => {
+            (503, String::new())
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried() {
+    let policy = RetryPolicy { initial_backoff: Duration::from_millis(10), ..RetryPolicy::default() };
+    let client = Client::builder("sessionKey=test")
+        .base_url(serve(flaky).await)
+        .retry_policy(policy)
+        .build().await
+        .unwrap();
+    assert_eq!(client.list_all_conversations().await.unwrap().len(), 2);
+    assert_eq!(FLAKY_FAILURES.load(Ordering::SeqCst), 3);
+}
+
+#[cfg(feature = "attachments")]
+static FLAKY_UPLOADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Fails converting a document with a 503 once before converting it.
+#[cfg(feature = "attachments")]
+fn flaky_upload(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/convert_document") if FLAKY_UPLOADS.fetch_add(1, Ordering::SeqCst) < 1 => (503, String::new()),
+        ("POST", "/api/convert_document") =>
+            (200, serde_json::json!({ "file_name": "notes.txt", "extracted_content": "notes" }).to_string()),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[cfg(feature = "attachments")]
+#[tokio::test]
+async fn streamed_uploads_are_retried() {
+    let dir = std::env::temp_dir().join(format!("claude-retry-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let notes = dir.join("notes.txt");
+    std::fs::write(&notes, "notes").unwrap();
+
+    let policy = RetryPolicy { initial_backoff: Duration::from_millis(10), ..RetryPolicy::default() };
+    let client = Client::builder("sessionKey=test")
+        .base_url(serve(flaky_upload).await)
+        .retry_policy(policy)
+        .build().await
+        .unwrap();
+    let uploaded = client.upload_attachment(&notes).await.unwrap();
+    assert_eq!(uploaded.extracted_content, "notes");
+    assert_eq!(FLAKY_UPLOADS.load(Ordering::SeqCst), 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn requests_are_rate_limited() {
+    let client = Client::builder("sessionKey=test")
+        .base_url(serve(claude_ai).await)
+        .requests_per_minute(600)
+        .build().await
+        .unwrap();
+    let started = std::time::Instant::now();
+    let clone = client.clone();
+    let (first, second) = tokio::join!(client.list_all_conversations(), clone.list_all_conversations());
+    first.unwrap();
+    second.unwrap();
+    client.list_all_conversations().await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(200));
+}
+
+static STOPS: AtomicUsize = AtomicUsize::new(0);
+
+fn answering(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/append_message" | "/api/retry_message") => (200, fixture("../corpus/sse/completion.txt")),
+        ("POST", p) if p.ends_with("/stop_response") => {
+            STOPS.fetch_add(1, Ordering::SeqCst);
+            (200, String::new())
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn cancellable_messages_can_be_awaited_or_aborted() {
+    let client = Client::builder("sessionKey=test").base_url(serve(answering).await).build().await.unwrap();
+
+    let completion = client.send_message_cancellable("chat", "Hello", None, None).await.unwrap();
+    assert_eq!(completion.text, "Hello! How can I help you today?");
+    assert_eq!(STOPS.load(Ordering::SeqCst), 0);
+
+    let handle = client.send_message_cancellable("chat", "Hello", None, None);
+    assert_eq!(handle.chat_uuid(), "chat");
+    handle.abort().await.unwrap();
+    assert_eq!(STOPS.load(Ordering::SeqCst), 1);
+}
+
+static ANSWERS: AtomicUsize = AtomicUsize::new(0);
+
+fn counting_answers(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/append_message") => {
+            ANSWERS.fetch_add(1, Ordering::SeqCst);
+            (200, fixture("../corpus/sse/completion.txt"))
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn builder_enables_the_answer_cache() {
+    let client = Client::builder("sessionKey=test")
+        .base_url(serve(counting_answers).await)
+        .answer_cache(Duration::from_secs(60))
+        .build().await
+        .unwrap();
+    for _ in 0..2 {
+        let answer = client.send_message("chat", "Hello", None, None).await.unwrap();
+        assert_eq!(answer, "Hello! How can I help you today?");
+    }
+    assert_eq!(ANSWERS.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn builder_rejects_invalid_cloudflare_proxies() {
+    let policy = claude::CloudflarePolicy { proxies: vec!["not a proxy".to_string()], ..Default::default() };
+    let built = Client::builder("sessionKey=test").base_url(serve(claude_ai).await).cloudflare_policy(policy).build().await;
+    assert!(built.is_err());
+}
+
+static ACCOUNT_LIMITED: AtomicUsize = AtomicUsize::new(0);
+
+/// Rate limits every message of the account.
+fn account_limited(method: &str, path: &str) -> (u16, Vec<String>, String) {
+    match (method, path) {
+        ("POST", "/api/append_message") => {
+            ACCOUNT_LIMITED.fetch_add(1, Ordering::SeqCst);
+            let body = r#"{"error":{"type":"rate_limit_error","message":"Too many requests"}}"#;
+            (429, vec!["retry-after: 30".to_string()], body.to_string())
+        }
+        _ => {
+            let (status, body) = claude_ai(method, path);
+            (status, vec![], body)
+        }
+    }
+}
+
+#[tokio::test]
+async fn account_rate_limits_skip_the_fallback_models() {
+    let mut client = Client::builder("sessionKey=test")
+        .base_url(serve_with_headers(account_limited).await)
+        .retry_policy(RetryPolicy { max_retries: 0, ..RetryPolicy::default() })
+        .build().await
+        .unwrap();
+    client.fallback_models = vec![claude::Model::Claude3Haiku];
+
+    let e = client.send_message("chat", "Hello", None, None).await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::RateLimited { .. }), "{:?}", e);
+    assert!(e.is_rate_limit());
+    assert!(e.retry_after().is_some_and(|wait| wait > Duration::from_secs(20)));
+    assert_eq!(ACCOUNT_LIMITED.load(Ordering::SeqCst), 1);
+}
+
+static MODEL_LIMITED: AtomicUsize = AtomicUsize::new(0);
+
+/// Refuses the first message because the quota of its model is exhausted, then answers.
+fn model_limited(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/append_message") if MODEL_LIMITED.fetch_add(1, Ordering::SeqCst) == 0 => {
+            let message = serde_json::json!({ "type": "exceeded_limit", "resetsAt": 4102444800u64, "perModelLimit": true });
+            (429, serde_json::json!({ "error": { "type": "rate_limit_error", "message": message.to_string() } }).to_string())
+        }
+        _ => answering(method, path),
+    }
+}
+
+#[tokio::test]
+async fn model_quotas_fall_back_to_the_next_model() {
+    let mut client = Client::builder("sessionKey=test").base_url(serve(model_limited).await).build().await.unwrap();
+    client.fallback_models = vec![claude::Model::Claude3Haiku];
+
+    let completion = client.send_message_detailed("chat", "Hello", None, None).await.unwrap();
+    assert_eq!(completion.model, claude::Model::Claude3Haiku.id());
+    assert_eq!(MODEL_LIMITED.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn long_prompts_skip_models_with_small_windows() {
+    let mut client = Client::builder("sessionKey=test").base_url(serve(answering).await).build().await.unwrap();
+    client.default_model = claude::Model::Claude2;
+    client.fallback_models = vec![claude::Model::Claude3Haiku];
+
+    let completion = client.send_message_detailed("chat", &"a".repeat(600_000), None, None).await.unwrap();
+    assert_eq!(completion.model, claude::Model::Claude3Haiku.id());
+
+    let e = client.send_message("chat", &"a".repeat(1_000_000), None, None).await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::PromptTooLong { limit: 200_000, .. }), "{:?}", e);
+}
+
+static VALIDATIONS: AtomicUsize = AtomicUsize::new(0);
+
+fn validating(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.ends_with("/chat_conversations/chat") => {
+            VALIDATIONS.fetch_add(1, Ordering::SeqCst);
+            (200, fixture("chat_conversation.json"))
+        }
+        _ => answering(method, path),
+    }
+}
+
+#[tokio::test]
+async fn existing_conversations_are_validated_once() {
+    let mut client = Client::builder("sessionKey=test").base_url(serve(validating).await).build().await.unwrap();
+    client.validate_before_send = true;
+
+    let outcome = client.send_or_create("chat", "Hello", None, None, None).await.unwrap();
+    assert!(!outcome.created);
+    assert_eq!(outcome.completion.text, "Hello! How can I help you today?");
+    assert_eq!(VALIDATIONS.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn last_answers_can_be_regenerated() {
+    let client = Client::builder("sessionKey=test").base_url(serve(answering).await).build().await.unwrap();
+    assert_eq!(client.retry_message("chat", None).await.unwrap(), "Hello! How can I help you today?");
+
+    let events: Vec<_> = client.retry_message_stream("chat", None).await.unwrap().collect().await;
+    let text: String = events
+        .into_iter()
+        .filter_map(|event| match event.unwrap() {
+            StreamEvent::Completion(text) => Some(text),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(text, "Hello! How can I help you today?");
+}
+
+#[tokio::test]
+async fn streamed_answers_render_as_json_lines() {
+    let client = Client::builder("sessionKey=test").base_url(serve(answering).await).build().await.unwrap();
+    let events = client.send_message_stream("chat", "Hello", None, None).await.unwrap();
+    let lines: Vec<String> = json_lines(events).map(|line| line.to_line()).collect().await;
+
+    let text: String = lines
+        .iter()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|line| line["type"] == "delta")
+        .map(|line| line["text"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(text, "Hello! How can I help you today?");
+    assert!(lines.iter().all(|line| line.ends_with('\n') && line.matches('\n').count() == 1));
+    assert_eq!(lines.last().unwrap(), "{\"type\":\"done\",\"stop_reason\":\"stop_sequence\"}\n");
+}
+
+fn stopping_at_once(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/append_message") => {
+            (200, "data: {\"completion\":\"\",\"stop_reason\":\"stop_sequence\",\"stop\":\"\\n\\nHuman:\"}\n\n".to_string())
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn answers_without_text_are_empty_responses() {
+    let policy = RetryPolicy { max_retries: 0, ..RetryPolicy::default() };
+    let client = Client::builder("sessionKey=test")
+        .base_url(serve(stopping_at_once).await)
+        .retry_policy(policy)
+        .build().await
+        .unwrap();
+    let e = client.send_message("chat", "Hello", None, None).await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::EmptyResponse));
+}
+
+fn writing_code(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/append_message") => (200, fixture("append_message_artifact.txt")),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[tokio::test]
+async fn artifacts_are_split_from_answers() {
+    let client = Client::builder("sessionKey=test").base_url(serve(writing_code).await).build().await.unwrap();
+
+    let (text, artifacts) = client.send_message_with_artifacts("chat", "Add numbers", None, None).await.unwrap();
+    assert_eq!(text, "Here is the function:\n\n\n\nIt adds two numbers.");
+    assert_eq!(artifacts.len(), 1);
+    assert_eq!(artifacts[0].id, "add");
+    assert_eq!(artifacts[0].title, "Add");
+    assert_eq!(artifacts[0].language.as_deref(), Some("rust"));
+    assert_eq!(artifacts[0].content, "fn add(a: i32, b: i32) -> i32 {\n    a + b\n}");
+
+    let mut parser = ArtifactParser::new();
+    let events: Vec<_> = client.send_message_stream("chat", "Add numbers", None, None).await.unwrap().collect().await;
+    let streamed: Vec<_> = events
+        .into_iter()
+        .flat_map(|event| match event.unwrap() {
+            StreamEvent::Completion(text) => parser.push(&text),
+            _ => vec![],
+        })
+        .collect();
+    assert_eq!(streamed, artifacts);
+}
+
+fn editing(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.ends_with("/chat_conversations/chat") => (200, fixture("chat_conversation.json")),
+        _ => answering(method, path),
+    }
+}
+
+#[tokio::test]
+async fn only_human_messages_can_be_edited() {
+    let client = Client::builder("sessionKey=test").base_url(serve(editing).await).build().await.unwrap();
+    let answer = client.edit_message("chat", "9a1c6f70-3b8e-4d2a-9f61-5e2c1d0b7a01", "Review my CV", None).await;
+    assert_eq!(answer.unwrap(), "Hello! How can I help you today?");
+
+    let assistant = client.edit_message("chat", "c2e8b4d1-7f3a-4e6b-8a90-1d2c3b4a5f02", "Hi", None).await;
+    assert!(matches!(assistant.unwrap_err().root_cause(), Error::NotEditable(_)));
+    let missing = client.edit_message("chat", "missing", "Hi", None).await;
+    assert!(matches!(missing.unwrap_err().root_cause(), Error::MessageNotFound(_)));
+}
+
+#[cfg(feature = "attachments")]
+static CONVERSIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Answers the first conversion of a document before its text is extracted.
+#[cfg(feature = "attachments")]
+fn slow_conversion(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/convert_document") => {
+            let content = if CONVERSIONS.fetch_add(1, Ordering::SeqCst) == 0 { "" } else { "Hello" };
+            (200, serde_json::json!({ "file_name": "hello.txt", "extracted_content": content }).to_string())
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[cfg(feature = "attachments")]
+#[tokio::test]
+async fn incomplete_conversions_are_requested_again() {
+    let dir = std::env::temp_dir().join(format!("claude-conversion-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (hello, other) = (dir.join("hello.txt"), dir.join("other.txt"));
+    std::fs::write(&hello, "Hello").unwrap();
+    std::fs::write(&other, "Other").unwrap();
+
+    let mut client = Client::builder("sessionKey=test").base_url(serve(slow_conversion).await).build().await.unwrap();
+    let uploaded = client.upload_attachment(&hello).await.unwrap();
+    assert_eq!(uploaded.extracted_content, "Hello");
+    assert_eq!(uploaded.file_name, "hello.txt");
+    assert_eq!(CONVERSIONS.load(Ordering::SeqCst), 2);
+
+    // In-memory attachments share the cache of files.
+    let uploaded = client.upload_attachment_bytes("greeting.txt", "Hello".into(), None).await.unwrap();
+    assert_eq!((uploaded.extracted_content.as_str(), uploaded.file_name.as_str()), ("Hello", "greeting.txt"));
+    assert_eq!(CONVERSIONS.load(Ordering::SeqCst), 2);
+    client.upload_attachment_bytes("report", "# Report".into(), Some("text/markdown")).await.unwrap();
+    assert_eq!(CONVERSIONS.load(Ordering::SeqCst), 3);
+
+    CONVERSIONS.store(0, Ordering::SeqCst);
+    client.conversion_timeout = Duration::ZERO;
+    let e = client.upload_attachment(&other).await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::ConversionIncomplete { .. }), "{:?}", e);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "attachments")]
+static SCANS: AtomicUsize = AtomicUsize::new(0);
+
+/// Never extracts any text, like the conversion of a scanned PDF.
+#[cfg(feature = "attachments")]
+fn scanned(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/convert_document") => {
+            SCANS.fetch_add(1, Ordering::SeqCst);
+            (200, serde_json::json!({ "file_name": "scan.pdf", "extracted_content": "" }).to_string())
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[cfg(feature = "attachments")]
+#[tokio::test]
+async fn documents_without_text_are_uploaded_a_bounded_number_of_times() {
+    let mut client = Client::builder("sessionKey=test").base_url(serve(scanned).await).build().await.unwrap();
+    client.max_conversion_attempts = 2;
+    let e = client.upload_attachment_bytes("scan.pdf", "%PDF".into(), Some("application/pdf")).await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::ConversionIncomplete { attempts: 2, .. }), "{:?}", e);
+    assert_eq!(SCANS.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "attachments")]
+static CACHED_CONVERSIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "attachments")]
+fn counting_conversions(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/convert_document") => {
+            CACHED_CONVERSIONS.fetch_add(1, Ordering::SeqCst);
+            (200, serde_json::json!({ "file_name": "note.txt", "extracted_content": "note" }).to_string())
+        }
+        _ => claude_ai(method, path),
+    }
+}
+
+#[cfg(feature = "attachments")]
+#[tokio::test]
+async fn attachment_cache_keeps_the_most_recent_documents() {
+    let client = Client::builder("sessionKey=test")
+        .base_url(serve(counting_conversions).await)
+        .attachment_cache_capacity(2)
+        .build().await
+        .unwrap();
+    for contents in ["first", "second", "first", "third", "first"] {
+        client.upload_attachment_bytes("note.txt", contents.into(), None).await.unwrap();
+    }
+    assert_eq!(CACHED_CONVERSIONS.load(Ordering::SeqCst), 3);
+
+    // "second" was the least recently used when "third" was cached.
+    client.upload_attachment_bytes("note.txt", "second".into(), None).await.unwrap();
+    assert_eq!(CACHED_CONVERSIONS.load(Ordering::SeqCst), 4);
+}
+
+#[cfg(feature = "attachments")]
+static PARTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Converts documents immediately and answers messages.
+#[cfg(feature = "attachments")]
+fn converting(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/api/convert_document") => {
+            PARTS.fetch_add(1, Ordering::SeqCst);
+            (200, serde_json::json!({ "file_name": "part.csv", "extracted_content": "part" }).to_string())
+        }
+        _ => answering(method, path),
+    }
+}
+
+#[cfg(feature = "attachments")]
+#[tokio::test]
+async fn oversized_text_attachments_are_split() {
+    let dir = std::env::temp_dir().join(format!("claude-split-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let csv = dir.join("people.csv");
+    let rows: String = (0..20).map(|i| format!("person {},{}\n", i, 20 + i)).collect();
+    std::fs::write(&csv, format!("name,age\n{}", rows)).unwrap();
+
+    let client = Client::builder("sessionKey=test")
+        .base_url(serve(converting).await)
+        .max_attachment_size(100)
+        .build().await
+        .unwrap();
+    client.send_message("chat", "Who is oldest?", Some(vec![csv.to_str().unwrap()]), None).await.unwrap();
+    // Each part holds the 9-byte header and 7 rows of 12 or 13 bytes.
+    assert_eq!(PARTS.load(Ordering::SeqCst), 3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Accepts image uploads and answers messages.
+#[cfg(feature = "attachments")]
+fn vision(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", p) if p.ends_with("/upload") =>
+            (200, serde_json::json!({ "file_uuid": "f-1", "file_name": "pixel.png", "file_kind": "image" }).to_string()),
+        _ => answering(method, path),
+    }
+}
+
+#[cfg(feature = "attachments")]
+#[tokio::test]
+async fn images_are_uploaded_for_vision_models() {
+    let dir = std::env::temp_dir().join(format!("claude-vision-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // Without an extension, the image is recognized by its signature.
+    let image = dir.join("pixel");
+    std::fs::write(&image, b"\x89PNG\r\n\x1a\n").unwrap();
+    let image = image.to_str().unwrap();
+
+    let progress = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let reported = progress.clone();
+    let mut client = Client::builder("sessionKey=test")
+        .base_url(serve(vision).await)
+        .upload_progress(move |progress| reported.lock().unwrap().push((progress.sent, progress.total)))
+        .build().await
+        .unwrap();
+    let uploaded = client.upload_image(image).await.unwrap();
+    assert_eq!((uploaded.file_uuid.as_str(), uploaded.file_kind.as_str()), ("f-1", "image"));
+    assert_eq!(progress.lock().unwrap().last(), Some(&(8, 8)));
+
+    let e = client.send_message("chat", "What is this?", Some(vec![image]), None).await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::ModelUnavailable { .. }), "{:?}", e);
+    client.default_model = claude::Model::Claude35Sonnet;
+    let answer = client.send_message("chat", "What is this?", Some(vec![image]), None).await.unwrap();
+    assert_eq!(answer, "Hello! How can I help you today?");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn messages_api(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/v1/messages") =>
+            (
+                200,
+                serde_json::json!({
+                    "content": [{ "type": "text", "text": "Hi!" }],
+                    "model": "claude-3-5-sonnet-20240620",
+                }).to_string(),
+            ),
+        _ => (404, String::new()),
+    }
+}
+
+#[tokio::test]
+async fn api_client_sends_the_whole_history() {
+    let base_url = serve(messages_api).await;
+    let client = ApiClient::new("test-key").unwrap().with_base_url(base_url).unwrap();
+    let chat = client.create_new_chat();
+
+    assert_eq!(client.send_message(&chat.uuid, "Hello", None, None).await.unwrap(), "Hi!");
+    client.send_message(&chat.uuid, "Hello again", None, None).await.unwrap();
+
+    let history = client.chat_conversation_history(&chat.uuid).unwrap();
+    let texts: Vec<_> = history.iter().map(|m| (m.sender.as_str(), m.text.as_str())).collect();
+    assert_eq!(texts, [("human", "Hello"), ("assistant", "Hi!"), ("human", "Hello again"), ("assistant", "Hi!")]);
+}
+
+#[tokio::test]
+async fn prompts_over_the_context_window_are_not_sent() {
+    // Nothing listens at this address, so any request would fail with a transport error.
+    let client = ApiClient::new("test-key").unwrap().with_base_url("http://127.0.0.1:9").unwrap();
+    let chat = client.create_new_chat();
+
+    let e = client.send_message(&chat.uuid, &"a".repeat(1_000_000), None, None).await.unwrap_err();
+    match e.root_cause() {
+        Error::PromptTooLong { estimated, limit } => assert_eq!((*estimated, *limit), (250_000, 200_000 - 4096)),
+        e => panic!("expected PromptTooLong, got {:?}", e),
+    }
+    assert!(client.chat_conversation_history(&chat.uuid).unwrap().is_empty());
+}
+
+async fn greet(backend: &impl ClaudeBackend) -> claude::Result<(String, usize)> {
+    let chat = backend.create_conversation().await?;
+    let answer = backend.send_message(&chat.uuid, "Hello", &[]).await?;
+    Ok((answer, backend.conversation_history(&chat.uuid).await?.len()))
+}
+
+#[tokio::test]
+async fn backends_are_interchangeable() {
+    let client = ApiClient::new("test-key").unwrap().with_base_url(serve(messages_api).await).unwrap();
+    assert_eq!(greet(&client).await.unwrap(), ("Hi!".to_string(), 2));
+}
+
+/// Serves the same conversation for every UUID in the list.
+#[cfg(feature = "export")]
+fn backing_up(method: &str, path: &str) -> (u16, String) {
+    match (method, path) {
+        ("GET", p) if p.contains("/chat_conversations/") => (200, fixture("chat_conversation.json")),
+        _ => claude_ai(method, path),
+    }
+}
+
+#[cfg(feature = "export")]
+#[tokio::test]
+async fn account_archives_hold_one_document_per_conversation() {
+    use std::io::Read;
+
+    let client = Client::builder("sessionKey=test").base_url(serve(backing_up).await).build().await.unwrap();
+    let archive = client.export_account_archive(Vec::new(), claude::export::ExportFormat::Markdown).await.unwrap();
+
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive.as_slice()));
+    let mut documents = vec![];
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let mut document = String::new();
+        entry.read_to_string(&mut document).unwrap();
+        assert_eq!(entry.header().size().unwrap(), document.len() as u64);
+        documents.push((entry.path().unwrap().display().to_string(), document));
+    }
+    assert_eq!(documents.len(), 2);
+    assert_eq!(documents[0].0, "e56a5ab3-0eca-4a04-9c63-3fadaf14cd17.md");
+    assert!(documents[0].1.starts_with("# CV review"));
+    assert!(documents.iter().all(|(_, document)| document.contains("Here are a few suggestions")));
+}
+
+/// Serves the organizations, then answers every other request with headers and a body that never arrives.
+async fn serve_stalling_bodies() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let response = if String::from_utf8_lossy(&buf[..n]).starts_with("GET /api/organizations ") {
+                    let body = fixture("organizations.json");
+                    format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body)
+                } else {
+                    "HTTP/1.1 200 OK\r\ncontent-length: 1000\r\n\r\n[".to_string()
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            });
+        }
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn deadlines_bound_body_reads() {
+    let client = Client::builder("sessionKey=test").base_url(serve_stalling_bodies().await).build().await.unwrap();
+    let deadline = std::time::Instant::now() + Duration::from_millis(200);
+    let e = claude::with_deadline(deadline, client.list_all_conversations()).await.unwrap_err();
+    assert!(matches!(e.root_cause(), Error::DeadlineExceeded));
+}
@@ -0,0 +1,168 @@
+use claude::{ sse::{ parse_completions, Event, EventDecoder, EventStreamParser, StreamEvent }, ChatMessage, Conversation };
+use proptest::prelude::*;
+use std::{ fs, path::Path };
+
+/// Every `*.txt` file in the corpus is a captured or hand-crafted event stream; its `*.expected`
+/// sibling holds the answer the parser must recover from it.
+#[test]
+fn corpus_streams_parse_to_expected_answers() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/sse");
+    let mut checked = 0;
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("txt") {
+            continue;
+        }
+        let body = String::from_utf8_lossy(&fs::read(&path).unwrap()).into_owned();
+        let expected = fs::read_to_string(path.with_extension("expected")).unwrap();
+        assert_eq!(parse_completions(&body).concat(), expected, "{}", path.display());
+        checked += 1;
+    }
+    assert!(checked > 0, "the SSE corpus is empty");
+}
+
+#[test]
+fn event_stream_fields() {
+    let mut parser = EventStreamParser::default();
+    let mut events = parser.push(b"event: completion\rdata: first\r");
+    events.extend(parser.push(b"\ndata:second\r\n\r"));
+    events.extend(parser.push(b"\n: comment\n\nid: 7\ndata\n\nevent: empty\n\ndata: last"));
+    events.extend(parser.finish());
+    assert_eq!(events, vec![
+        Event { event: Some("completion".to_string()), data: "first\nsecond".to_string(), id: None },
+        Event { event: None, data: String::new(), id: Some("7".to_string()) },
+        Event { event: None, data: "last".to_string(), id: Some("7".to_string()) },
+    ]);
+}
+
+#[test]
+fn crlf_split_across_chunks_ends_one_line() {
+    let mut parser = EventStreamParser::default();
+    let mut events = parser.push(b"data: x\r");
+    events.extend(parser.push(b"\n"));
+    assert_eq!(events, vec![]);
+    events.extend(parser.push(b"\n"));
+    assert_eq!(events, vec![Event { event: None, data: "x".to_string(), id: None }]);
+}
+
+fn completion_line(text: &str) -> String {
+    format!("data: {}", serde_json::json!({ "completion": text }))
+}
+
+proptest! {
+    #[test]
+    fn parser_never_panics(body in any::<String>()) {
+        parse_completions(&body);
+    }
+
+    #[test]
+    fn parser_never_panics_on_event_like_lines(
+        lines in prop::collection::vec("(data|event|id|:)?:? ?[{}\"a-z:,0-9\\[\\] ]{0,40}", 0..20)
+    ) {
+        parse_completions(&lines.join("\n"));
+    }
+
+    #[test]
+    fn garbage_lines_do_not_hide_completions(
+        chunks in prop::collection::vec(("[^\r\n]{0,30}", "[^\r\n]{0,30}"), 0..20)
+    ) {
+        let mut body = String::new();
+        let mut expected = Vec::new();
+        for (text, garbage) in &chunks {
+            body.push_str(&completion_line(text));
+            body.push_str("\n\n");
+            // Prefixing with "x" guarantees the garbage can never be a valid data line.
+            body.push('x');
+            body.push_str(garbage);
+            body.push('\n');
+            expected.push(text.clone());
+        }
+        prop_assert_eq!(parse_completions(&body), expected);
+    }
+
+    #[test]
+    fn crlf_line_endings_are_supported(texts in prop::collection::vec("[^\r\n]{0,30}", 0..10)) {
+        let body: String = texts
+            .iter()
+            .map(|t| format!("{}\r\n\r\n", completion_line(t)))
+            .collect();
+        prop_assert_eq!(parse_completions(&body), texts);
+    }
+
+    #[test]
+    fn decoder_matches_parser_whatever_the_chunking(
+        texts in prop::collection::vec("[^\r\n]{0,30}", 0..10),
+        cuts in prop::collection::vec(any::<prop::sample::Index>(), 0..10)
+    ) {
+        let body: String = texts.iter().map(|t| format!("{}\n\n", completion_line(t))).collect();
+        let bytes = body.as_bytes();
+        let mut cuts: Vec<usize> = cuts.iter().map(|i| i.index(bytes.len() + 1)).collect();
+        cuts.sort_unstable();
+
+        let mut decoder = EventDecoder::default();
+        let mut events = Vec::new();
+        let mut start = 0;
+        for cut in cuts.into_iter().chain(std::iter::once(bytes.len())) {
+            events.extend(decoder.push(&bytes[start..cut]));
+            start = cut;
+        }
+        events.extend(decoder.finish());
+
+        let expected: Vec<_> = parse_completions(&body).into_iter().map(StreamEvent::Completion).collect();
+        prop_assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn parser_events_do_not_depend_on_the_chunking(
+        lines in prop::collection::vec("(data|event|id|:)?:? ?[a-z{}:]{0,10}", 0..20),
+        separators in prop::collection::vec(prop_oneof![Just("\n"), Just("\r\n"), Just("\r")], 20),
+        cuts in prop::collection::vec(any::<prop::sample::Index>(), 0..10)
+    ) {
+        let body: String = lines.iter().zip(&separators).map(|(line, separator)| format!("{}{}", line, separator)).collect();
+        let bytes = body.as_bytes();
+        let mut whole = EventStreamParser::default();
+        let mut expected = whole.push(bytes);
+        expected.extend(whole.finish());
+
+        let mut cuts: Vec<usize> = cuts.iter().map(|i| i.index(bytes.len() + 1)).collect();
+        cuts.sort_unstable();
+        let mut parser = EventStreamParser::default();
+        let mut events = Vec::new();
+        let mut start = 0;
+        for cut in cuts.into_iter().chain(std::iter::once(bytes.len())) {
+            events.extend(parser.push(&bytes[start..cut]));
+            start = cut;
+        }
+        events.extend(parser.finish());
+        prop_assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn payload_deserializers_never_panic(body in any::<String>()) {
+        let _ = serde_json::from_str::<ChatMessage>(&body);
+        let _ = serde_json::from_str::<Conversation>(&body);
+        let _ = serde_json::from_str::<Vec<Conversation>>(&body);
+    }
+
+    #[test]
+    fn chat_messages_deserialize_from_arbitrary_field_values(
+        uuid in any::<String>(),
+        sender in any::<String>(),
+        index in any::<usize>(),
+        text in any::<String>(),
+        feedback in proptest::option::of(any::<String>())
+    ) {
+        let json = serde_json::json!({
+            "uuid": uuid,
+            "attachments": [],
+            "sender": sender,
+            "index": index,
+            "text": text,
+            "chat_feedback": feedback,
+        });
+        let message: ChatMessage = serde_json::from_value(json).unwrap();
+        prop_assert_eq!(message.sender.as_str(), sender);
+        prop_assert_eq!(message.text, text);
+        prop_assert_eq!(message.chat_feedback, feedback);
+    }
+}
@@ -0,0 +1,41 @@
+use claude::{ throttle, StreamEvent };
+use futures_util::{ stream, StreamExt };
+use std::time::{ Duration, Instant };
+
+fn answer(fragments: &[&str]) -> impl futures_util::Stream<Item = claude::Result<StreamEvent>> {
+    let mut events: Vec<_> = fragments
+        .iter()
+        .map(|f| Ok(StreamEvent::Completion(f.to_string())))
+        .collect();
+    events.push(Ok(StreamEvent::Stop("stop_sequence".to_string())));
+    stream::iter(events)
+}
+
+#[tokio::test]
+async fn typewriter_yields_one_character_per_interval() {
+    let started = Instant::now();
+    let events: Vec<_> = throttle::typewriter(answer(&["Hé", "llo"]), Duration::from_millis(10))
+        .map(Result::unwrap)
+        .collect().await;
+
+    let chars: String = events
+        .iter()
+        .filter_map(|e| match e {
+            StreamEvent::Completion(c) => Some(c.as_str()),
+            StreamEvent::Stop(_) => None,
+        })
+        .collect();
+    assert_eq!(chars, "Héllo");
+    assert_eq!(events.len(), 6);
+    assert_eq!(events[5], StreamEvent::Stop("stop_sequence".to_string()));
+    assert!(started.elapsed() >= Duration::from_millis(40));
+}
+
+#[tokio::test]
+async fn debounce_always_ends_with_the_whole_answer() {
+    let renders: Vec<_> = throttle::debounce(answer(&["a", "b", "c"]), Duration::from_secs(60))
+        .map(Result::unwrap)
+        .collect().await;
+    assert_eq!(renders.last().map(String::as_str), Some("abc"));
+    assert!(renders.len() <= 2);
+}
@@ -0,0 +1,63 @@
+use claude::{ truncation::{ truncate, TruncationPolicy }, utils::split_text };
+use proptest::prelude::*;
+use std::ops::Range;
+
+#[test]
+fn head_and_tail_truncation() {
+    let (kept, dropped) = truncate("abcdéf", 4, TruncationPolicy::TruncateTail);
+    assert_eq!((kept.as_str(), dropped), ("abcd", vec![Range { start: 4, end: 6 }]));
+    let (kept, dropped) = truncate("abcdéf", 4, TruncationPolicy::TruncateHead);
+    assert_eq!((kept.as_str(), dropped), ("cdéf", vec![Range { start: 0, end: 2 }]));
+    assert_eq!(truncate("abc", 4, TruncationPolicy::TruncateHead), ("abc".to_string(), vec![]));
+}
+
+#[test]
+fn sampling_keeps_the_first_and_last_sections() {
+    let sections: Vec<String> = (0..10).map(|i| format!("Section {}\n\n", i)).collect();
+    let text = sections.concat();
+    let (kept, dropped) = truncate(&text, 60, TruncationPolicy::Sample);
+    assert!(kept.starts_with("Section 0\n\n"));
+    assert!(kept.contains("Section 9"));
+    assert!(kept.contains("[...]"));
+    assert!(!dropped.is_empty());
+}
+
+proptest! {
+    #[test]
+    fn truncated_text_fits_the_budget(
+        text in "([a-z ]{0,20}\n{0,2}){0,30}",
+        budget in 0usize..200,
+        policy in prop_oneof![
+            Just(TruncationPolicy::TruncateHead),
+            Just(TruncationPolicy::TruncateTail),
+            Just(TruncationPolicy::Sample),
+        ]
+    ) {
+        let (kept, dropped) = truncate(&text, budget, policy);
+        prop_assert!(kept.chars().count() <= budget);
+        let dropped_chars: usize = dropped.iter().map(|range| range.len()).sum();
+        if text.chars().count() <= budget {
+            prop_assert_eq!(kept, text);
+        } else {
+            prop_assert!(dropped_chars > 0);
+        }
+    }
+
+    #[test]
+    fn split_parts_fit_and_rebuild_the_text(
+        text in "([a-zé,]{0,30}\n?){0,40}",
+        max_bytes in 4usize..80,
+        repeat_header in any::<bool>()
+    ) {
+        let parts = split_text(&text, max_bytes, repeat_header);
+        prop_assert!(parts.iter().all(|part| part.len() <= max_bytes || parts.len() == 1));
+        let header = text.split_inclusive('\n').next().unwrap_or("");
+        let header = if repeat_header && parts.len() > 1 && header.len() < max_bytes / 2 { header } else { "" };
+        let rebuilt: String = parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| if i == 0 { part.as_str() } else { &part[header.len()..] })
+            .collect();
+        prop_assert_eq!(rebuilt, text);
+    }
+}
@@ -0,0 +1,135 @@
+//! Snapshot tests over recorded, sanitized claude.ai responses.
+//!
+//! Each fixture in `tests/fixtures` is a real response body for one endpoint. If claude.ai changes its
+//! schema, refresh the fixture and these tests show exactly which parsed fields changed. The fixtures in
+//! `tests/fixtures/synthetic` are hand-written instead, to cover fields the recorded responses lack.
+
+use claude::{
+    payload::{ AppendMessageRequest, CompletionParams },
+    sse::parse_completions,
+    utils::conversation_checksum,
+    ChatMessage,
+    Conversation,
+    ModelInfo,
+    Organization,
+    UploadedAttachment,
+};
+use serde::Deserialize;
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path, e))
+}
+
+#[test]
+fn organizations() {
+    let orgs: Vec<Organization> = serde_json::from_str(&fixture("organizations.json")).unwrap();
+    insta::assert_json_snapshot!(orgs);
+}
+
+#[test]
+fn list_models() {
+    let models: Vec<ModelInfo> = serde_json::from_str(&fixture("models.json")).unwrap();
+    insta::assert_json_snapshot!(models);
+}
+
+#[test]
+fn list_conversations() {
+    let conversations: Vec<Conversation> = serde_json
+        ::from_str(&fixture("chat_conversations.json"))
+        .unwrap();
+    insta::assert_json_snapshot!(conversations);
+}
+
+/// Fields of listed conversations missing from the recorded listing.
+#[test]
+fn list_conversations_synthetic() {
+    let conversations: Vec<Conversation> = serde_json
+        ::from_str(&fixture("synthetic/chat_conversations.json"))
+        .unwrap();
+    insta::assert_json_snapshot!(conversations);
+}
+
+#[test]
+fn create_conversation() {
+    let conversation: Conversation = serde_json
+        ::from_str(&fixture("create_chat_conversation.json"))
+        .unwrap();
+    insta::assert_json_snapshot!(conversation);
+}
+
+/// Parses a conversation response into the conversation and its messages.
+fn conversation_and_messages(body: &str) -> (Conversation, Vec<ChatMessage>) {
+    #[derive(Deserialize)]
+    struct History {
+        chat_messages: Vec<ChatMessage>,
+    }
+
+    let mut conversation: Conversation = serde_json::from_str(body).unwrap();
+    conversation.extra.remove("chat_messages");
+    let history: History = serde_json::from_str(body).unwrap();
+    (conversation, history.chat_messages)
+}
+
+#[test]
+fn conversation_history() {
+    let (conversation, messages) = conversation_and_messages(&fixture("chat_conversation.json"));
+    insta::assert_json_snapshot!((conversation, messages));
+}
+
+/// Fields of conversations and messages missing from the recorded conversation.
+#[test]
+fn conversation_history_synthetic() {
+    let (conversation, messages) = conversation_and_messages(&fixture("synthetic/chat_conversation.json"));
+    insta::assert_json_snapshot!((conversation, messages));
+}
+
+/// Checksums are compared with ones computed by earlier releases, so they must never change.
+#[test]
+fn conversation_checksum_is_stable() {
+    #[derive(Deserialize)]
+    struct History {
+        chat_messages: Vec<ChatMessage>,
+    }
+
+    let mut messages = serde_json::from_str::<History>(&fixture("chat_conversation.json")).unwrap().chat_messages;
+    let checksum = conversation_checksum(&messages);
+    insta::assert_snapshot!(checksum);
+
+    messages[0].chat_feedback = Some("upvote".to_string());
+    assert_eq!(conversation_checksum(&messages), checksum);
+    messages[0].text.push('!');
+    assert_ne!(conversation_checksum(&messages), checksum);
+}
+
+#[test]
+fn convert_document() {
+    let body = fixture("convert_document.json");
+    let document: UploadedAttachment = serde_json::from_str(&body).unwrap();
+    insta::assert_json_snapshot!(document);
+
+    // The document is sent back with the message, so no field may be lost on the way.
+    let original: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(serde_json::to_value(&document).unwrap(), original);
+}
+
+#[test]
+fn append_message() {
+    insta::assert_json_snapshot!(parse_completions(&fixture("append_message.txt")));
+}
+
+/// The body sent by `send_message`, with a converted document and an image attached.
+#[test]
+fn append_message_request() {
+    let document: UploadedAttachment = serde_json::from_str(&fixture("convert_document.json")).unwrap();
+    let request = AppendMessageRequest {
+        completion: CompletionParams { prompt: "Summarize this", timezone: "Asia/Saigon", model: "claude-2" },
+        organization_uuid: "0f6b7a34-9c7e-4c4f-9a1e-2f4a1e0d5b11",
+        conversation_uuid: "e56a5ab3-0eca-4a04-9c63-3fadaf14cd17",
+        parent_message_uuid: None,
+        text: "Summarize this",
+        attachments: &[document],
+        files: vec!["4e2d1c0b-9a8f-4e7d-b6c5-a4b3c2d1e0f9"],
+    };
+    insta::assert_json_snapshot!(request);
+}